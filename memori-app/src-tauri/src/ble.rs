@@ -1,96 +1,138 @@
-// use crate::widget_data::refresh_bus_widget;
-use crate::widget_data::github_data::refresh_github_widget;
-// use crate::widget_data::refresh_twitch_widget;
-// use crate::widget_data::refresh_weather_widget;
-use memori_ui::widgets::MemoriWidget;
-use memori_ui::widgets::WidgetId;
-use memori_ui::widgets::WidgetKind;
+use crate::device_commands::handle_device_command;
+use crate::state::{AppState, DeviceConnection};
+use crate::widget_data::clock_data::current_time;
+use ble_host::{ConnectionState, DiscoveredDevice};
+use memori_ui::battery::BatteryStatus;
 use memori_ui::MemoriState;
+use serde::{Deserialize, Serialize};
+use specta::Type;
 use std::sync::Arc;
-use tauri::AppHandle;
+use tauri::{AppHandle, Manager};
+use tauri_specta::Event;
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
-use tokio::sync::RwLock;
-use transport::ble_types::*;
-use transport::TransError;
+use tokio::sync::{watch, RwLock};
+use transport::protocol::{DeviceCommand, HostResponse};
+use transport::HostTransport as _;
+
+/// Sent to the frontend whenever the bluetooth link goes down or comes back.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Type, Event)]
+pub enum ConnectionStatus {
+    Connected,
+    Reconnecting { attempt: u32 },
+    Disconnected,
+}
+
+impl From<ConnectionState> for ConnectionStatus {
+    fn from(state: ConnectionState) -> Self {
+        match state {
+            ConnectionState::Connected => ConnectionStatus::Connected,
+            ConnectionState::Reconnecting { attempt } => ConnectionStatus::Reconnecting { attempt },
+            ConnectionState::Disconnected => ConnectionStatus::Disconnected,
+        }
+    }
+}
+
+/// Sent to the frontend whenever the device's battery level or charging changes.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Type, Event)]
+pub struct DeviceBattery(pub BatteryStatus);
+
+/// A Memori device seen while scanning, sent to the frontend as soon as it shows up.
+#[derive(Serialize, Deserialize, Debug, Clone, Type, Event)]
+pub struct NearbyDevice {
+    pub name: Option<String>,
+    pub code: Option<String>,
+    pub address: String,
+    pub rssi: Option<i16>,
+}
+
+impl From<DiscoveredDevice> for NearbyDevice {
+    fn from(device: DiscoveredDevice) -> Self {
+        Self {
+            name: device.name,
+            code: device.code,
+            address: device.address,
+            rssi: device.rssi,
+        }
+    }
+}
 
 // async task that runs in the background when we have an active connection and
 // lets us see and choose how to handle incoming requests from the device
 pub async fn ble_request_handler(
     memori: Arc<RwLock<Option<MemoriState>>>,
-    mut dev_cmd_rx: UnboundedReceiver<DeviceBLECommand>,
-    host_resp_tx: UnboundedSender<HostBLEResponse>,
+    mut dev_cmd_rx: UnboundedReceiver<DeviceCommand>,
+    host_resp_tx: UnboundedSender<HostResponse>,
     app_handle: &AppHandle,
 ) {
     while let Some(cmd) = dev_cmd_rx.recv().await {
         println!("received command from device {cmd:#?}");
-        let resp = match cmd {
-            DeviceBLECommand::RefreshData { widget_id } => {
-                handle_refresh_data(&memori, widget_id, app_handle).await
-            }
-            DeviceBLECommand::Ping => HostBLEResponse::Ping { result: Ok(()) },
-        };
+        let resp = handle_device_command(&memori, cmd, app_handle).await;
         host_resp_tx.send(resp).unwrap();
     }
 }
 
-// takes a widgetid and updates it based on the current state, returing a
-// response object to send back to the device.
-async fn handle_refresh_data(
-    memori: &RwLock<Option<MemoriState>>,
-    widget_id: WidgetId,
-    app_handle: &AppHandle,
-) -> HostBLEResponse {
-    let guard = memori.read().await;
+// forwards connection state changes to the frontend, and once the device is back
+// after dropping, gives it the time and the state it had since it may have restarted since.
+// runs until the transport is gone.
+pub async fn ble_connection_watcher(app: AppHandle, mut states: watch::Receiver<ConnectionState>) {
+    let mut was_reconnecting = false;
 
-    let state = match &*guard {
-        Some(s) => s,
-        None => {
-            return HostBLEResponse::RefreshData {
-                result: Err(TransError::InternalError),
+    while states.changed().await.is_ok() {
+        let connection_state = *states.borrow_and_update();
+        println!("[ble] connection state changed: {connection_state:?}");
+
+        if let Err(e) = ConnectionStatus::from(connection_state).emit(&app) {
+            eprintln!("[ble] failed to emit connection status: {e}");
+        }
+
+        match connection_state {
+            ConnectionState::Reconnecting { .. } => was_reconnecting = true,
+            ConnectionState::Connected if was_reconnecting => {
+                was_reconnecting = false;
+                resend_state(&app).await;
             }
+            _ => {}
         }
-    };
+    }
+}
 
-    let widget = match state.widgets.get(&widget_id) {
-        Some(w) => w,
-        None => {
-            return HostBLEResponse::RefreshData {
-                result: Err(TransError::InternalError),
+// forwards the battery to the frontend whenever the device tells us it changed,
+// so nothing has to poll get_battery. runs until the transport is gone.
+pub async fn ble_battery_watcher(
+    app: AppHandle,
+    mut battery: watch::Receiver<Option<BatteryStatus>>,
+) {
+    loop {
+        let status = *battery.borrow_and_update();
+        if let Some(status) = status {
+            println!("[ble] battery: {status:?}");
+            if let Err(e) = DeviceBattery(status).emit(&app) {
+                eprintln!("[ble] failed to emit battery status: {e}");
             }
         }
-    };
 
-    // log
-    let refresh_result: Result<Box<MemoriWidget>, String> = match widget.kind {
-        //WidgetKind::Twitch(_) => refresh_twitch_widget().await,
-        WidgetKind::Github(_) => Ok(Box::new(MemoriWidget::new(
-            widget.id,
-            WidgetKind::Github(refresh_github_widget(app_handle).await.unwrap()),
-            widget.get_remote_update_frequency(),
-            widget.get_local_update_frequency(),
-        ))),
-        //   WidgetKind::Bus(_) => refresh_bus_widget().await,
-        // WidgetKind::Weather(_) => refresh_temp().await,
-        _ => Err("invalid refresh branch".to_string()),
-    };
+        if battery.changed().await.is_err() {
+            break;
+        }
+    }
+}
 
-    match refresh_result {
-        Ok(new_widget) => {
-            let updated_kind = new_widget.kind;
+async fn resend_state(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let Some(memori) = state.memori.read().await.clone() else {
+        return;
+    };
 
-            let updated_widget = MemoriWidget::new(
-                widget.id,
-                updated_kind,
-                widget.get_remote_update_frequency(),
-                widget.get_local_update_frequency(),
-            );
+    let mut conn = state.conn.lock().await;
+    if let DeviceConnection::RealDevice(transport) = &mut *conn {
+        let (utc_epoch_secs, utc_offset_minutes) = current_time();
+        if let Err(e) = transport.sync_time(utc_epoch_secs, utc_offset_minutes).await {
+            eprintln!("[ble] failed to sync the time after reconnecting: {e}");
+        }
 
-            HostBLEResponse::RefreshData {
-                result: Ok(updated_widget),
-            }
+        match transport.set_state(memori).await {
+            Ok(()) => println!("[ble] resent state after reconnecting"),
+            Err(e) => eprintln!("[ble] failed to resend state after reconnecting: {e}"),
         }
-        Err(_) => HostBLEResponse::RefreshData {
-            result: Err(TransError::InternalError),
-        },
     }
 }
@@ -1,10 +1,21 @@
+use crate::scheduler::widget_scheduler;
 use crate::simulator::request_handler;
 use crate::state::{AppState, DeviceConnection, DeviceMode};
+use crate::widget_data::clock_data::current_time;
 use ble_host::HostBLETransport;
 use memori_tcp::HostTcpTransport;
+use std::collections::HashMap;
+use std::time::Duration;
 use tauri::{AppHandle, State};
-use crate::ble::ble_request_handler;
-use transport::HostTransport as _;
+use tauri_specta::Event as _;
+use crate::ble::{ble_battery_watcher, ble_connection_watcher, ble_request_handler, NearbyDevice};
+use memori_ui::{DISPLAY_HEIGHT, DISPLAY_WIDTH};
+use transport::{Capabilities, HostTransport as _};
+
+/// What the app tells the device it supports when connecting.
+fn app_capabilities() -> Capabilities {
+    Capabilities::new(env!("CARGO_PKG_VERSION"), DISPLAY_WIDTH, DISPLAY_HEIGHT)
+}
 
 #[tauri::command]
 #[specta::specta]
@@ -22,15 +33,25 @@ pub async fn connect_device(
         return Err("Already connected. Disconnect first.".to_string());
     }
 
-    match mode {
+    let scheduler_app = app.clone();
+
+    let result = match mode {
         DeviceMode::RealDevice => {
-            let (conn, address, (dev_req_rx, host_resp_tx)) = HostBLETransport::connect(code, known_address.as_deref())
+            let (mut conn, address, (dev_req_rx, host_resp_tx)) = HostBLETransport::connect(code, known_address.as_deref(), app_capabilities())
                 .await
                 .map_err(|e| {
                     eprintln!("[ble-host] failed to connect: {e}");
                     format!("Failed to connect to device: {e}")
                 })?;
             
+            let (utc_epoch_secs, utc_offset_minutes) = current_time();
+            if let Err(e) = conn.sync_time(utc_epoch_secs, utc_offset_minutes).await {
+                eprintln!("[ble-host] failed to sync the time: {e}");
+            }
+
+            tokio::spawn(ble_connection_watcher(app.clone(), conn.connection_state()));
+            tokio::spawn(ble_battery_watcher(app.clone(), conn.battery()));
+
             tokio::spawn(async move {
                 ble_request_handler(memori, dev_req_rx, host_resp_tx, &app).await;
             });
@@ -41,22 +62,59 @@ pub async fn connect_device(
             Ok(address)
         }
         DeviceMode::Simulator => {
-            let transport = HostTcpTransport::default();
-            let (conn, (dev_req_rx, host_resp_tx)) = transport
+            let transport = HostTcpTransport::with_capabilities(app_capabilities());
+            let (mut conn, (dev_req_rx, host_resp_tx)) = transport
                 .connect()
                 .await
                 .map_err(|e| format!("Failed to connect to simulator: {e}"))?;
 
+            let (utc_epoch_secs, utc_offset_minutes) = current_time();
+            if let Err(e) = conn.sync_time(utc_epoch_secs, utc_offset_minutes).await {
+                eprintln!("[simulator] failed to sync the time: {e}");
+            }
+
             *guard = DeviceConnection::Simulator(conn);
 
             tokio::spawn(async move {
-                request_handler(dev_req_rx, host_resp_tx).await;
+                request_handler(memori, dev_req_rx, host_resp_tx, &app).await;
             });
 
             println!("Connected to simulator over TCP");
             Ok("".to_string())
         }
+    };
+
+    if result.is_ok() {
+        let scheduler = tokio::spawn(widget_scheduler(scheduler_app));
+        if let Some(old) = state.scheduler.lock().await.replace(scheduler) {
+            old.abort();
+        }
     }
+
+    result
+}
+
+/// Scans for nearby Memori devices for `seconds`, emitting a `NearbyDevice` event
+/// as they are found and returning everything that was seen, closest first.
+#[tauri::command]
+#[specta::specta]
+pub async fn scan_devices(app: AppHandle, seconds: u32) -> Result<Vec<NearbyDevice>, String> {
+    let mut found = ble_host::scan_devices(Duration::from_secs(seconds.into()))
+        .await
+        .map_err(|e| format!("Failed to scan for devices: {e}"))?;
+
+    let mut devices: HashMap<String, NearbyDevice> = HashMap::new();
+    while let Some(device) = found.recv().await {
+        let device = NearbyDevice::from(device);
+        if let Err(e) = device.clone().emit(&app) {
+            eprintln!("[ble] failed to emit nearby device: {e}");
+        }
+        devices.insert(device.address.clone(), device);
+    }
+
+    let mut devices: Vec<NearbyDevice> = devices.into_values().collect();
+    devices.sort_by_key(|device| std::cmp::Reverse(device.rssi.unwrap_or(i16::MIN)));
+    Ok(devices)
 }
 
 #[tauri::command]
@@ -66,6 +124,10 @@ pub async fn disconnect_device(state: State<'_, AppState>) -> Result<(), String>
 
     let old_connection = std::mem::replace(&mut *guard, DeviceConnection::Disconnected);
 
+    if let Some(scheduler) = state.scheduler.lock().await.take() {
+        scheduler.abort();
+    }
+
     match old_connection {
         DeviceConnection::RealDevice(transport) => {
             transport.disconnect().await;
@@ -1,12 +1,8 @@
 use crate::commands::translation_structs::*;
 use crate::state::{AppState, DeviceConnection};
-use crate::widget_data::bus_data::{refresh_bus_widget, bus_to_memori_widget};
-use crate::widget_data::clock_data::{refresh_clock_widget, clock_to_memori_widget};
-use crate::widget_data::github_data::{github_to_memori_widget, refresh_github_widget};
-use crate::widget_data::twitch_data::{refresh_twitch_widget, twitch_to_memori_widget};
-use crate::widget_data::weather_data::{refresh_weather_widget, weather_to_memori_widget};
-use memori_ui::widgets::{WidgetKind};
-use memori_ui::widgets::Name;
+use crate::widget_data::PROVIDERS;
+use memori_snapshot::Snapshot;
+use memori_ui::widgets::WidgetId;
 use memori_ui::{widgets::MemoriWidget, MemoriState};
 use serde::de::DeserializeOwned;
 use tauri::{AppHandle, State};
@@ -18,24 +14,13 @@ impl MemoriStateInput {
         let active_frame_idx = usize::try_from(self.active_frame_idx)
             .map_err(|_| "activeFrameIdx is out of range for this platform".to_string())?;
 
-        if self.frames.is_empty() {
-            return Err("frames cannot be empty".to_string());
-        }
-
-        if active_frame_idx >= self.frames.len() {
-            return Err(format!(
-                "activeFrameIdx {} is out of bounds for {} frame(s)",
-                self.active_frame_idx,
-                self.frames.len()
-            ));
-        }
-
-        Ok(MemoriState::new(
+        MemoriState::try_new(
             active_frame_idx,
             self.widgets,
             self.frames,
             self.frame_time,
-        ))
+        )
+        .map_err(|e| e.to_string())
     }
 }
 
@@ -66,7 +51,7 @@ async fn set_memori_state(
         *memori_guard = Some(memori_state);
     }
 
-    Ok(())
+    result
 }
 
 pub fn read_store_state<T>(app: &AppHandle, store_id: &str) -> T
@@ -89,28 +74,30 @@ pub async fn flash_memori_state(
     set_memori_state(&state, memori_state.into_memori_state()?).await
 }
 
+/// Renders the active frame of the `MemoriStateInput` like the device would,
+/// returns the PNG bytes.
+///
+/// # Errors
+/// Could error if the state isn't valid.
+#[tauri::command]
+#[specta::specta]
+pub async fn preview_memori_state(
+    memori_state: MemoriStateInput,
+    dark_mode: bool,
+) -> Result<Vec<u8>, String> {
+    let memori_state = memori_state.into_memori_state()?;
+    let snapshot = Snapshot::render(&memori_state, dark_mode).map_err(|e| e.to_string())?;
+    Ok(snapshot.to_png())
+}
+
 /// Sends back a copy of the different widget types that are used during drag and drop.
 #[tauri::command]
 #[specta::specta]
-pub async fn get_widget_kinds(app: AppHandle) -> Result<[MemoriWidget; 6], String> {
+pub async fn get_widget_kinds(app: AppHandle) -> Result<Vec<MemoriWidget>, String> {
     println!("get_widget_kinds called");
-    let prefs: PrefsState = read_store_state(&app, "prefs");
-    let clock = refresh_clock_widget().await.unwrap_or_default();
-    let weather = refresh_weather_widget(36.97145812967173, -122.03535749883835).await.unwrap_or_default();
-    let bus = refresh_bus_widget().await.unwrap_or_default();
-    let github = refresh_github_widget(&app).await.unwrap_or_default();
-    let twitch = refresh_twitch_widget(&app).await.unwrap_or_default();
-    let name = memori_ui::widgets::Name { name: prefs.name.clone() };
-    println!("Weather: {:?}\n\n", weather);
-    println!("Twitch: {:?}\n\n", twitch);
-    println!("Bus: {:?}\n\n", bus);
-
-    Ok([
-        clock_to_memori_widget(1, clock).await.unwrap(),
-        weather_to_memori_widget(2, weather).await.unwrap(),
-        bus_to_memori_widget(3, bus).await.unwrap(),
-        github_to_memori_widget(4, github).await.unwrap(),
-        twitch_to_memori_widget(5, twitch).await.unwrap(),
-        MemoriWidget::with_never_update_frequency(6, WidgetKind::Name(name))
-    ])
+    let mut widgets = Vec::with_capacity(PROVIDERS.len());
+    for (id, provider) in (1..).zip(PROVIDERS) {
+        widgets.push(provider.default_instance(&app, WidgetId(id)).await?);
+    }
+    Ok(widgets)
 }
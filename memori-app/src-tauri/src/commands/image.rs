@@ -0,0 +1,100 @@
+use image::imageops::FilterType;
+use memori_ui::overlay::Bitmap;
+use memori_ui::widgets::SlotSize;
+use serde::Deserialize;
+
+/// How grays are turned into black and white pixels.
+#[derive(Debug, Clone, Copy, Deserialize, specta::Type)]
+pub enum DitherMethod {
+    /// Spreads all of a pixel's error to its neighbours, smooth gradients.
+    FloydSteinberg,
+    /// Spreads three quarters of the error, more contrast and less noise, the
+    /// classic look of small black and white screens.
+    Atkinson,
+}
+
+impl DitherMethod {
+    // (dx, dy, weight) of the neighbours that get part of a pixel's error
+    fn kernel(self) -> (&'static [(i32, i32, f32)], f32) {
+        match self {
+            Self::FloydSteinberg => (&[(1, 0, 7.0), (-1, 1, 3.0), (0, 1, 5.0), (1, 1, 1.0)], 16.0),
+            Self::Atkinson => (
+                &[
+                    (1, 0, 1.0),
+                    (2, 0, 1.0),
+                    (-1, 1, 1.0),
+                    (0, 1, 1.0),
+                    (1, 1, 1.0),
+                    (0, 2, 1.0),
+                ],
+                8.0,
+            ),
+        }
+    }
+}
+
+// brightness of every pixel from 0 (black) to 1 (white), transparent parts
+// count as white since that's the color of the display
+fn brightness(image: &image::RgbaImage) -> Vec<f32> {
+    image
+        .pixels()
+        .map(|p| {
+            let [r, g, b, a] = p.0.map(|c| f32::from(c) / 255.0);
+            let luma = 0.299 * r + 0.587 * g + 0.114 * b;
+            luma * a + (1.0 - a)
+        })
+        .collect()
+}
+
+fn dither(mut levels: Vec<f32>, width: u32, height: u32, method: DitherMethod) -> Bitmap {
+    let (kernel, divisor) = method.kernel();
+    let mut bitmap = Bitmap::new(width as u16, height as u16);
+
+    for y in 0..height as i32 {
+        for x in 0..width as i32 {
+            let old = levels[(y as u32 * width + x as u32) as usize];
+            let black = old < 0.5;
+            bitmap.set(x as u16, y as u16, black);
+
+            let error = old - if black { 0.0 } else { 1.0 };
+            for &(dx, dy, weight) in kernel {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx >= 0 && nx < width as i32 && ny < height as i32 {
+                    levels[(ny as u32 * width + nx as u32) as usize] += error * weight / divisor;
+                }
+            }
+        }
+    }
+    bitmap
+}
+
+/// Turns a PNG or JPEG into a black and white picture for an image widget in a
+/// `slot` sized slot. The picture is made as big as fits, keeping its shape.
+///
+/// # Errors
+/// Could error if the file isn't a PNG or JPEG the decoder understands.
+#[tauri::command]
+#[specta::specta]
+pub async fn dither_image(
+    image: Vec<u8>,
+    slot: SlotSize,
+    method: DitherMethod,
+) -> Result<Bitmap, String> {
+    let image =
+        image::load_from_memory(&image).map_err(|e| format!("Couldn't read the image: {e}"))?;
+    if image.width() == 0 || image.height() == 0 {
+        return Err("The image is empty".to_string());
+    }
+
+    let (slot_width, slot_height) = slot.pixel_size();
+    let scale = (f64::from(slot_width) / f64::from(image.width()))
+        .min(f64::from(slot_height) / f64::from(image.height()));
+    let width = ((f64::from(image.width()) * scale).round() as u32).clamp(1, u32::from(slot_width));
+    let height =
+        ((f64::from(image.height()) * scale).round() as u32).clamp(1, u32::from(slot_height));
+
+    let resized = image
+        .resize_exact(width, height, FilterType::Lanczos3)
+        .to_rgba8();
+    Ok(dither(brightness(&resized), width, height, method))
+}
@@ -1,7 +1,11 @@
 mod connection;
 pub mod data;
+mod image;
 pub mod translation_structs;
+mod todo;
 
 pub use connection::*;
 pub use data::*;
+pub use image::*;
 pub use translation_structs::*;
+pub use todo::*;
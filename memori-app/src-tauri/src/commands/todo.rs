@@ -0,0 +1,170 @@
+use crate::commands::translation_structs::TodoState;
+use crate::state::{AppState, DeviceConnection};
+use crate::widget_data::refresh_widget;
+use crate::widget_data::todo_data::{read_todos, write_todos};
+use memori_ui::widgets::{MemoriWidget, TodoItem, WidgetKindTag};
+use tauri::{AppHandle, Manager};
+use transport::HostTransport as _;
+
+// saves the changed list and sends it to every to-do widget on the device
+async fn save_todos(app: &AppHandle, todos: TodoState) -> Result<Vec<TodoItem>, String> {
+    write_todos(app, &todos)?;
+    push_todo_widgets(app).await;
+    Ok(todos.items)
+}
+
+// the device only hears about the list from us, so after each change we push it
+// instead of waiting for the device to ask
+async fn push_todo_widgets(app: &AppHandle) {
+    let state = app.state::<AppState>();
+
+    let widgets: Vec<MemoriWidget> = match &*state.memori.read().await {
+        Some(memori) => memori
+            .widgets
+            .values()
+            .filter(|widget| widget.kind.tag() == WidgetKindTag::Todo)
+            .cloned()
+            .collect(),
+        None => return,
+    };
+
+    for widget in widgets {
+        let fresh = match refresh_widget(app, &widget).await {
+            Ok(fresh) => fresh,
+            Err(e) => {
+                eprintln!("[todo] failed to refresh widget {:?}: {e}", widget.id);
+                continue;
+            }
+        };
+
+        let result = match &mut *state.conn.lock().await {
+            DeviceConnection::RealDevice(transport) => transport.push_widget(fresh.clone()).await,
+            DeviceConnection::Simulator(transport) => transport.push_widget(fresh.clone()).await,
+            DeviceConnection::Disconnected => return,
+        };
+
+        match result {
+            Ok(()) => {
+                if let Some(memori) = &mut *state.memori.write().await {
+                    memori.upsert_widget(fresh);
+                }
+            }
+            Err(e) => eprintln!("[todo] failed to push widget {:?}: {e}", widget.id),
+        }
+    }
+}
+
+fn find_item(todos: &mut TodoState, id: u32) -> Result<&mut TodoItem, String> {
+    todos
+        .items
+        .iter_mut()
+        .find(|item| item.id == id)
+        .ok_or_else(|| format!("No to-do item with id {id}"))
+}
+
+/// Sends back the to-do list, in order.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_todos(app: AppHandle) -> Result<Vec<TodoItem>, String> {
+    Ok(read_todos(&app).items)
+}
+
+/// Adds an item to the end of the to-do list, returns the new list.
+///
+/// # Errors
+/// Could error if the text is empty or the list couldn't be saved.
+#[tauri::command]
+#[specta::specta]
+pub async fn add_todo(app: AppHandle, text: String) -> Result<Vec<TodoItem>, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("To-do items need some text".to_string());
+    }
+
+    let mut todos = read_todos(&app);
+    // ids start at 1 and are never reused, even after an item is removed
+    let id = todos.next_id.max(1);
+    todos.next_id = id + 1;
+    todos.items.push(TodoItem::new(id, text, false));
+    save_todos(&app, todos).await
+}
+
+/// Changes the text of an item, returns the new list.
+///
+/// # Errors
+/// Could error if there's no item with the id, the text is empty, or the list
+/// couldn't be saved.
+#[tauri::command]
+#[specta::specta]
+pub async fn edit_todo(app: AppHandle, id: u32, text: String) -> Result<Vec<TodoItem>, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("To-do items need some text".to_string());
+    }
+
+    let mut todos = read_todos(&app);
+    find_item(&mut todos, id)?.text = text.to_string();
+    save_todos(&app, todos).await
+}
+
+/// Checks an item off, or unchecks it, returns the new list.
+///
+/// # Errors
+/// Could error if there's no item with the id or the list couldn't be saved.
+#[tauri::command]
+#[specta::specta]
+pub async fn complete_todo(app: AppHandle, id: u32, done: bool) -> Result<Vec<TodoItem>, String> {
+    let mut todos = read_todos(&app);
+    find_item(&mut todos, id)?.done = done;
+    save_todos(&app, todos).await
+}
+
+/// Puts the items in the order of `ids`, returns the new list.
+///
+/// # Errors
+/// Could error if `ids` isn't exactly the ids of the items on the list, or the
+/// list couldn't be saved.
+#[tauri::command]
+#[specta::specta]
+pub async fn reorder_todos(app: AppHandle, ids: Vec<u32>) -> Result<Vec<TodoItem>, String> {
+    let mut todos = read_todos(&app);
+    if ids.len() != todos.items.len() {
+        return Err("The new order has to list every to-do item once".to_string());
+    }
+
+    let mut reordered = Vec::with_capacity(ids.len());
+    for id in ids {
+        let index = todos
+            .items
+            .iter()
+            .position(|item| item.id == id)
+            .ok_or_else(|| format!("No to-do item with id {id}"))?;
+        reordered.push(todos.items.swap_remove(index));
+    }
+    todos.items = reordered;
+    save_todos(&app, todos).await
+}
+
+/// Takes an item off the list, returns the new list.
+///
+/// # Errors
+/// Could error if there's no item with the id or the list couldn't be saved.
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_todo(app: AppHandle, id: u32) -> Result<Vec<TodoItem>, String> {
+    let mut todos = read_todos(&app);
+    let before = todos.items.len();
+    todos.items.retain(|item| item.id != id);
+    if todos.items.len() == before {
+        return Err(format!("No to-do item with id {id}"));
+    }
+    save_todos(&app, todos).await
+}
+
+/// Marks an item done or not because it was toggled on the device. The device
+/// already shows the change, so unlike [`complete_todo`] nothing is pushed back.
+pub async fn todo_toggled_on_device(app: &AppHandle, id: u32, done: bool) -> Result<(), String> {
+    let mut todos = read_todos(app);
+    find_item(&mut todos, id)?.done = done;
+    write_todos(app, &todos)
+}
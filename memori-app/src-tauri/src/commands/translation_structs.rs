@@ -1,5 +1,8 @@
-use memori_ui::{layout::MemoriLayout, widgets::MemoriWidget};
-use serde::Deserialize;
+use memori_ui::{
+    layout::MemoriLayout,
+    widgets::{MemoriWidget, TodoItem},
+};
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize, specta::Type)]
 #[serde(rename_all = "camelCase")]
@@ -52,4 +55,16 @@ pub struct PrefsState {
     pub onboarded: bool,
     pub lastKnownDeviceId: Option<String>,
     pub name: String,
+    #[serde(rename = "calendarSource")]
+    pub calendar_source: Option<String>,
+    #[serde(rename = "calendarLookaheadDays")]
+    pub calendar_lookahead_days: Option<u32>,
+}
+
+// Mirrored from the frontend's "todos" store, written by the todo commands
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoState {
+    pub items: Vec<TodoItem>,
+    pub next_id: u32,
 }
@@ -0,0 +1,125 @@
+use crate::commands::todo_toggled_on_device;
+use crate::widget_data::refresh_widget;
+use memori_ui::input::InputEvent;
+use memori_ui::widgets::{MemoriWidget, WidgetId, WidgetKind};
+use memori_ui::MemoriState;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::AppHandle;
+use tauri_specta::Event;
+use tokio::sync::RwLock;
+use transport::protocol::{DeviceCommand, HostResponse};
+use transport::TransError;
+
+/// Sent to the frontend whenever a button is pressed on the device.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Type, Event)]
+pub struct DeviceInput(pub InputEvent);
+
+// figures out the response to a command sent by the device, this is shared by
+// every transport so the simulator behaves exactly like the real device
+pub async fn handle_device_command(
+    memori: &RwLock<Option<MemoriState>>,
+    cmd: DeviceCommand,
+    app_handle: &AppHandle,
+) -> HostResponse {
+    match cmd {
+        DeviceCommand::RefreshData { widget_id } => {
+            handle_refresh_data(memori, widget_id, app_handle).await
+        }
+        DeviceCommand::Ping => HostResponse::Ping { result: Ok(()) },
+        DeviceCommand::TodoToggled {
+            widget_id,
+            item_id,
+            done,
+        } => handle_todo_toggled(memori, widget_id, item_id, done, app_handle).await,
+        DeviceCommand::Input { event } => handle_input(memori, event, app_handle).await,
+    }
+}
+
+// the device already did what the press does, we do the same to our copy of the
+// state so the next push doesn't undo it. a to-do item checked off this way
+// gets its own TodoToggled right after.
+async fn handle_input(
+    memori: &RwLock<Option<MemoriState>>,
+    event: InputEvent,
+    app_handle: &AppHandle,
+) -> HostResponse {
+    if let Some(state) = &mut *memori.write().await {
+        state.handle_input(event);
+    }
+
+    if let Err(e) = DeviceInput(event).emit(app_handle) {
+        eprintln!("failed to emit device input: {e}");
+    }
+
+    HostResponse::Input { result: Ok(()) }
+}
+
+// saves an item the device checked off, and keeps our copy of the widget in
+// line with what the device shows
+async fn handle_todo_toggled(
+    memori: &RwLock<Option<MemoriState>>,
+    widget_id: WidgetId,
+    item_id: u32,
+    done: bool,
+    app_handle: &AppHandle,
+) -> HostResponse {
+    if let Err(e) = todo_toggled_on_device(app_handle, item_id, done).await {
+        eprintln!("failed to save to-do item {item_id}: {e}");
+        return HostResponse::TodoToggled {
+            result: Err(TransError::InternalError),
+        };
+    }
+
+    if let Some(state) = &mut *memori.write().await {
+        if let Some(MemoriWidget {
+            kind: WidgetKind::Todo(todo),
+            ..
+        }) = state.widgets.get_mut(&widget_id)
+        {
+            todo.set_done(item_id, done);
+        }
+    }
+
+    HostResponse::TodoToggled { result: Ok(()) }
+}
+
+// takes a widgetid and updates it based on the current state, returing a
+// response object to send back to the device.
+async fn handle_refresh_data(
+    memori: &RwLock<Option<MemoriState>>,
+    widget_id: WidgetId,
+    app_handle: &AppHandle,
+) -> HostResponse {
+    let guard = memori.read().await;
+
+    let state = match &*guard {
+        Some(s) => s,
+        None => {
+            return HostResponse::RefreshData {
+                result: Err(TransError::InternalError),
+            }
+        }
+    };
+
+    let widget = match state.widgets.get(&widget_id) {
+        Some(w) => w,
+        None => {
+            return HostResponse::RefreshData {
+                result: Err(TransError::InternalError),
+            }
+        }
+    };
+
+    match refresh_widget(app_handle, widget).await {
+        Ok(updated_widget) => HostResponse::RefreshData {
+            result: Ok(updated_widget),
+        },
+        Err(e) => {
+            eprintln!("failed to refresh widget {widget_id:?}: {e}");
+            HostResponse::RefreshData {
+                result: Err(TransError::InternalError),
+            }
+        }
+    }
+}
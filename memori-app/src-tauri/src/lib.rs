@@ -1,20 +1,26 @@
 mod ble;
 mod commands;
+mod device_commands;
 mod oauth;
+mod scheduler;
 mod simulator;
 mod state;
 mod widget_data;
 
+use crate::ble::{ConnectionStatus, DeviceBattery, NearbyDevice};
+use crate::device_commands::DeviceInput;
 use crate::widget_data::github_data::get_github_repos;
 use commands::{
-    connect_device, disconnect_device, flash_memori_state, get_battery, get_device_mode,
-    get_widget_kinds, is_connected,
+    add_todo, complete_todo, connect_device, disconnect_device, dither_image, edit_todo,
+    flash_memori_state,
+    get_battery, get_device_mode, get_todos, get_widget_kinds, is_connected,
+    preview_memori_state, remove_todo, reorder_todos, scan_devices,
 };
 use memori_ui::{layout::MemoriLayout, widgets::MemoriWidget};
 use oauth::{login_with_provider, start_oauth_server};
 use specta_typescript::{BigIntExportBehavior, Typescript};
 use state::{AppState, DeviceConnection};
-use tauri_specta::{collect_commands, Builder};
+use tauri_specta::{collect_commands, collect_events, Builder};
 use tauri::Manager;
 
 // use serde::{Deserialize, Serialize};
@@ -29,16 +35,25 @@ pub fn run() {
         .commands(collect_commands![
             connect_device,
             disconnect_device,
+            scan_devices,
             is_connected,
             get_widget_kinds,
             get_battery,
             get_device_mode,
             flash_memori_state,
+            preview_memori_state,
             start_oauth_server,
             login_with_provider,
             get_github_repos,
+            get_todos,
+            add_todo,
+            edit_todo,
+            complete_todo,
+            reorder_todos,
+            remove_todo,
+            dither_image,
         ])
-        // .events(collect_events![UpdateIsConnected])
+        .events(collect_events![ConnectionStatus, NearbyDevice, DeviceInput, DeviceBattery])
         .typ::<MemoriLayout>()
         .typ::<MemoriWidget>();
 
@@ -0,0 +1,89 @@
+use crate::state::{AppState, DeviceConnection};
+use crate::widget_data::refresh_widget;
+use memori_ui::widgets::{MemoriWidget, UpdateFrequency, WidgetId};
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::time::{interval, Instant};
+use transport::HostTransport as _;
+
+// how often we check whether any widget is due for new data
+const TICK: Duration = Duration::from_secs(1);
+
+// background task that keeps the widgets on the device up to date by fetching their data on
+// each widget's remote_update_frequency and pushing it, so the device doesn't have to poll
+// us for it. runs until the device disconnects.
+pub async fn widget_scheduler(app: AppHandle) {
+    // when each widget is due next, along with the frequency that was computed with
+    let mut schedule: HashMap<WidgetId, (UpdateFrequency, Instant)> = HashMap::new();
+    let mut ticker = interval(TICK);
+
+    loop {
+        ticker.tick().await;
+        let now = Instant::now();
+        let state = app.state::<AppState>();
+
+        let due: Vec<MemoriWidget> = {
+            let memori = state.memori.read().await;
+            let Some(memori) = &*memori else {
+                continue;
+            };
+
+            // forget widgets that aren't on the device anymore
+            schedule.retain(|id, _| memori.widgets.contains_key(id));
+
+            memori
+                .widgets
+                .values()
+                .filter(|widget| {
+                    let frequency = widget.get_remote_update_frequency();
+                    let Some(seconds) = frequency.to_seconds() else {
+                        return false;
+                    };
+                    let period = Duration::from_secs(seconds.into());
+
+                    // the device just got fresh data when it was added, so start counting from now
+                    let (scheduled_frequency, next_due) = schedule
+                        .entry(widget.id)
+                        .or_insert((frequency, now + period));
+                    if *scheduled_frequency != frequency {
+                        *scheduled_frequency = frequency;
+                        *next_due = now + period;
+                    }
+
+                    if now < *next_due {
+                        return false;
+                    }
+                    *next_due = now + period;
+                    true
+                })
+                .cloned()
+                .collect()
+        };
+
+        for widget in due {
+            let fresh = match refresh_widget(&app, &widget).await {
+                Ok(fresh) => fresh,
+                Err(e) => {
+                    eprintln!("[scheduler] failed to refresh widget {:?}: {e}", widget.id);
+                    continue;
+                }
+            };
+
+            let result = match &mut *state.conn.lock().await {
+                DeviceConnection::RealDevice(transport) => transport.push_widget(fresh.clone()).await,
+                DeviceConnection::Simulator(transport) => transport.push_widget(fresh.clone()).await,
+                DeviceConnection::Disconnected => return,
+            };
+
+            match result {
+                Ok(()) => {
+                    if let Some(memori) = &mut *state.memori.write().await {
+                        memori.upsert_widget(fresh);
+                    }
+                }
+                Err(e) => eprintln!("[scheduler] failed to push widget {:?}: {e}", widget.id),
+            }
+        }
+    }
+}
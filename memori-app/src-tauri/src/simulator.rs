@@ -1,19 +1,22 @@
-use memori_tcp::{DeviceRequest, HostResponse, Sequenced};
+use crate::device_commands::handle_device_command;
+use memori_tcp::Sequenced;
+use memori_ui::MemoriState;
+use std::sync::Arc;
+use tauri::AppHandle;
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use tokio::sync::RwLock;
+use transport::protocol::{DeviceCommand, HostResponse};
 
 pub async fn request_handler(
-    mut dev_req_rx: UnboundedReceiver<Sequenced<DeviceRequest>>,
+    memori: Arc<RwLock<Option<MemoriState>>>,
+    mut dev_cmd_rx: UnboundedReceiver<Sequenced<DeviceCommand>>,
     host_resp_tx: UnboundedSender<Sequenced<HostResponse>>,
+    app_handle: &AppHandle,
 ) {
-    while let Some(req) = dev_req_rx.recv().await {
+    while let Some(req) = dev_cmd_rx.recv().await {
         println!("received request from device! {req:#?}");
 
-        let resp = match req.msg_kind {
-            DeviceRequest::RefreshData(_id) => {
-                todo!()
-            }
-            DeviceRequest::Ping => HostResponse::Pong,
-        };
+        let resp = handle_device_command(&memori, req.msg_kind, app_handle).await;
 
         host_resp_tx
             .send(Sequenced::new(req.seq_num, resp))
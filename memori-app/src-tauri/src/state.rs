@@ -4,6 +4,7 @@ use memori_tcp::{host::DeviceConnected, HostTcpTransport};
 use serde::{Deserialize, Serialize};
 use specta::Type;
 use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
 use std::sync::Arc;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
@@ -20,7 +21,9 @@ pub(crate) enum DeviceConnection {
 
 pub struct AppState {
     pub(crate) conn: Mutex<DeviceConnection>,
-    pub(crate) memori: Arc<RwLock<Option<MemoriState>>>
+    pub(crate) memori: Arc<RwLock<Option<MemoriState>>>,
+    /// Task pushing fresh widget data while a device is connected.
+    pub(crate) scheduler: Mutex<Option<JoinHandle<()>>>,
 }
 
 impl AppState {
@@ -28,6 +31,7 @@ impl AppState {
         Self {
             conn: Mutex::new(DeviceConnection::Disconnected),
             memori: Arc::new(RwLock::new(None)),
+            scheduler: Mutex::new(None),
         }
     }
 }
@@ -1,5 +1,8 @@
 use memori_ui::widgets::Bus;
-use memori_ui::widgets::{MemoriWidget, UpdateFrequency, WidgetId, WidgetKind};
+use crate::widget_data::WidgetProvider;
+use async_trait::async_trait;
+use memori_ui::widgets::{MemoriWidget, UpdateFrequency, WidgetId, WidgetKind, WidgetKindTag};
+use tauri::AppHandle;
 use reqwest::Client;
 use serde::Deserialize;
 use serde_json::Value;
@@ -213,7 +216,7 @@ pub async fn refresh_bus_widget() -> Result<Bus, String> {
         }
     }
     if api_key.is_empty() {
-        panic!("BUS_API_KEY not found");
+        return Err("BUS_API_KEY not found".to_string());
     }
     let key = api_key.as_str();
     let lat: f64 = 37.000074;
@@ -249,3 +252,20 @@ pub async fn bus_to_memori_widget(bus_id: u32, bus: Bus) -> Result<MemoriWidget,
         local_update_frequency: UpdateFrequency::Never,
     })
 }
+
+pub struct BusProvider;
+
+#[async_trait]
+impl WidgetProvider for BusProvider {
+    fn tag(&self) -> WidgetKindTag {
+        WidgetKindTag::Bus
+    }
+
+    async fn default_instance(&self, _app: &AppHandle, id: WidgetId) -> Result<MemoriWidget, String> {
+        bus_to_memori_widget(id.0, refresh_bus_widget().await.unwrap_or_default()).await
+    }
+
+    async fn refresh(&self, _app: &AppHandle, _widget: &MemoriWidget) -> Result<WidgetKind, String> {
+        Ok(WidgetKind::Bus(refresh_bus_widget().await?))
+    }
+}
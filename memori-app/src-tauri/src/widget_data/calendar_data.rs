@@ -0,0 +1,751 @@
+use crate::commands::data::read_store_state;
+use crate::commands::translation_structs::PrefsState;
+use crate::widget_data::WidgetProvider;
+use async_trait::async_trait;
+use chrono::{
+    DateTime, Datelike, Duration, Local, Months, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc,
+    Weekday,
+};
+use chrono_tz::Tz;
+use ical::parser::ical::component::IcalEvent;
+use ical::property::Property;
+use ical::IcalParser;
+use memori_ui::time::LocalTime;
+use memori_ui::widgets::{
+    Calendar, CalendarEvent, MemoriWidget, UpdateFrequency, WidgetId, WidgetKind, WidgetKindTag,
+};
+use reqwest::Client;
+use std::io::BufReader;
+use tauri::AppHandle;
+
+/// How far ahead to look for events when the prefs don't say.
+const DEFAULT_LOOKAHEAD_DAYS: u32 = 7;
+/// Furthest ahead to look for events, whatever the prefs say.
+const MAX_LOOKAHEAD_DAYS: u32 = 366;
+/// Most events sent to the device, more than even the full display has room for.
+const MAX_EVENTS: usize = 8;
+/// Gives up on expanding a recurring event after this many occurrences.
+const MAX_OCCURRENCES: usize = 100_000;
+
+/// The zone a time in the calendar is written in.
+#[derive(Debug, Clone, Copy)]
+enum Zone {
+    Utc,
+    Named(Tz),
+    /// No zone given, the time is the same wherever you are.
+    Floating,
+}
+
+impl Zone {
+    fn to_utc(self, time: NaiveDateTime) -> DateTime<Utc> {
+        match self {
+            Zone::Utc => time.and_utc(),
+            Zone::Named(tz) => resolve(&tz, time),
+            Zone::Floating => resolve(&Local, time),
+        }
+    }
+}
+
+/// `time` in `tz`, times skipped by a DST change move forward an hour.
+fn resolve<T: TimeZone>(tz: &T, time: NaiveDateTime) -> DateTime<Utc> {
+    tz.from_local_datetime(&time)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(time + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(|| time.and_utc())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Freq {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// The parts of an `RRULE` we understand.
+#[derive(Debug, Clone)]
+struct RRule {
+    freq: Freq,
+    interval: u32,
+    count: Option<usize>,
+    until: Option<DateTime<Utc>>,
+    /// Weekdays, with which one of the month for monthly rules, e.g. `-1FR`.
+    by_day: Vec<(Option<i32>, Weekday)>,
+}
+
+/// A `VEVENT`, before its recurrences are expanded.
+#[derive(Debug, Clone)]
+struct IcsEvent {
+    uid: String,
+    title: String,
+    start: NaiveDateTime,
+    zone: Zone,
+    all_day: bool,
+    duration: Duration,
+    rrule: Option<RRule>,
+    exdates: Vec<DateTime<Utc>>,
+    /// Set on an event replacing one occurrence of a recurring event.
+    recurrence_id: Option<DateTime<Utc>>,
+}
+
+/// One occurrence of an event, in UTC.
+struct Occurrence<'a> {
+    event: &'a IcsEvent,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+fn param<'a>(prop: &'a Property, name: &str) -> Option<&'a str> {
+    prop.params
+        .as_ref()?
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .and_then(|(_, values)| values.first())
+        .map(String::as_str)
+}
+
+fn property<'a>(event: &'a IcalEvent, name: &str) -> Option<&'a Property> {
+    event
+        .properties
+        .iter()
+        .find(|p| p.name.eq_ignore_ascii_case(name))
+}
+
+/// Undoes the escaping of iCalendar text values.
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => out.push(' '),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+/// A `DATE` or `DATE-TIME` value, with whether it was a whole day.
+fn parse_time(value: &str, tzid: Option<&str>) -> Option<(NaiveDateTime, Zone, bool)> {
+    let value = value.trim();
+    if value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some((date.and_hms_opt(0, 0, 0)?, Zone::Floating, true));
+    }
+
+    let (value, zone) = match value.strip_suffix('Z') {
+        Some(value) => (value, Zone::Utc),
+        // Zones we don't know, like the Windows names Outlook uses, are
+        // taken as the phone's own.
+        None => match tzid.and_then(|id| id.parse::<Tz>().ok()) {
+            Some(tz) => (value, Zone::Named(tz)),
+            None => (value, Zone::Floating),
+        },
+    };
+    let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    Some((time, zone, false))
+}
+
+/// Every time in a `DTSTART`-like property, `EXDATE` can list several.
+fn parse_times(prop: &Property) -> Vec<(NaiveDateTime, Zone, bool)> {
+    let tzid = param(prop, "TZID");
+    prop.value
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|value| parse_time(value, tzid))
+        .collect()
+}
+
+/// A `DURATION` value like `PT1H30M` or `P1D`.
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let value = value.strip_prefix('P')?;
+
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match unit {
+                    'W' => Duration::weeks(n),
+                    'D' => Duration::days(n),
+                    'H' => Duration::hours(n),
+                    'M' => Duration::minutes(n),
+                    'S' => Duration::seconds(n),
+                    _ => return None,
+                };
+            }
+        }
+    }
+
+    Some(if negative { -total } else { total })
+}
+
+fn parse_weekday(day: &str) -> Option<Weekday> {
+    Some(match day {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+fn parse_rrule(value: &str) -> Option<RRule> {
+    let mut rule = RRule {
+        freq: Freq::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        by_day: Vec::new(),
+    };
+    let mut has_freq = false;
+
+    for part in value.split(';') {
+        let Some((key, value)) = part.split_once('=') else {
+            continue;
+        };
+        match key.to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                has_freq = true;
+                rule.freq = match value.to_ascii_uppercase().as_str() {
+                    "DAILY" => Freq::Daily,
+                    "WEEKLY" => Freq::Weekly,
+                    "MONTHLY" => Freq::Monthly,
+                    "YEARLY" => Freq::Yearly,
+                    // Anything more often than daily isn't worth showing on the display.
+                    _ => return None,
+                }
+            }
+            "INTERVAL" => rule.interval = value.parse().ok().filter(|&i| i > 0)?,
+            "COUNT" => rule.count = value.parse().ok(),
+            "UNTIL" => {
+                rule.until = parse_time(value, None).map(|(time, zone, all_day)| {
+                    // A date means the whole of that day is still in.
+                    let time = if all_day {
+                        time + Duration::days(1)
+                    } else {
+                        time
+                    };
+                    zone.to_utc(time)
+                })
+            }
+            "BYDAY" => {
+                rule.by_day = value
+                    .split(',')
+                    .filter_map(|day| {
+                        let day = day.trim().to_ascii_uppercase();
+                        let (ordinal, weekday) = day.split_at(day.len().checked_sub(2)?);
+                        Some((ordinal.parse().ok(), parse_weekday(weekday)?))
+                    })
+                    .collect()
+            }
+            _ => {}
+        }
+    }
+
+    has_freq.then_some(rule)
+}
+
+fn parse_event(event: &IcalEvent) -> Option<IcsEvent> {
+    let status = property(event, "STATUS").and_then(|p| p.value.as_deref());
+    if status.is_some_and(|s| s.eq_ignore_ascii_case("CANCELLED")) {
+        return None;
+    }
+
+    let (start, zone, all_day) = *parse_times(property(event, "DTSTART")?).first()?;
+
+    let end = property(event, "DTEND")
+        .and_then(|p| parse_times(p).first().copied())
+        .map(|(end, end_zone, _)| end_zone.to_utc(end) - zone.to_utc(start));
+    let duration = property(event, "DURATION").and_then(|p| parse_duration(p.value.as_deref()?));
+    let duration = end.or(duration).unwrap_or(if all_day {
+        Duration::days(1)
+    } else {
+        Duration::zero()
+    });
+
+    let exdates = event
+        .properties
+        .iter()
+        .filter(|p| p.name.eq_ignore_ascii_case("EXDATE"))
+        .flat_map(parse_times)
+        .map(|(time, zone, _)| zone.to_utc(time))
+        .collect();
+
+    Some(IcsEvent {
+        uid: property(event, "UID")
+            .and_then(|p| p.value.clone())
+            .unwrap_or_default(),
+        title: property(event, "SUMMARY")
+            .and_then(|p| p.value.as_deref())
+            .map(unescape)
+            .unwrap_or_else(|| "(No title)".to_string()),
+        start,
+        zone,
+        all_day,
+        duration,
+        rrule: property(event, "RRULE").and_then(|p| parse_rrule(p.value.as_deref()?)),
+        exdates,
+        recurrence_id: property(event, "RECURRENCE-ID")
+            .and_then(|p| parse_times(p).first().copied())
+            .map(|(time, zone, _)| zone.to_utc(time)),
+    })
+}
+
+/// The days of a month an `RRULE` with `by_day` lands on.
+fn monthly_days(year: i32, month: u32, by_day: &[(Option<i32>, Weekday)]) -> Vec<NaiveDate> {
+    let Some(first) = NaiveDate::from_ymd_opt(year, month, 1) else {
+        return Vec::new();
+    };
+    let days: Vec<NaiveDate> = first
+        .iter_days()
+        .take_while(|day| day.month() == month)
+        .collect();
+
+    let mut matching: Vec<NaiveDate> = by_day
+        .iter()
+        .flat_map(|&(ordinal, weekday)| {
+            let of_weekday: Vec<NaiveDate> = days
+                .iter()
+                .copied()
+                .filter(|day| day.weekday() == weekday)
+                .collect();
+            match ordinal {
+                None => of_weekday,
+                Some(n) if n > 0 => of_weekday
+                    .get(n as usize - 1)
+                    .copied()
+                    .into_iter()
+                    .collect(),
+                Some(n) => of_weekday
+                    .len()
+                    .checked_sub(n.unsigned_abs() as usize)
+                    .and_then(|i| of_weekday.get(i).copied())
+                    .into_iter()
+                    .collect(),
+            }
+        })
+        .collect();
+    matching.sort();
+    matching.dedup();
+    matching
+}
+
+/// Starts of the occurrences of `event` in its own zone, up to `until`.
+fn expand(event: &IcsEvent, until: DateTime<Utc>) -> Vec<NaiveDateTime> {
+    let Some(rule) = &event.rrule else {
+        return vec![event.start];
+    };
+
+    let time = event.start.time();
+    let start_date = event.start.date();
+    let interval = rule.interval as i64;
+    let mut starts = Vec::new();
+
+    for period in 0.. {
+        let candidates: Vec<NaiveDate> = match rule.freq {
+            Freq::Daily => {
+                let day = start_date + Duration::days(period * interval);
+                let weekday_ok = rule.by_day.is_empty()
+                    || rule
+                        .by_day
+                        .iter()
+                        .any(|&(_, weekday)| weekday == day.weekday());
+                if weekday_ok {
+                    vec![day]
+                } else {
+                    vec![]
+                }
+            }
+            Freq::Weekly => {
+                let monday = start_date
+                    - Duration::days(start_date.weekday().num_days_from_monday() as i64)
+                    + Duration::weeks(period * interval);
+                let mut days: Vec<NaiveDate> = if rule.by_day.is_empty() {
+                    vec![start_date.weekday()]
+                } else {
+                    rule.by_day.iter().map(|&(_, weekday)| weekday).collect()
+                }
+                .into_iter()
+                .map(|weekday| monday + Duration::days(weekday.num_days_from_monday() as i64))
+                .collect();
+                days.sort();
+                days.dedup();
+                days
+            }
+            Freq::Monthly => {
+                let Some(month) = start_date.with_day(1).and_then(|first| {
+                    first.checked_add_months(Months::new((period * interval) as u32))
+                }) else {
+                    break;
+                };
+                if rule.by_day.is_empty() {
+                    // Months without the day, like the 31st, are skipped.
+                    month.with_day(start_date.day()).into_iter().collect()
+                } else {
+                    monthly_days(month.year(), month.month(), &rule.by_day)
+                }
+            }
+            Freq::Yearly => {
+                let year = start_date.year() + (period * interval) as i32;
+                NaiveDate::from_ymd_opt(year, start_date.month(), start_date.day())
+                    .into_iter()
+                    .collect()
+            }
+        };
+
+        let first_candidate = candidates.first().map(|day| day.and_time(time));
+        if first_candidate.is_some_and(|start| event.zone.to_utc(start) > until) {
+            break;
+        }
+
+        for day in candidates {
+            let start = day.and_time(time);
+            if start < event.start {
+                continue;
+            }
+            let start_utc = event.zone.to_utc(start);
+            if start_utc > until || rule.until.is_some_and(|last| start_utc > last) {
+                return starts;
+            }
+            starts.push(start);
+            if rule.count.is_some_and(|count| starts.len() >= count)
+                || starts.len() >= MAX_OCCURRENCES
+            {
+                return starts;
+            }
+        }
+
+        // Stop eventually on rules that never land on a real date.
+        if period > MAX_OCCURRENCES as i64 {
+            break;
+        }
+    }
+
+    starts
+}
+
+/// Every occurrence overlapping `from..until`, soonest first.
+fn occurrences(
+    events: &[IcsEvent],
+    from: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Vec<Occurrence<'_>> {
+    let mut found = Vec::new();
+
+    for event in events {
+        // Occurrences moved or changed by another event with the same UID.
+        let replaced: Vec<DateTime<Utc>> = events
+            .iter()
+            .filter(|other| other.uid == event.uid)
+            .filter_map(|other| other.recurrence_id)
+            .collect();
+
+        for start in expand(event, until) {
+            let start = event.zone.to_utc(start);
+            let end = start + event.duration;
+            let is_replaced = event.recurrence_id.is_none() && replaced.contains(&start);
+            if is_replaced || event.exdates.contains(&start) {
+                continue;
+            }
+            if end < from || start >= until {
+                continue;
+            }
+            found.push(Occurrence { event, start, end });
+        }
+    }
+
+    found.sort_by_key(|occurrence| occurrence.start);
+    found
+}
+
+/// `time` as the phone's local time.
+fn to_local_time(time: DateTime<Utc>) -> LocalTime {
+    let offset_secs = time.with_timezone(&Local).offset().fix().local_minus_utc();
+    LocalTime::from_utc(time.timestamp(), (offset_secs / 60) as i16)
+}
+
+fn to_calendar_event(occurrence: &Occurrence) -> CalendarEvent {
+    let event = occurrence.event;
+    let (start, end) = if event.all_day {
+        // Whole days, whatever DST did to the hours in between.
+        let first_day = occurrence.start.with_timezone(&Local).date_naive();
+        let days = ((event.duration.num_hours() + 12) / 24).max(1);
+        let midnight = |day: NaiveDate| {
+            LocalTime::from_ymd_hms(day.year(), day.month() as u8, day.day() as u8, 0, 0, 0)
+        };
+        (
+            midnight(first_day),
+            midnight(first_day + Duration::days(days)),
+        )
+    } else {
+        (
+            to_local_time(occurrence.start),
+            to_local_time(occurrence.end),
+        )
+    };
+
+    CalendarEvent {
+        title: event.title.clone(),
+        start,
+        end,
+        all_day: event.all_day,
+    }
+}
+
+/// The text of the calendar at `source`, a file path or an `http(s)://` or `webcal://` URL.
+async fn read_source(source: &str) -> Result<String, String> {
+    let source = source.trim();
+    let url = match source.strip_prefix("webcal://") {
+        Some(rest) => Some(format!("https://{rest}")),
+        None if source.starts_with("http://") || source.starts_with("https://") => {
+            Some(source.to_string())
+        }
+        None => None,
+    };
+
+    match url {
+        Some(url) => Client::new()
+            .get(&url)
+            .header("User-Agent", "tauri-app")
+            .send()
+            .await
+            .map_err(|e| e.to_string())?
+            .error_for_status()
+            .map_err(|e| format!("failed to fetch calendar: {e}"))?
+            .text()
+            .await
+            .map_err(|e| format!("failed to read calendar: {e}")),
+        None => tokio::fs::read_to_string(source)
+            .await
+            .map_err(|e| format!("failed to read calendar file {source}: {e}")),
+    }
+}
+
+/// The name of the calendar in `text`, and its events.
+fn parse_calendar(text: &str) -> Result<(String, Vec<IcsEvent>), String> {
+    let mut name = String::new();
+    let mut events = Vec::new();
+    for calendar in IcalParser::new(BufReader::new(text.as_bytes())) {
+        let calendar = calendar.map_err(|e| format!("failed to parse calendar: {e}"))?;
+        if name.is_empty() {
+            if let Some(cal_name) = calendar
+                .properties
+                .iter()
+                .find(|p| p.name.eq_ignore_ascii_case("X-WR-CALNAME"))
+                .and_then(|p| p.value.as_deref())
+            {
+                name = unescape(cal_name);
+            }
+        }
+        events.extend(calendar.events.iter().filter_map(parse_event));
+    }
+    Ok((name, events))
+}
+
+/// The next events in the calendar at `source`, starting within `lookahead_days`,
+/// at most [`MAX_LOOKAHEAD_DAYS`].
+pub async fn refresh_calendar_widget(
+    source: &str,
+    lookahead_days: u32,
+) -> Result<Calendar, String> {
+    let text = read_source(source).await?;
+    let (name, events) = parse_calendar(&text)?;
+
+    let now = Utc::now();
+    let until = now + Duration::days(lookahead_days.min(MAX_LOOKAHEAD_DAYS).into());
+    let upcoming: Vec<CalendarEvent> = occurrences(&events, now, until)
+        .iter()
+        .take(MAX_EVENTS)
+        .map(to_calendar_event)
+        .collect();
+
+    Ok(Calendar::new(name, upcoming, to_local_time(now)))
+}
+
+pub async fn calendar_to_memori_widget(
+    id: u32,
+    calendar: Calendar,
+) -> Result<MemoriWidget, String> {
+    Ok(MemoriWidget {
+        id: WidgetId(id),
+        kind: WidgetKind::Calendar(calendar),
+        remote_update_frequency: UpdateFrequency::Minutes(15),
+        // Lets the device drop events as they end.
+        local_update_frequency: UpdateFrequency::Minutes(1),
+    })
+}
+
+/// Where the calendar is and how far ahead to look, from the prefs.
+fn calendar_prefs(app: &AppHandle) -> Result<(String, u32), String> {
+    let prefs: PrefsState = read_store_state(app, "prefs");
+    let source = prefs
+        .calendar_source
+        .filter(|source| !source.trim().is_empty())
+        .ok_or("no calendar set up")?;
+    Ok((
+        source,
+        prefs
+            .calendar_lookahead_days
+            .unwrap_or(DEFAULT_LOOKAHEAD_DAYS),
+    ))
+}
+
+pub struct CalendarProvider;
+
+#[async_trait]
+impl WidgetProvider for CalendarProvider {
+    fn tag(&self) -> WidgetKindTag {
+        WidgetKindTag::Calendar
+    }
+
+    async fn default_instance(
+        &self,
+        app: &AppHandle,
+        id: WidgetId,
+    ) -> Result<MemoriWidget, String> {
+        let calendar = match calendar_prefs(app) {
+            Ok((source, days)) => refresh_calendar_widget(&source, days)
+                .await
+                .unwrap_or_default(),
+            Err(_) => Calendar::default(),
+        };
+        calendar_to_memori_widget(id.0, calendar).await
+    }
+
+    async fn refresh(&self, app: &AppHandle, _widget: &MemoriWidget) -> Result<WidgetKind, String> {
+        let (source, days) = calendar_prefs(app)?;
+        Ok(WidgetKind::Calendar(
+            refresh_calendar_widget(&source, days).await?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(time: &str) -> DateTime<Utc> {
+        time.parse().unwrap()
+    }
+
+    /// The titles and starts of the occurrences in a calendar made of `events`,
+    /// during the first half of 2025.
+    fn starts(events: &str) -> Vec<(String, DateTime<Utc>)> {
+        let text = format!("BEGIN:VCALENDAR\nVERSION:2.0\n{events}END:VCALENDAR\n");
+        let (_, events) = parse_calendar(&text).unwrap();
+        occurrences(&events, utc("2025-01-01T00:00:00Z"), utc("2025-07-01T00:00:00Z"))
+            .iter()
+            .map(|occurrence| (occurrence.event.title.clone(), occurrence.start))
+            .collect()
+    }
+
+    fn times(events: &str) -> Vec<DateTime<Utc>> {
+        starts(events).into_iter().map(|(_, start)| start).collect()
+    }
+
+    #[test]
+    fn by_day_ordinals_count_from_either_end_of_the_month() {
+        let last_friday = "BEGIN:VEVENT\nUID:a\nSUMMARY:Drinks\nDTSTART:20250131T170000Z\n\
+                           RRULE:FREQ=MONTHLY;BYDAY=-1FR;COUNT=3\nEND:VEVENT\n";
+        assert_eq!(
+            times(last_friday),
+            [
+                utc("2025-01-31T17:00:00Z"),
+                utc("2025-02-28T17:00:00Z"),
+                utc("2025-03-28T17:00:00Z"),
+            ]
+        );
+
+        let second_tuesday = "BEGIN:VEVENT\nUID:b\nSUMMARY:Board\nDTSTART:20250114T090000Z\n\
+                              RRULE:FREQ=MONTHLY;INTERVAL=2;BYDAY=2TU;COUNT=3\nEND:VEVENT\n";
+        assert_eq!(
+            times(second_tuesday),
+            [
+                utc("2025-01-14T09:00:00Z"),
+                utc("2025-03-11T09:00:00Z"),
+                utc("2025-05-13T09:00:00Z"),
+            ]
+        );
+    }
+
+    #[test]
+    fn months_without_the_day_are_skipped() {
+        let ics = "BEGIN:VEVENT\nUID:a\nSUMMARY:Rent\nDTSTART:20250131T080000Z\n\
+                   RRULE:FREQ=MONTHLY;COUNT=3\nEND:VEVENT\n";
+        assert_eq!(
+            times(ics),
+            [
+                utc("2025-01-31T08:00:00Z"),
+                utc("2025-03-31T08:00:00Z"),
+                utc("2025-05-31T08:00:00Z"),
+            ]
+        );
+    }
+
+    #[test]
+    fn until_is_the_last_occurrence() {
+        let ics = "BEGIN:VEVENT\nUID:a\nSUMMARY:Gym\nDTSTART:20250106T070000Z\n\
+                   RRULE:FREQ=WEEKLY;BYDAY=MO,WE;UNTIL=20250115T070000Z\nEND:VEVENT\n";
+        assert_eq!(
+            times(ics),
+            [
+                utc("2025-01-06T07:00:00Z"),
+                utc("2025-01-08T07:00:00Z"),
+                utc("2025-01-13T07:00:00Z"),
+                utc("2025-01-15T07:00:00Z"),
+            ]
+        );
+    }
+
+    #[test]
+    fn excluded_and_replaced_occurrences() {
+        let ics = "BEGIN:VEVENT\nUID:a\nSUMMARY:Standup\nDTSTART:20250303T100000Z\n\
+                   DURATION:PT15M\nRRULE:FREQ=DAILY;COUNT=4\nEXDATE:20250304T100000Z\n\
+                   END:VEVENT\n\
+                   BEGIN:VEVENT\nUID:a\nSUMMARY:Late standup\nDTSTART:20250305T150000Z\n\
+                   DURATION:PT15M\nRECURRENCE-ID:20250305T100000Z\nEND:VEVENT\n";
+        assert_eq!(
+            starts(ics),
+            [
+                ("Standup".to_string(), utc("2025-03-03T10:00:00Z")),
+                ("Late standup".to_string(), utc("2025-03-05T15:00:00Z")),
+                ("Standup".to_string(), utc("2025-03-06T10:00:00Z")),
+            ]
+        );
+    }
+
+    #[test]
+    fn recurrences_keep_their_local_time_across_dst() {
+        // Berlin moves to summer time on the 30th of March.
+        let ics = "BEGIN:VEVENT\nUID:a\nSUMMARY:Call\n\
+                   DTSTART;TZID=Europe/Berlin:20250324T090000\n\
+                   RRULE:FREQ=WEEKLY;COUNT=2\nEND:VEVENT\n";
+        assert_eq!(
+            times(ics),
+            [utc("2025-03-24T08:00:00Z"), utc("2025-03-31T07:00:00Z")]
+        );
+    }
+}
+
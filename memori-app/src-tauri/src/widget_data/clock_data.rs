@@ -1,13 +1,20 @@
+use crate::widget_data::WidgetProvider;
+use async_trait::async_trait;
+use chrono::{Local, Utc};
+use memori_ui::time::LocalTime;
 use memori_ui::widgets::Clock;
-use chrono::{Local, Timelike};
-use memori_ui::widgets::{WidgetId, UpdateFrequency};
+use memori_ui::widgets::{MemoriWidget, WidgetId, WidgetKind, WidgetKindTag};
+use tauri::AppHandle;
+
+/// Seconds since the unix epoch in UTC, and how many minutes local time is ahead of UTC.
+pub fn current_time() -> (i64, i16) {
+    let offset_secs = Local::now().offset().local_minus_utc();
+    (Utc::now().timestamp(), (offset_secs / 60) as i16)
+}
 
 pub async fn refresh_clock_widget() -> Result<Clock, String> {
-    Ok(Clock {
-        seconds: Local::now().second() as u32,
-        minutes: Local::now().minute() as u32,
-        hours: Local::now().hour() as u32,
-    })
+    let (utc_epoch_secs, utc_offset_minutes) = current_time();
+    Ok(Clock::new(LocalTime::from_utc(utc_epoch_secs, utc_offset_minutes)))
 }
 
 pub async fn clock_to_memori_widget(id: u32, clock: Clock) -> Result<memori_ui::widgets::MemoriWidget, String> {
@@ -17,4 +24,26 @@ pub async fn clock_to_memori_widget(id: u32, clock: Clock) -> Result<memori_ui::
         remote_update_frequency: memori_ui::widgets::UpdateFrequency::Hours(1),
         local_update_frequency: memori_ui::widgets::UpdateFrequency::Minutes(1),
     })
-}
\ No newline at end of file
+}
+
+pub struct ClockProvider;
+
+#[async_trait]
+impl WidgetProvider for ClockProvider {
+    fn tag(&self) -> WidgetKindTag {
+        WidgetKindTag::Clock
+    }
+
+    async fn default_instance(&self, _app: &AppHandle, id: WidgetId) -> Result<MemoriWidget, String> {
+        clock_to_memori_widget(id.0, refresh_clock_widget().await.unwrap_or_default()).await
+    }
+
+    async fn refresh(&self, _app: &AppHandle, widget: &MemoriWidget) -> Result<WidgetKind, String> {
+        let mut clock = refresh_clock_widget().await?;
+        // Keep showing the time the way it was set up.
+        if let WidgetKind::Clock(old) = &widget.kind {
+            clock.format = old.format;
+        }
+        Ok(WidgetKind::Clock(clock))
+    }
+}
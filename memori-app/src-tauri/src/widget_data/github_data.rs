@@ -1,7 +1,9 @@
 use crate::commands::data::{read_store_state};
 use crate::commands::translation_structs::AuthState;
 use chrono::{Datelike, Local};
-use memori_ui::widgets::{Github, WidgetId, WidgetKind, UpdateFrequency, MemoriWidget};
+use crate::widget_data::WidgetProvider;
+use async_trait::async_trait;
+use memori_ui::widgets::{Github, WidgetId, WidgetKind, WidgetKindTag, UpdateFrequency, MemoriWidget};
 use reqwest::Client;
 use serde::Deserialize;
 use std::collections::HashSet;
@@ -223,4 +225,21 @@ pub async fn github_to_memori_widget(github_id: u32, github: Github) -> Result<M
         local_update_frequency: UpdateFrequency::Never,
     });
     Ok(widget)
-}
\ No newline at end of file
+}
+
+pub struct GithubProvider;
+
+#[async_trait]
+impl WidgetProvider for GithubProvider {
+    fn tag(&self) -> WidgetKindTag {
+        WidgetKindTag::Github
+    }
+
+    async fn default_instance(&self, app: &AppHandle, id: WidgetId) -> Result<MemoriWidget, String> {
+        github_to_memori_widget(id.0, refresh_github_widget(app).await.unwrap_or_default()).await
+    }
+
+    async fn refresh(&self, app: &AppHandle, _widget: &MemoriWidget) -> Result<WidgetKind, String> {
+        Ok(WidgetKind::Github(refresh_github_widget(app).await?))
+    }
+}
@@ -0,0 +1,21 @@
+use crate::widget_data::WidgetProvider;
+use async_trait::async_trait;
+use memori_ui::widgets::{Image, MemoriWidget, WidgetId, WidgetKind, WidgetKindTag};
+use tauri::AppHandle;
+
+pub struct ImageProvider;
+
+#[async_trait]
+impl WidgetProvider for ImageProvider {
+    fn tag(&self) -> WidgetKindTag {
+        WidgetKindTag::Image
+    }
+
+    // the picture is picked and dithered in the editor, there's nothing to fetch
+    async fn default_instance(&self, _app: &AppHandle, id: WidgetId) -> Result<MemoriWidget, String> {
+        Ok(MemoriWidget::with_never_update_frequency(
+            id,
+            WidgetKind::Image(Image::default()),
+        ))
+    }
+}
@@ -1,5 +1,75 @@
 pub mod bus_data;
+pub mod calendar_data;
 pub mod clock_data;
 pub mod github_data;
+pub mod image_data;
+pub mod name_data;
+pub mod qr_data;
+pub mod todo_data;
 pub mod twitch_data;
 pub mod weather_data;
+
+use async_trait::async_trait;
+use bus_data::BusProvider;
+use calendar_data::CalendarProvider;
+use clock_data::ClockProvider;
+use github_data::GithubProvider;
+use image_data::ImageProvider;
+use memori_ui::widgets::{MemoriWidget, WidgetId, WidgetKind, WidgetKindTag};
+use name_data::NameProvider;
+use qr_data::QrProvider;
+use tauri::AppHandle;
+use todo_data::TodoProvider;
+use twitch_data::TwitchProvider;
+use weather_data::WeatherProvider;
+
+/// Where the data for one kind of widget comes from. Adding a kind of widget to the app is
+/// implementing this and listing it in [`PROVIDERS`].
+#[async_trait]
+pub trait WidgetProvider: Send + Sync {
+    /// The kind of widget this provides data for.
+    fn tag(&self) -> WidgetKindTag;
+
+    /// The widget as the editor offers it, filled in with fresh data when there is any.
+    async fn default_instance(&self, app: &AppHandle, id: WidgetId) -> Result<MemoriWidget, String>;
+
+    /// Fetches fresh data for `widget`. Widgets without any data to fetch keep the default,
+    /// which refuses.
+    async fn refresh(&self, _app: &AppHandle, _widget: &MemoriWidget) -> Result<WidgetKind, String> {
+        Err(format!("{:?} widgets have no data to refresh", self.tag()))
+    }
+}
+
+/// Every kind of widget the app can put on the device, in the order the editor lists them.
+pub static PROVIDERS: &[&dyn WidgetProvider] = &[
+    &ClockProvider,
+    &CalendarProvider,
+    &TodoProvider,
+    &WeatherProvider,
+    &BusProvider,
+    &GithubProvider,
+    &TwitchProvider,
+    &QrProvider,
+    &ImageProvider,
+    &NameProvider,
+];
+
+/// The provider for widgets of kind `tag`, if the app has one.
+pub fn provider(tag: WidgetKindTag) -> Option<&'static dyn WidgetProvider> {
+    PROVIDERS.iter().copied().find(|provider| provider.tag() == tag)
+}
+
+/// Fetches fresh data for a widget from the provider behind its kind, keeping its id and
+/// update frequencies.
+pub async fn refresh_widget(app: &AppHandle, widget: &MemoriWidget) -> Result<MemoriWidget, String> {
+    let tag = widget.kind.tag();
+    let provider = provider(tag).ok_or_else(|| format!("{tag:?} widgets have no provider"))?;
+    let kind = provider.refresh(app, widget).await?;
+
+    Ok(MemoriWidget::new(
+        widget.id,
+        kind,
+        widget.get_remote_update_frequency(),
+        widget.get_local_update_frequency(),
+    ))
+}
@@ -0,0 +1,23 @@
+use crate::commands::data::read_store_state;
+use crate::commands::translation_structs::PrefsState;
+use crate::widget_data::WidgetProvider;
+use async_trait::async_trait;
+use memori_ui::widgets::{MemoriWidget, Name, WidgetId, WidgetKind, WidgetKindTag};
+use tauri::AppHandle;
+
+pub struct NameProvider;
+
+#[async_trait]
+impl WidgetProvider for NameProvider {
+    fn tag(&self) -> WidgetKindTag {
+        WidgetKindTag::Name
+    }
+
+    async fn default_instance(&self, app: &AppHandle, id: WidgetId) -> Result<MemoriWidget, String> {
+        let prefs: PrefsState = read_store_state(app, "prefs");
+        Ok(MemoriWidget::with_never_update_frequency(
+            id,
+            WidgetKind::Name(Name::new(prefs.name)),
+        ))
+    }
+}
@@ -0,0 +1,21 @@
+use crate::widget_data::WidgetProvider;
+use async_trait::async_trait;
+use memori_ui::widgets::{MemoriWidget, Qr, WidgetId, WidgetKind, WidgetKindTag};
+use tauri::AppHandle;
+
+pub struct QrProvider;
+
+#[async_trait]
+impl WidgetProvider for QrProvider {
+    fn tag(&self) -> WidgetKindTag {
+        WidgetKindTag::Qr
+    }
+
+    // what the code says is filled in in the editor, there's nothing to fetch
+    async fn default_instance(&self, _app: &AppHandle, id: WidgetId) -> Result<MemoriWidget, String> {
+        Ok(MemoriWidget::with_never_update_frequency(
+            id,
+            WidgetKind::Qr(Qr::default()),
+        ))
+    }
+}
@@ -0,0 +1,52 @@
+use crate::commands::data::read_store_state;
+use crate::commands::translation_structs::TodoState;
+use crate::widget_data::WidgetProvider;
+use async_trait::async_trait;
+use memori_ui::widgets::{MemoriWidget, Todo, WidgetId, WidgetKind, WidgetKindTag};
+use tauri::AppHandle;
+use tauri_plugin_svelte::ManagerExt;
+
+// id of the svelte store the to-do list lives in
+pub const TODO_STORE: &str = "todos";
+
+pub fn read_todos(app: &AppHandle) -> TodoState {
+    read_store_state(app, TODO_STORE)
+}
+
+// replaces the to-do list in the store, the frontend sees the change right away
+pub fn write_todos(app: &AppHandle, todos: &TodoState) -> Result<(), String> {
+    let items = serde_json::to_value(&todos.items).map_err(|e| e.to_string())?;
+    let svelte = app.svelte();
+    svelte
+        .set(TODO_STORE, "items", items)
+        .and_then(|()| svelte.set(TODO_STORE, "nextId", todos.next_id))
+        .and_then(|()| svelte.save(TODO_STORE))
+        .map_err(|e| e.to_string())
+}
+
+pub struct TodoProvider;
+
+#[async_trait]
+impl WidgetProvider for TodoProvider {
+    fn tag(&self) -> WidgetKindTag {
+        WidgetKindTag::Todo
+    }
+
+    // the host pushes the list whenever it changes, so there's nothing to poll for
+    async fn default_instance(&self, app: &AppHandle, id: WidgetId) -> Result<MemoriWidget, String> {
+        Ok(MemoriWidget::with_never_update_frequency(
+            id,
+            WidgetKind::Todo(Todo::new("", read_todos(app).items)),
+        ))
+    }
+
+    async fn refresh(&self, app: &AppHandle, widget: &MemoriWidget) -> Result<WidgetKind, String> {
+        let mut todo = Todo::new("", read_todos(app).items);
+        // Keep the name and the item the device had selected.
+        if let WidgetKind::Todo(old) = &widget.kind {
+            todo.name = old.name.clone();
+            todo.selected = old.selected.min(todo.items.len().saturating_sub(1));
+        }
+        Ok(WidgetKind::Todo(todo))
+    }
+}
@@ -1,5 +1,7 @@
 use crate::commands::{read_store_state, AuthState};
-use memori_ui::widgets::{Twitch, WidgetId, WidgetKind, UpdateFrequency, MemoriWidget};
+use crate::widget_data::WidgetProvider;
+use async_trait::async_trait;
+use memori_ui::widgets::{Twitch, WidgetId, WidgetKind, WidgetKindTag, UpdateFrequency, MemoriWidget};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
@@ -77,3 +79,20 @@ pub async fn twitch_to_memori_widget(twitch_id: u32, twitch: Twitch) -> Result<M
     };
     Ok(widget)
 }
+
+pub struct TwitchProvider;
+
+#[async_trait]
+impl WidgetProvider for TwitchProvider {
+    fn tag(&self) -> WidgetKindTag {
+        WidgetKindTag::Twitch
+    }
+
+    async fn default_instance(&self, app: &AppHandle, id: WidgetId) -> Result<MemoriWidget, String> {
+        twitch_to_memori_widget(id.0, refresh_twitch_widget(app).await.unwrap_or_default()).await
+    }
+
+    async fn refresh(&self, app: &AppHandle, _widget: &MemoriWidget) -> Result<WidgetKind, String> {
+        Ok(WidgetKind::Twitch(refresh_twitch_widget(app).await?))
+    }
+}
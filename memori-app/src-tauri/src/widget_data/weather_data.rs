@@ -1,5 +1,10 @@
 use memori_ui::widgets::Weather;
-use memori_ui::widgets::{MemoriWidget, UpdateFrequency, WidgetId, WidgetKind};
+use crate::commands::data::read_store_state;
+use crate::commands::translation_structs::PrefsState;
+use crate::widget_data::WidgetProvider;
+use async_trait::async_trait;
+use memori_ui::widgets::{MemoriWidget, UpdateFrequency, WidgetId, WidgetKind, WidgetKindTag};
+use tauri::AppHandle;
 use reqwest::Client;
 use serde::Deserialize;
 use std::env;
@@ -41,6 +46,9 @@ struct Rain {
     mmph: f32,
 }
 
+/// Where to get the weather for when we don't know where the user is, Santa Cruz.
+pub(crate) const DEFAULT_LOCATION: (f64, f64) = (36.97145812967173, -122.03535749883835);
+
 pub(crate) async fn refresh_weather_widget(lat: f64, lon: f64) -> Result<Weather, String> {
     println!("Refresh weather widget called");
     let env = include_bytes!("../../.env");
@@ -55,7 +63,7 @@ pub(crate) async fn refresh_weather_widget(lat: f64, lon: f64) -> Result<Weather
         }
     }
     if api_key.is_empty() {
-        panic!("WEATHER_API_KEY not found");
+        return Err("WEATHER_API_KEY not found".to_string());
     }
     let appid = api_key.as_str();
     println!("appid: {}", appid);
@@ -120,3 +128,32 @@ pub async fn weather_to_memori_widget(
     };
     Ok(widget)
 }
+
+/// Where the phone last was, or [`DEFAULT_LOCATION`] if we never found out.
+fn location(app: &AppHandle) -> (f64, f64) {
+    let prefs: PrefsState = read_store_state(app, "prefs");
+    prefs
+        .lastKnownLocation
+        .map(|pos| (pos.coords.latitude, pos.coords.longitude))
+        .unwrap_or(DEFAULT_LOCATION)
+}
+
+pub struct WeatherProvider;
+
+#[async_trait]
+impl WidgetProvider for WeatherProvider {
+    fn tag(&self) -> WidgetKindTag {
+        WidgetKindTag::Weather
+    }
+
+    async fn default_instance(&self, app: &AppHandle, id: WidgetId) -> Result<MemoriWidget, String> {
+        let (lat, lon) = location(app);
+        let weather = refresh_weather_widget(lat, lon).await.unwrap_or_default();
+        weather_to_memori_widget(id.0, weather).await
+    }
+
+    async fn refresh(&self, app: &AppHandle, _widget: &MemoriWidget) -> Result<WidgetKind, String> {
+        let (lat, lon) = location(app);
+        Ok(WidgetKind::Weather(refresh_weather_widget(lat, lon).await?))
+    }
+}
@@ -1,4 +1,5 @@
 import type { WidgetView } from '@/features/widgets/model/widget-frame'
+import type { Bitmap, DitherMethod, SlotSize } from '@/tauri'
 
 export interface SortableItemDraft {
 	name: string
@@ -14,6 +15,11 @@ export interface SortableItemDraft {
 	busStop: string
 	twitchUser: string
 	githubRepo: string
+	qrPayload: string
+	qrLabel: string
+	imageBitmap: Bitmap | null
+	imageSlot: SlotSize
+	imageDither: DitherMethod
 }
 
 const EMPTY_DRAFT: SortableItemDraft = {
@@ -30,6 +36,11 @@ const EMPTY_DRAFT: SortableItemDraft = {
 	busStop: '',
 	twitchUser: '',
 	githubRepo: '',
+	qrPayload: '',
+	qrLabel: '',
+	imageBitmap: null,
+	imageSlot: 'Full',
+	imageDither: 'FloydSteinberg',
 }
 
 function hasText(value: string): boolean {
@@ -49,6 +60,11 @@ export function createDraftFromKind(kind: WidgetView['kind']): SortableItemDraft
 		draft.twitchUser = kind.Twitch.username
 	} else if ('Github' in kind) {
 		draft.githubRepo = kind.Github.repo ?? ''
+	} else if ('Qr' in kind) {
+		draft.qrPayload = kind.Qr.payload
+		draft.qrLabel = kind.Qr.label
+	} else if ('Image' in kind) {
+		draft.imageBitmap = kind.Image.bitmap
 	}
 
 	return draft
@@ -110,6 +126,17 @@ export function buildKindFromDraft(
 		}
 	}
 
+	if ('Qr' in kind) {
+		const payload = draft.qrPayload.trim()
+		if (!hasText(payload)) return null
+		return { Qr: { payload, label: draft.qrLabel.trim() } }
+	}
+
+	if ('Image' in kind) {
+		if (!draft.imageBitmap) return null
+		return { Image: { bitmap: draft.imageBitmap } }
+	}
+
 	return null
 }
 
@@ -120,7 +147,9 @@ export function isKindEditable(kind: WidgetView['kind']): boolean {
 		'Weather' in kind ||
 		'Bus' in kind ||
 		'Twitch' in kind ||
-		'Github' in kind
+		'Github' in kind ||
+		'Qr' in kind ||
+		'Image' in kind
 	)
 }
 
@@ -131,6 +160,8 @@ export type SortableItemKindVariant =
 	| 'Bus'
 	| 'Twitch'
 	| 'Github'
+	| 'Qr'
+	| 'Image'
 	| 'Readonly'
 
 export function getKindVariant(kind: WidgetView['kind']): SortableItemKindVariant {
@@ -140,6 +171,8 @@ export function getKindVariant(kind: WidgetView['kind']): SortableItemKindVarian
 	if ('Bus' in kind) return 'Bus'
 	if ('Twitch' in kind) return 'Twitch'
 	if ('Github' in kind) return 'Github'
+	if ('Qr' in kind) return 'Qr'
+	if ('Image' in kind) return 'Image'
 	return 'Readonly'
 }
 
@@ -1,12 +1,18 @@
-import type { Clock } from '@/tauri'
+import type { Clock, ClockFormat } from '@/tauri'
 
 export type CompactClock = { time: string; zone: string }
 
-export function getCurrentClock(date = new Date()): Clock {
+const DEFAULT_CLOCK_FORMAT: ClockFormat = {
+	twentyFourHour: true,
+	showDate: false,
+	showSeconds: false,
+}
+
+export function getCurrentClock(date = new Date(), format = DEFAULT_CLOCK_FORMAT): Clock {
+	const localMs = date.getTime() - date.getTimezoneOffset() * 60_000
 	return {
-		hours: date.getHours(),
-		minutes: date.getMinutes(),
-		seconds: date.getSeconds(),
+		time: { secs: Math.floor(localMs / 1000) },
+		format,
 	}
 }
 
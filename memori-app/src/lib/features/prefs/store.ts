@@ -12,6 +12,8 @@ const initialPrefsState: PrefsState = {
 	lastKnownBleAddress: null,
 	systemOptions: getSystemOptions(),
 	name: '',
+	calendarSource: null,
+	calendarLookaheadDays: 7,
 }
 
 const prefsStore = new RuneStore<PrefsState>('prefs', initialPrefsState, {
@@ -11,4 +11,8 @@ export type PrefsState = {
 	lastKnownBleAddress: string | null
 	systemOptions: SystemOptions
 	name: string
+	/** Path or URL of the iCalendar file the calendar widget shows. */
+	calendarSource: string | null
+	/** How many days ahead the calendar widget looks for events. */
+	calendarLookaheadDays: number
 }
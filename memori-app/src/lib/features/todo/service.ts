@@ -0,0 +1,22 @@
+import type { ResultAsync } from 'neverthrow'
+import { type AppError, commands, type TodoItem, tryCmd } from '@/tauri'
+
+export function addTodo(text: string): ResultAsync<TodoItem[], AppError> {
+	return tryCmd(commands.addTodo(text))
+}
+
+export function editTodo(id: number, text: string): ResultAsync<TodoItem[], AppError> {
+	return tryCmd(commands.editTodo(id, text))
+}
+
+export function completeTodo(id: number, done: boolean): ResultAsync<TodoItem[], AppError> {
+	return tryCmd(commands.completeTodo(id, done))
+}
+
+export function reorderTodos(ids: number[]): ResultAsync<TodoItem[], AppError> {
+	return tryCmd(commands.reorderTodos(ids))
+}
+
+export function removeTodo(id: number): ResultAsync<TodoItem[], AppError> {
+	return tryCmd(commands.removeTodo(id))
+}
@@ -0,0 +1,39 @@
+import { RuneStore } from '@tauri-store/svelte'
+import type { TodoItem } from '@/tauri'
+
+// Written by the backend's todo commands, so the list stays the same on the
+// device and in the app. Change it through the service, not directly.
+type TodoState = {
+	items: TodoItem[]
+	nextId: number
+}
+
+export type { TodoState }
+
+const initialTodoState: TodoState = {
+	items: [],
+	nextId: 1,
+}
+
+const todoStore = new RuneStore<TodoState>('todos', initialTodoState, {
+	autoStart: false,
+	saveOnChange: false,
+	hooks: {
+		error: error => {
+			console.error('Todo store error:', error)
+		},
+	},
+})
+
+export const todoState = todoStore.state
+
+let startPromise: Promise<void> | null = null
+
+export function startTodoStore(): Promise<void> {
+	startPromise ??= todoStore.start().catch(error => {
+		startPromise = null
+		throw error
+	})
+
+	return startPromise
+}
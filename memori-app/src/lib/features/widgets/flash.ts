@@ -123,7 +123,7 @@ export function selectFlashPayload(state: WidgetsState): MemoriStateInput {
 				? {
 						...widget,
 						kind: {
-							Clock: getCurrentClock(now),
+							Clock: getCurrentClock(now, widget.kind.Clock.format),
 						},
 					}
 				: widget
@@ -141,9 +141,11 @@ export function kindToDisplay(kind: MemoriWidget['kind']): WidgetDisplay {
 	}
 
 	if ('Clock' in kind) {
-		const hours = String(kind.Clock.hours).padStart(2, '0')
-		const minutes = String(kind.Clock.minutes).padStart(2, '0')
-		const seconds = String(kind.Clock.seconds).padStart(2, '0')
+		// Seconds since the epoch in local time, so read them back as UTC.
+		const time = new Date(kind.Clock.time.secs * 1000)
+		const hours = String(time.getUTCHours()).padStart(2, '0')
+		const minutes = String(time.getUTCMinutes()).padStart(2, '0')
+		const seconds = String(time.getUTCSeconds()).padStart(2, '0')
 		return { name: 'Clock', content: `${hours}:${minutes}:${seconds}` }
 	}
 
@@ -167,6 +169,25 @@ export function kindToDisplay(kind: MemoriWidget['kind']): WidgetDisplay {
 		return { name: 'Twitch', content: kind.Twitch.username }
 	}
 
+	if ('Calendar' in kind) {
+		const next = kind.Calendar.events[0]
+		return { name: 'Calendar', content: next?.title ?? 'No upcoming events' }
+	}
+
+	if ('Qr' in kind) {
+		return { name: 'QR Code', content: kind.Qr.label || kind.Qr.payload }
+	}
+
+	if ('Image' in kind) {
+		const { width, height } = kind.Image.bitmap
+		return { name: 'Image', content: width ? `${width}x${height}` : 'No image' }
+	}
+
+	if ('Todo' in kind) {
+		const done = kind.Todo.items.filter(item => item.done).length
+		return { name: 'To-do', content: `${done}/${kind.Todo.items.length} done` }
+	}
+
 	return { name: 'Widget', content: '' }
 }
 
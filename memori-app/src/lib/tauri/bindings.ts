@@ -21,6 +21,18 @@ async disconnectDevice() : Promise<Result<null, string>> {
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * Scans for nearby Memori devices for `seconds`, emitting a `NearbyDevice` event
+ * as they are found and returning everything that was seen, closest first.
+ */
+async scanDevices(seconds: number) : Promise<Result<NearbyDevice[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("scan_devices", { seconds }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async isConnected() : Promise<Result<boolean, string>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("is_connected") };
@@ -32,7 +44,7 @@ async isConnected() : Promise<Result<boolean, string>> {
 /**
  * Sends back a copy of the different widget types that are used during drag and drop.
  */
-async getWidgetKinds() : Promise<Result<[MemoriWidget, MemoriWidget, MemoriWidget, MemoriWidget, MemoriWidget, MemoriWidget], string>> {
+async getWidgetKinds() : Promise<Result<MemoriWidget[], string>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("get_widget_kinds") };
 } catch (e) {
@@ -70,6 +82,21 @@ async flashMemoriState(memoriState: MemoriStateInput) : Promise<Result<null, str
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * Renders the active frame of the `MemoriStateInput` like the device would,
+ * returns the PNG bytes.
+ * 
+ * # Errors
+ * Could error if the state isn't valid.
+ */
+async previewMemoriState(memoriState: MemoriStateInput, darkMode: boolean) : Promise<Result<number[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("preview_memori_state", { memoriState, darkMode }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async startOauthServer() : Promise<Result<number, string>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("start_oauth_server") };
@@ -93,12 +120,121 @@ async getGithubRepos() : Promise<Result<string[], string>> {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
+},
+/**
+ * Sends back the to-do list, in order.
+ */
+async getTodos() : Promise<Result<TodoItem[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_todos") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Adds an item to the end of the to-do list, returns the new list.
+ * 
+ * # Errors
+ * Could error if the text is empty or the list couldn't be saved.
+ */
+async addTodo(text: string) : Promise<Result<TodoItem[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_todo", { text }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Changes the text of an item, returns the new list.
+ * 
+ * # Errors
+ * Could error if there's no item with the id, the text is empty, or the list
+ * couldn't be saved.
+ */
+async editTodo(id: number, text: string) : Promise<Result<TodoItem[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("edit_todo", { id, text }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Checks an item off, or unchecks it, returns the new list.
+ * 
+ * # Errors
+ * Could error if there's no item with the id or the list couldn't be saved.
+ */
+async completeTodo(id: number, done: boolean) : Promise<Result<TodoItem[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("complete_todo", { id, done }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Puts the items in the order of `ids`, returns the new list.
+ * 
+ * # Errors
+ * Could error if `ids` isn't exactly the ids of the items on the list, or the
+ * list couldn't be saved.
+ */
+async reorderTodos(ids: number[]) : Promise<Result<TodoItem[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("reorder_todos", { ids }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Takes an item off the list, returns the new list.
+ * 
+ * # Errors
+ * Could error if there's no item with the id or the list couldn't be saved.
+ */
+async removeTodo(id: number) : Promise<Result<TodoItem[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_todo", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Turns a PNG or JPEG into a black and white picture for an image widget in a
+ * `slot` sized slot. The picture is made as big as fits, keeping its shape.
+ * 
+ * # Errors
+ * Could error if the file isn't a PNG or JPEG the decoder understands.
+ */
+async ditherImage(image: number[], slot: SlotSize, method: DitherMethod) : Promise<Result<Bitmap, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("dither_image", { image, slot, method }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
 }
 }
 
 /** user-defined events **/
 
 
+export const events = __makeEvents__<{
+connectionStatus: ConnectionStatus,
+deviceBattery: DeviceBattery,
+deviceInput: DeviceInput,
+nearbyDevice: NearbyDevice
+}>({
+connectionStatus: "connection-status",
+deviceBattery: "device-battery",
+deviceInput: "device-input",
+nearbyDevice: "nearby-device"
+})
 
 /** user-defined constants **/
 
@@ -106,13 +242,107 @@ async getGithubRepos() : Promise<Result<string[], string>> {
 
 /** user-defined types **/
 
+export type BatteryStatus = { 
+/**
+ * How full the battery is, 0 to 100.
+ */
+percent: number; charging: boolean }
+/**
+ * A 1-bit image, packed 8 pixels to a byte with the leftmost pixel in the
+ * highest bit, every row starting on a new byte.
+ */
+export type Bitmap = { width: number; height: number; bits: number[] }
 /**
  * Define a widget by its data
  */
-export type Bus = { stop: [string, string]; predictions: ([string, string, number])[] }
-export type Clock = { seconds: number; minutes: number; hours: number }
+export type Bus = { stop: string; predictions: ([string, string, number])[] }
+export type Button = "Prev" | "Next" | "Select"
+/**
+ * The next events on a calendar.
+ */
+export type Calendar = { 
+/**
+ * Name of the calendar, shown as the title.
+ */
+name: string; 
+/**
+ * Soonest first.
+ */
+events: CalendarEvent[]; 
+/**
+ * The time on the device, events that ended before it aren't shown.
+ */
+now: LocalTime }
+export type CalendarEvent = { title: string; start: LocalTime; end: LocalTime; 
+/**
+ * Lasts the whole day, the times are left out.
+ */
+all_day: boolean }
+export type Clock = { 
+/**
+ * The time shown, the device replaces it with its own clock once the host
+ * has synced it.
+ */
+time: LocalTime; format: ClockFormat }
+/**
+ * How a [`Clock`] shows the time.
+ */
+export type ClockFormat = { 
+/**
+ * `13:05` rather than `1:05 PM`.
+ */
+twentyFourHour: boolean; 
+/**
+ * Show the date under the time.
+ */
+showDate: boolean; 
+/**
+ * Show the seconds, only useful with a local update frequency of a second.
+ */
+showSeconds: boolean }
+/**
+ * Sent to the frontend whenever the bluetooth link goes down or comes back.
+ */
+export type ConnectionStatus = "Connected" | { Reconnecting: { attempt: number } } | "Disconnected"
+/**
+ * Sent to the frontend whenever the device's battery level or charging changes.
+ */
+export type DeviceBattery = BatteryStatus
+/**
+ * Sent to the frontend whenever a button is pressed on the device.
+ */
+export type DeviceInput = InputEvent
 export type DeviceMode = "RealDevice" | "Simulator"
+/**
+ * How grays are turned into black and white pixels.
+ */
+export type DitherMethod = 
+/**
+ * Spreads all of a pixel's error to its neighbours, smooth gradients.
+ */
+"FloydSteinberg" | 
+/**
+ * Spreads three quarters of the error, more contrast and less noise, the
+ * classic look of small black and white screens.
+ */
+"Atkinson"
 export type Github = { username: string; repo: string | null; openIssues: number; openPrs: number; stars: number; notifications: number; commits: [number, number, number, number, number, number, number]; weekday: number }
+/**
+ * A black and white picture, drawn pixel for pixel over its slot.
+ * 
+ * Pixels that are on are black, whether the display is in dark mode or not,
+ * so pictures don't turn into negatives.
+ */
+export type Image = { bitmap: Bitmap }
+export type InputEvent = { button: Button; press: Press }
+/**
+ * A local date and time, to the second.
+ */
+export type LocalTime = { 
+/**
+ * Seconds since midnight on 1970-01-01, local time.
+ */
+secs: number }
 export type MemoriLayout = 
 /**
  * ┌─────────────────┐
@@ -206,14 +436,130 @@ export type MemoriLayout =
  * │  Left  │ Right  │
  * └────────┴────────┘
  */
-{ Fourths: { topLeft: WidgetId; topRight: WidgetId; bottomLeft: WidgetId; bottomRight: WidgetId } }
+{ Fourths: { topLeft: WidgetId; topRight: WidgetId; bottomLeft: WidgetId; bottomRight: WidgetId } } | 
+/**
+ * Any number of layouts next to or on top of each other, sized by
+ * `constraints` (one per child). Nest these for anything the shapes above
+ * can't do.
+ * 
+ * ┌─────┬───────────┬─────┐
+ * │     │           │     │
+ * │     │           │     │
+ * │ 25% │    50%    │ 25% │
+ * │     │           │     │
+ * │     │           │     │
+ * └─────┴───────────┴─────┘
+ */
+{ Split: { direction: SplitDirection; constraints: SplitSize[]; children: MemoriLayout[] } }
 export type MemoriStateInput = { activeFrameIdx: number; widgets: MemoriWidget[]; frames: MemoriLayout[]; frameTime: number }
 export type MemoriWidget = { id: WidgetId; kind: WidgetKind; remoteUpdateFrequency: UpdateFrequency; localUpdateFrequency: UpdateFrequency }
 /**
  * Define a widget by its data
  */
 export type Name = { name: string }
-export type Pair = { code: string }
+/**
+ * A Memori device seen while scanning, sent to the frontend as soon as it shows up.
+ */
+export type NearbyDevice = { name: string | null; code: string | null; address: string; rssi: number | null }
+export type Pair = { code: string; 
+/**
+ * Show a QR code of the [`PAIR_LINK`] next to the code.
+ */
+qr: boolean }
+export type Press = 
+/**
+ * Let go before [`LONG_PRESS_MS`].
+ */
+"Short" | 
+/**
+ * Held for [`LONG_PRESS_MS`], reported right away rather than on release.
+ */
+"Long"
+/**
+ * A QR code, like for Wi-Fi credentials or a meeting link, with an optional
+ * label next to it.
+ */
+export type Qr = { 
+/**
+ * What the code encodes.
+ */
+payload: string; 
+/**
+ * Shown next to the code when there's room.
+ */
+label: string }
+/**
+ * The slots a layout can give a widget.
+ */
+export type SlotSize = 
+/**
+ * The whole display.
+ */
+"Full" | 
+/**
+ * Half of a vertical split, narrow and tall.
+ */
+"Tall" | 
+/**
+ * Half of a horizontal split, wide and short.
+ */
+"Wide" | 
+/**
+ * A quarter of the display.
+ */
+"Quarter"
+/**
+ * Which way a [`MemoriLayout::Split`] lays out its children.
+ */
+export type SplitDirection = 
+/**
+ * Side by side, left to right.
+ */
+"Horizontal" | 
+/**
+ * Stacked, top to bottom.
+ */
+"Vertical"
+/**
+ * How much room one child of a [`MemoriLayout::Split`] gets.
+ * 
+ * Lengths are in terminal cells, the way the whole UI is laid out.
+ */
+export type SplitSize = { Percentage: number } | 
+/**
+ * `numerator / denominator` of the space.
+ */
+{ Ratio: [number, number] } | 
+/**
+ * Exactly this many cells.
+ */
+{ Length: number } | 
+/**
+ * At least this many cells.
+ */
+{ Min: number } | 
+/**
+ * Whatever is left, shared with the other `Fill`s by weight.
+ */
+{ Fill: number }
+/**
+ * A checklist, scrolled so the selected item is always in view.
+ */
+export type Todo = { 
+/**
+ * Name of the list, shown as the title.
+ */
+name: string; items: TodoItem[]; 
+/**
+ * Index into `items` of the item the device would check off next.
+ */
+selected: number }
+export type TodoItem = { 
+/**
+ * Stays the same when the item is edited or moved, so the host knows
+ * which one the device checked off.
+ */
+id: number; text: string; done: boolean }
 /**
  * Define a widget by its data
  */
@@ -225,7 +571,7 @@ export type UserInfo = { id: string; name: string; email: string; avatar: string
  */
 export type Weather = { city: string; temp: string; clouds: string; wind: string; rain: string; humidity: string; description: string }
 export type WidgetId = number
-export type WidgetKind = { Name: Name } | { Clock: Clock } | { Github: Github } | { Weather: Weather } | { Bus: Bus } | { Twitch: Twitch } | { Pair: Pair }
+export type WidgetKind = { Name: Name } | { Clock: Clock } | { Github: Github } | { Weather: Weather } | { Bus: Bus } | { Twitch: Twitch } | { Pair: Pair } | { Calendar: Calendar } | { Todo: Todo } | { Qr: Qr } | { Image: Image }
 
 /** tauri-specta globals **/
 
@@ -0,0 +1,124 @@
+//! The battery, measured on D0 through the two 220k resistors that halve its
+//! voltage for the ADC.
+//!
+//! [`battery_task`] keeps [`status`] up to date and signals [`BATTERY_CHANGED`]
+//! for the BLE side to notify the host. The display only shows the battery
+//! when it needs attention, see [`indicator`].
+
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, mutex::Mutex, signal::Signal};
+use embassy_time::{Duration, Timer};
+use esp_hal::{
+    analog::adc::{Adc, AdcCalCurve, AdcConfig, Attenuation},
+    peripherals::{ADC1, GPIO2},
+};
+use log::info;
+use memori_ui::battery::BatteryStatus;
+
+use crate::RenderTx;
+
+/// How often the battery is measured.
+const MEASURE_PERIOD: Duration = Duration::from_secs(30);
+
+/// Readings averaged into one measurement, the ADC is noisy.
+const SAMPLES: u32 = 16;
+
+/// Each measurement moves the smoothed voltage this fraction of the way, so a
+/// burst of radio or a display refresh sagging the battery doesn't show.
+const SMOOTHING: i32 = 4;
+
+/// The charger holds the battery at about 4.2V, a battery that's just sitting
+/// there drops below this within minutes of being unplugged.
+const CHARGING_MILLIVOLTS: i32 = 4_190;
+
+/// Battery voltage to charge left for a single LiPo cell under a light load,
+/// highest first. In between two points it's a straight line.
+const CURVE: [(i32, u8); 12] = [
+    (4_150, 100),
+    (4_050, 90),
+    (3_970, 80),
+    (3_900, 70),
+    (3_840, 60),
+    (3_800, 50),
+    (3_770, 40),
+    (3_740, 30),
+    (3_700, 20),
+    (3_650, 10),
+    (3_500, 5),
+    (3_300, 0),
+];
+
+static STATUS: Mutex<CriticalSectionRawMutex, Option<BatteryStatus>> = Mutex::new(None);
+
+/// Signaled with the new status every time it changes.
+pub static BATTERY_CHANGED: Signal<CriticalSectionRawMutex, BatteryStatus> = Signal::new();
+
+/// The last measurement, `None` until the first one is done.
+pub async fn status() -> Option<BatteryStatus> {
+    *STATUS.lock().await
+}
+
+/// Whether the battery is about to run out.
+pub async fn is_low() -> bool {
+    status().await.is_some_and(|status| status.is_low())
+}
+
+/// What to show in the corner of the display, the battery only while it's low
+/// or charging.
+pub async fn indicator() -> Option<BatteryStatus> {
+    status()
+        .await
+        .filter(|status| status.is_low() || status.charging)
+}
+
+fn percent(millivolts: i32) -> u8 {
+    let (full, _) = CURVE[0];
+    if millivolts >= full {
+        return 100;
+    }
+
+    for pair in CURVE.windows(2) {
+        let [(high_mv, high), (low_mv, low)] = [pair[0], pair[1]];
+        if millivolts >= low_mv {
+            let span = i32::from(high - low);
+            return low + ((millivolts - low_mv) * span / (high_mv - low_mv)) as u8;
+        }
+    }
+    0
+}
+
+/// Measures the battery every [`MEASURE_PERIOD`].
+#[embassy_executor::task]
+pub async fn battery_task(adc: ADC1<'static>, pin: GPIO2<'static>, render_tx: RenderTx) {
+    let mut config = AdcConfig::new();
+    let mut pin = config.enable_pin_with_cal::<_, AdcCalCurve<ADC1>>(pin, Attenuation::_11dB);
+    let mut adc = Adc::new(adc, config).into_async();
+
+    let mut smoothed = None;
+    loop {
+        let mut total = 0;
+        for _ in 0..SAMPLES {
+            total += u32::from(adc.read_oneshot(&mut pin).await);
+        }
+        // Undo the divider.
+        let millivolts = (total / SAMPLES * 2) as i32;
+        let smooth = match smoothed {
+            Some(previous) => previous + (millivolts - previous) / SMOOTHING,
+            None => millivolts,
+        };
+        smoothed = Some(smooth);
+
+        let new = BatteryStatus::new(percent(smooth), smooth >= CHARGING_MILLIVOLTS);
+        let shown_before = indicator().await;
+        let old = STATUS.lock().await.replace(new);
+
+        if old != Some(new) {
+            info!("battery at {smooth}mV: {new:?}");
+            BATTERY_CHANGED.signal(new);
+            if indicator().await != shown_before {
+                render_tx.send(crate::Render {}).await;
+            }
+        }
+
+        Timer::after(MEASURE_PERIOD).await;
+    }
+}
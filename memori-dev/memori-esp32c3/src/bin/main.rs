@@ -21,8 +21,17 @@ use esp_hal::time::Rate;
 use esp_hal::timer::timg::TimerGroup;
 use esp_hal::{Blocking, clock::CpuClock};
 use log::info;
+use memori_esp32c3::battery::{self, battery_task};
 use memori_esp32c3::ble::ble_task;
-use memori_esp32c3::{MemTermInitPins, Render, RenderRx, setup_term};
+use memori_esp32c3::config::{self, device_config, set_device_config};
+use memori_esp32c3::frame_rotation::frame_rotation_task;
+use memori_esp32c3::input::{ButtonPins, input_forward_task, input_task};
+use memori_esp32c3::persist;
+use memori_esp32c3::power::{self, power_task};
+use memori_esp32c3::wall_clock;
+use memori_esp32c3::widget_tasks::spawn_widget_tasks;
+use memori_esp32c3::{MemTermInitPins, Render, RenderRx, set_upside_down, setup_term};
+use memori_ui::overlay::PixelLayer;
 use memori_ui::widgets::{MemoriWidget, Pair, WidgetId, WidgetKind};
 use memori_ui::{Memori, MemoriState};
 use static_cell::StaticCell;
@@ -98,28 +107,16 @@ async fn main(spawner: Spawner) -> () {
 
     info!("My id: {DEVICE_ID}");
 
-    // at build time we can set a random string as this devices "connection-id"
-    // that then gets picked up by the phone.
-    let mem_state = MEMORI_STATE.init_with(|| {
-        // in this function it would be nice if we stored something
-        // in flash that told us if it was already onboarded or if its
-        // a new device.
-
-        // let mem_state = MemoriState::default();
-        //
-        let pair_widget = MemoriWidget::new(
-            WidgetId(0),
-            WidgetKind::Pair(Pair::new(alloc::string::String::from(DEVICE_ID))),
-            memori_ui::widgets::UpdateFrequency::Never,
-            memori_ui::widgets::UpdateFrequency::Never,
-        );
-
-        let frame = memori_ui::layout::MemoriLayout::Full(WidgetId(0));
-
-        let mem_state = MemoriState::new(0, [pair_widget], alloc::vec![frame], 0);
+    persist::init(peripherals.FLASH).await;
+    if let Some(config) = persist::load_config().await {
+        set_device_config(config).await;
+    }
 
-        Mutex::new(mem_state)
-    });
+    // whatever the phone last sent us, or the pairing screen if it never did.
+    let mut restored = persist::load_state().await.unwrap_or_else(pairing_state);
+    // the time and the frame we were on if we just woke up from sleeping.
+    power::restore_after_deep_sleep(&mut restored).await;
+    let mem_state = MEMORI_STATE.init(Mutex::new(restored));
 
     let transport = BLE_TRANSPORT.init(Mutex::<CriticalSectionRawMutex, DeviceBLETransport>::new(
         DeviceBLETransport::new(),
@@ -135,12 +132,41 @@ async fn main(spawner: Spawner) -> () {
 
     render_tx.send(Render {}).await;
 
+    // a restored state needs its widgets kept up to date like it just came from the host.
+    for widget in mem_state.lock().await.widgets.values() {
+        spawn_widget_tasks(widget, transport, mem_state, render_tx, spawner).await;
+    }
+
     // Temporarily disable the e-paper UI task while validating BLE advertising.
     // The display driver performs blocking operations that can starve async BLE startup.
     spawner
         .spawn(ui_task(spi_bus, term_init_pins, mem_state, render_rx))
         .expect("Failed to begin ui_task");
 
+    spawner
+        .spawn(frame_rotation_task(mem_state, render_tx))
+        .expect("Failed to start frame_rotation_task");
+
+    let button_pins = ButtonPins {
+        prev: peripherals.GPIO7,
+        next: peripherals.GPIO20,
+        select: peripherals.GPIO9,
+    };
+    spawner
+        .spawn(input_task(button_pins, mem_state, render_tx))
+        .expect("Failed to start input_task");
+    spawner
+        .spawn(input_forward_task(transport, mem_state, render_tx))
+        .expect("Failed to start input_forward_task");
+
+    spawner
+        .spawn(battery_task(peripherals.ADC1, peripherals.GPIO2, render_tx))
+        .expect("Failed to start battery_task");
+
+    spawner
+        .spawn(power_task(peripherals.LPWR, mem_state, render_rx))
+        .expect("Failed to start power_task");
+
     spawner
         .spawn(ble_task(
             radio,
@@ -153,6 +179,22 @@ async fn main(spawner: Spawner) -> () {
         .expect("Failed to start ble_task");
 }
 
+/// The state of a device that has never been set up, only showing the pairing code.
+// at build time we can set a random string as this devices "connection-id"
+// that then gets picked up by the phone.
+fn pairing_state() -> MemoriState {
+    let pair_widget = MemoriWidget::new(
+        WidgetId(0),
+        WidgetKind::Pair(Pair::new(alloc::string::String::from(DEVICE_ID)).with_qr()),
+        memori_ui::widgets::UpdateFrequency::Never,
+        memori_ui::widgets::UpdateFrequency::Never,
+    );
+
+    let frame = memori_ui::layout::MemoriLayout::Full(WidgetId(0));
+
+    MemoriState::new(0, [pair_widget], alloc::vec![frame], 0)
+}
+
 /// The UI task for our application.
 #[allow(
     clippy::large_stack_frames,
@@ -168,16 +210,34 @@ pub async fn ui_task(
     info!("UI Task Begun!");
 
     let mut display = Display290BlackWhite::new();
-    let term = setup_term(spi, &mut display, term_init_pins);
+    let pixels = PixelLayer::new();
+    let term = setup_term(spi, &mut display, term_init_pins, pixels.clone());
 
     info!("initialized terminal");
-    let mut memori = Memori::new(term);
+    let mut memori = Memori::with_pixel_layer(term, pixels);
+    let mut upside_down = false;
 
     loop {
         // wait till we receive a Render message
         let _ = render_rx.receive().await;
 
-        let state_guard = state.lock().await;
+        memori.set_inverted(device_config().await.dark_mode());
+
+        // Turned before drawing, everything already on the display was drawn
+        // the other way around and has to be drawn again.
+        if config::upside_down() != upside_down {
+            upside_down = config::upside_down();
+            set_upside_down(memori.backend_mut().display_mut(), upside_down);
+            memori
+                .redraw_all()
+                .expect("memori should not panic on render");
+        }
+
+        let mut state_guard = state.lock().await;
+        if let Some(now) = wall_clock::now().await {
+            state_guard.set_time(now);
+        }
+        state_guard.set_battery(battery::indicator().await);
         let state = &*state_guard;
         memori
             .update(state)
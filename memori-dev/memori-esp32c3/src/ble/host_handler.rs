@@ -1,32 +1,26 @@
-use core::sync::atomic::{AtomicU32, Ordering};
-
-use alloc::vec::Vec;
-use ble_device::{BLE_CONNECTED, DeviceBLETransport};
+use ble_device::DeviceBLETransport;
 use embassy_executor::Spawner;
 use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, mutex::Mutex};
-use embassy_time::{Duration, Timer};
-use log::{error, info};
-use memori_ui::{
-    MemoriState,
-    widgets::{MemoriWidget, UpdateFrequency},
-};
-use transport::{DeviceTransport, TransError, ble_types::*};
+use log::{error, info, warn};
+use memori_ui::MemoriState;
+use transport::{ConfigApplied, TransError, protocol::*};
 use trouble_host::prelude::*;
 
-use crate::local_widget_update::widget_update_task;
+use crate::config::{MAX_NAME_LEN, device_config, set_device_config};
+use crate::persist::{save_config, save_state};
+use crate::power;
+use crate::wall_clock;
+use crate::widget_tasks::{
+    record_push, retire_all_widget_tasks, retire_widget_tasks, spawn_widget_tasks,
+};
 use crate::{
     RenderTx,
     ble::{Server, send_packet},
 };
 
-/// Keeps track of the generation of refresh tasks, basically
-/// a way to tell older tasks to die when we update the state with
-/// new update-able widgets.
-static REFRESH_GENERATION: AtomicU32 = AtomicU32::new(0);
-
 /// Act on any host commands.
 pub(super) async fn handle_host_cmd<P: PacketPool>(
-    cmd: HostBLECommand,
+    cmd: HostCommand,
     msg_id: MessageID,
     server: &Server<'_>,
     state: &'static Mutex<CriticalSectionRawMutex, MemoriState>,
@@ -36,135 +30,166 @@ pub(super) async fn handle_host_cmd<P: PacketPool>(
     conn: &GattConnection<'_, '_, P>,
 ) {
     info!("[transport] received cmd {:#?}", cmd);
+    power::stay_awake().await;
 
     let mut state_guard = state.lock().await;
     let mem_state = &mut *state_guard;
+    // Whether the state should be written to flash once we're done. Pushed data
+    // doesn't count, the host sends it again anyway and it would wear out the flash.
+    let mut persist = false;
     let resp = match cmd {
-        HostBLECommand::GetWidget { widget_id } => DeviceBLEResponse::WidgetGet {
+        HostCommand::GetWidget { widget_id } => DeviceResponse::GetWidget {
             result: mem_state
                 .widgets
                 .get(&widget_id)
                 .cloned()
                 .ok_or(TransError::WidgetNotFound),
         },
-        HostBLECommand::SetState { state: new_state } => {
+        HostCommand::SetState { state: new_state } => 'set_state: {
+            if let Err(e) = new_state.validate() {
+                warn!("Refusing state: {e}");
+                break 'set_state DeviceResponse::SetState {
+                    result: Err(TransError::InvalidState(e)),
+                };
+            }
             *mem_state = new_state;
 
-            let current_gen = REFRESH_GENERATION.load(Ordering::Relaxed);
-            let new_gen = current_gen.wrapping_add(1);
-            REFRESH_GENERATION.store(new_gen, Ordering::Relaxed);
-
-            let widgets_needing_refresh = mem_state
-                .widgets
-                .iter()
-                // Filter for widgets that have an update frequency
-                .filter_map(|(_, w)| {
-                    if !matches!(w.get_remote_update_frequency(), UpdateFrequency::Never) {
-                        Some(w)
-                    } else {
-                        None
-                    }
-                })
-                .collect::<Vec<_>>();
-
-            for widget in widgets_needing_refresh {
-                let _ = spawner
-                    .spawn(refresh_widget_task(widget.clone(), transport, state,render_tx.clone(),new_gen))
-                    .inspect_err(|e| error!("Error with spawning refresh task: {e:#?}, aborting spawning refresh for this task, may not work as intended."));
+            retire_all_widget_tasks().await;
+            for widget in mem_state.widgets.values() {
+                spawn_widget_tasks(widget, transport, state, render_tx.clone(), spawner).await;
             }
 
-            let widgets_to_update = {
-                mem_state
-                    .widgets
-                    .iter()
-                    .filter_map(
-                        |(widget_id, widget)| match widget.get_local_update_frequency() {
-                            UpdateFrequency::Never => None,
-                            UpdateFrequency::Seconds(s) if s < 60 => Some((*widget_id, s)),
-                            UpdateFrequency::Minutes(m) => Some((*widget_id, m as u32 * 60)),
-                            _ => None,
-                        },
-                    )
-                    .collect::<Vec<_>>()
-            };
+            render_tx.send(crate::Render {}).await;
+            persist = true;
 
-            for (widget_id, seconds) in widgets_to_update {
-                spawner
-                    .spawn(widget_update_task(
-                        state,
-                        widget_id,
-                        seconds as u64,
-                        render_tx.clone(),
-                    ))
-                    .expect("Failed to spawn widget update task");
+            DeviceResponse::SetState { result: Ok(()) }
+        }
+        HostCommand::UpsertWidget { widget } => {
+            // Only restart the timers when they would tick differently.
+            let frequencies_changed = mem_state.widgets.get(&widget.id).is_none_or(|old| {
+                old.get_remote_update_frequency() != widget.get_remote_update_frequency()
+                    || old.get_local_update_frequency() != widget.get_local_update_frequency()
+            });
+
+            if frequencies_changed {
+                spawn_widget_tasks(&widget, transport, state, render_tx.clone(), spawner).await;
             }
+            mem_state.upsert_widget(widget);
 
             render_tx.send(crate::Render {}).await;
+            persist = true;
 
-            DeviceBLEResponse::SetState { result: Ok(()) }
+            DeviceResponse::UpsertWidget { result: Ok(()) }
         }
-        HostBLECommand::SetConfig { config: _ } => {
-            todo!()
-        }
-    };
-
-    // Release mutex as soon as possible.
-    drop(state_guard);
-
-    let pkt = DeviceBLEPacket::Response(resp);
+        HostCommand::RemoveWidget { widget_id } => {
+            let result = if mem_state.is_widget_shown(widget_id) {
+                Err(TransError::WidgetInUse)
+            } else if mem_state.remove_widget(widget_id).is_none() {
+                Err(TransError::WidgetNotFound)
+            } else {
+                retire_widget_tasks(widget_id).await;
+                persist = true;
+                Ok(())
+            };
 
-    let _ = send_packet(pkt, msg_id, server, conn).await;
-}
+            DeviceResponse::RemoveWidget { result }
+        }
+        HostCommand::SetFrames { frames } => {
+            // Only walked once the splits are known not to be too deep.
+            let all_widgets_exist = || {
+                frames
+                    .iter()
+                    .flat_map(|frame| frame.widget_ids())
+                    .all(|id| mem_state.widgets.contains_key(&id))
+            };
 
-/// Refreshes widget data from the host on the interval specified in the widget.
-#[embassy_executor::task(pool_size = 16)]
-async fn refresh_widget_task(
-    widget: MemoriWidget,
-    transport: &'static Mutex<CriticalSectionRawMutex, DeviceBLETransport>,
-    state: &'static Mutex<CriticalSectionRawMutex, MemoriState>,
-    render_tx: RenderTx,
-    my_generation: u32,
-) {
-    // Watches for the cancellation watch to be updated, and returns when it does so.
-    let Some(wait_period) = widget.get_remote_update_frequency().to_seconds() else {
-        // Called this function on a widget that doesn't have an update frequency, just return.
-        return;
-    };
+            let result = if let Err(e) = MemoriState::validate_splits(&frames) {
+                warn!("Refusing frames: {e}");
+                Err(TransError::InvalidState(e))
+            } else if !all_widgets_exist() {
+                Err(TransError::WidgetNotFound)
+            } else if !mem_state.set_frames(frames) {
+                Err(TransError::InvalidMessage)
+            } else {
+                render_tx.send(crate::Render {}).await;
+                persist = true;
+                Ok(())
+            };
 
-    loop {
-        Timer::after(Duration::from_secs(wait_period.into())).await;
+            DeviceResponse::SetFrames { result }
+        }
+        HostCommand::SetActiveFrame { index } => {
+            let result = if mem_state.set_active_frame(index) {
+                render_tx.send(crate::Render {}).await;
+                persist = true;
+                Ok(())
+            } else {
+                Err(TransError::InvalidMessage)
+            };
 
-        // If the generation of tasks has passed the generation for this one, we just kill ourself lol.
-        if REFRESH_GENERATION.load(Ordering::Relaxed) != my_generation {
-            info!("Generation increased! killing myself!");
-            return;
+            DeviceResponse::SetActiveFrame { result }
         }
+        HostCommand::PushWidget { widget } => {
+            // Only take the data, the timers and frequencies stay as they were set up.
+            let result = match mem_state.widgets.get_mut(&widget.id) {
+                Some(existing) => {
+                    existing.kind = widget.kind;
+                    record_push(widget.id).await;
+                    render_tx.send(crate::Render {}).await;
+                    Ok(())
+                }
+                None => Err(TransError::WidgetNotFound),
+            };
 
-        let widget_id = widget.id;
+            DeviceResponse::PushWidget { result }
+        }
+        HostCommand::SetConfig { config } => {
+            let result = if config.name().len() > MAX_NAME_LEN {
+                Err(TransError::InvalidMessage)
+            } else {
+                // The GAP service only takes a name once, when the device starts.
+                let applied = if device_config().await.name() == config.name() {
+                    ConfigApplied::Now
+                } else {
+                    ConfigApplied::AfterRestart
+                };
+                let _ = save_config(&config)
+                    .await
+                    .inspect_err(|e| error!("Failed to save config to flash: {e:?}"));
+                set_device_config(config).await;
+                render_tx.send(crate::Render {}).await;
+                Ok(applied)
+            };
 
-        let mut transport = transport.lock().await;
+            DeviceResponse::SetConfig { result }
+        }
+        HostCommand::GetBatteryLevel => DeviceResponse::BatteryLevel {
+            result: Ok(server
+                .get(&server.battery_service.level)
+                .unwrap_or_default()),
+        },
+        HostCommand::Ping => DeviceResponse::Ping { result: Ok(()) },
+        HostCommand::SyncTime {
+            utc_epoch_secs,
+            utc_offset_minutes,
+        } => {
+            wall_clock::sync(utc_epoch_secs, utc_offset_minutes).await;
+            render_tx.send(crate::Render {}).await;
 
-        if !BLE_CONNECTED.load(Ordering::SeqCst) {
-            error!("Phone not connected! cannot refresh!");
-            continue;
+            DeviceResponse::SyncTime { result: Ok(()) }
         }
+    };
 
-        let Ok(data) = transport
-            .refresh_data(widget_id)
+    if persist {
+        let _ = save_state(mem_state)
             .await
-            .inspect_err(|e| error!("Failed to refresh data for widget: {e:#?}"))
-        else {
-            continue;
-        };
-
-        info!("successfully got refresh data: {data:?}");
+            .inspect_err(|e| error!("Failed to save state to flash: {e:?}"));
+    }
 
-        // Drop guard as soon as possible.
-        drop(transport);
+    // Release mutex as soon as possible.
+    drop(state_guard);
 
-        let mut state = state.lock().await;
-        state.widgets.insert(widget_id, data);
+    let pkt = DevicePacket::Response(resp);
 
-        render_tx.send(crate::Render {}).await;
-    }
+    let _ = send_packet(pkt, msg_id, server, conn).await;
 }
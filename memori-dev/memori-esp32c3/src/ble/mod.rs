@@ -1,21 +1,31 @@
-use ble_device::{BLE_CONNECTED, BLE_HOST_RESPONSE, DeviceBLETransport};
+use ble_device::{BLE_CONNECTED, BLE_HOST_RESPONSE, DeviceBLETransport, next_transfer_id};
 use core::usize;
 use embassy_executor::Spawner;
-use embassy_futures::{join::join, select::select};
+use embassy_futures::{
+    join::join,
+    select::{Either, Either4, select, select4},
+};
 use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
 use embassy_sync::mutex::Mutex;
+use embassy_time::Instant;
 use esp_hal::peripherals;
 use esp_radio::ble::controller::BleConnector;
 use log::{error, info, warn};
-use memori_ui::MemoriState;
-use postcard::{from_bytes, to_slice};
+use memori_ui::{DISPLAY_HEIGHT, DISPLAY_WIDTH, MemoriState};
 use transport::ble_types::*;
-use transport::{TransError, TransResult};
+use transport::fragment::{Reassembler, fragment_packet};
+use transport::protocol::{
+    DevicePacket, DeviceResponse, HostPacket, MessageID, Packet, PacketPayload,
+};
+use transport::{Capabilities, PROTOCOL_VERSION, TransError, TransResult};
 use trouble_host::prelude::*;
 
 use crate::RenderTx;
+use crate::battery::{self, BATTERY_CHANGED};
+use crate::config::device_config;
 use crate::ble::host_handler::handle_host_cmd;
 use crate::ble::sender::sender_task;
+use crate::power;
 
 const CONNECTIONS_MAX: usize = 1;
 const L2CAP_CHANNELS_MAX: usize = 1;
@@ -55,8 +65,9 @@ struct NordicUartService {
 #[gatt_service(uuid = service::BATTERY)]
 struct BatteryService {
     #[descriptor(uuid = descriptors::VALID_RANGE, read, value = [0, 100])]
-    #[characteristic(uuid = BATTERY_LEVEL_CHAR_UUID, read, notify, value = 42)]
+    #[characteristic(uuid = BATTERY_LEVEL_CHAR_UUID, read, notify, value = 0)]
     level: u8,
+    /// Whether the battery is charging.
     #[characteristic(uuid = BATTERY_NOTIFY_CHAR_UUID, write, read, notify)]
     status: bool,
 }
@@ -91,34 +102,69 @@ pub async fn ble_task(
     } = stack.build();
 
     info!("Starting advertising and GATT service");
+    // We keep advertising as memori-<id> so the app can find us by pairing code,
+    // the configured name is what shows up once connected.
+    let name = alloc::string::String::from(device_config().await.name());
     let server = Server::new_with_config(GapConfig::Peripheral(PeripheralConfig {
-        name: PERIPHERAL_NAME,
+        name: &name,
         appearance: &appearance::power_device::GENERIC_POWER_DEVICE,
     }))
     .unwrap();
 
     let _ = join(ble_bg_task(runner), async {
         loop {
-            match advertise(PERIPHERAL_NAME, &mut peripheral, &server).await {
-                Ok(conn) => {
+            // The radio stays off while the device sleeps.
+            power::awake().await;
+
+            let advertising = advertise(PERIPHERAL_NAME, &mut peripheral, &server);
+            match select(advertising, power::going_to_sleep()).await {
+                Either::First(Ok(conn)) => {
                     BLE_CONNECTED.store(true, core::sync::atomic::Ordering::SeqCst);
+                    power::stay_awake().await;
 
                     let a =
                         gatt_events_task(&server, &conn, state, ble_transport, render_tx, spawner);
                     let b = sender_task(&server, &conn);
-                    select(a, b).await;
+                    let c = battery_notify_task(&server, &conn);
+                    if let Either4::Fourth(()) = select4(a, b, c, power::going_to_sleep()).await {
+                        info!("[gatt] disconnecting to go to sleep");
+                        conn.raw().disconnect();
+                    }
 
                     BLE_CONNECTED.store(false, core::sync::atomic::Ordering::SeqCst);
                 }
-                Err(e) => {
+                Either::First(Err(e)) => {
                     panic!("[adv] error: {:?}", e);
                 }
+                Either::Second(()) => info!("[adv] stopped advertising to go to sleep"),
             }
         }
     })
     .await;
 }
 
+/// Keeps the battery characteristics up to date, notifying the host whenever
+/// the battery changes.
+async fn battery_notify_task<P: PacketPool>(server: &Server<'_>, conn: &GattConnection<'_, '_, P>) {
+    let level = server.battery_service.level;
+    let charging = server.battery_service.status;
+
+    // Whatever was signaled while nobody was connected is in `status` already.
+    BATTERY_CHANGED.reset();
+    let mut status = battery::status().await;
+    loop {
+        if let Some(status) = status {
+            if let Err(e) = level.notify(conn, &status.percent).await {
+                warn!("[gatt] failed to notify battery level: {e:?}");
+            }
+            if let Err(e) = charging.notify(conn, &status.charging).await {
+                warn!("[gatt] failed to notify battery status: {e:?}");
+            }
+        }
+        status = Some(BATTERY_CHANGED.wait().await);
+    }
+}
+
 async fn ble_bg_task<C: Controller, P: PacketPool>(mut runner: Runner<'_, C, P>) {
     loop {
         if let Err(e) = runner.run().await {
@@ -138,6 +184,15 @@ async fn gatt_events_task<P: PacketPool>(
     let rx_handle = server.nus_service.rx.handle;
     let battery_handle = server.battery_service.level.handle;
 
+    // Host packets can span multiple writes, keep the pieces around until they're complete.
+    let mut reassembler = Reassembler::new();
+    // Nothing but a hello gets through until the host said a compatible one. A
+    // host that never says hello is from before the handshake, version 0.
+    let mut handshake = Err(TransError::IncompatibleProtocol {
+        local: PROTOCOL_VERSION,
+        remote: 0,
+    });
+
     let reason = loop {
         match conn.next().await {
             GattConnectionEvent::Disconnected { reason } => break reason,
@@ -147,6 +202,8 @@ async fn gatt_events_task<P: PacketPool>(
                         if event.handle() == rx_handle {
                             handle_receive_data(
                                 event.data(),
+                                &mut reassembler,
+                                &mut handshake,
                                 server,
                                 conn,
                                 transport,
@@ -182,6 +239,8 @@ async fn gatt_events_task<P: PacketPool>(
 
 async fn handle_receive_data<P: PacketPool>(
     data: &[u8],
+    reassembler: &mut Reassembler,
+    handshake: &mut TransResult<()>,
     server: &Server<'_>,
     conn: &GattConnection<'_, '_, P>,
     transport: &'static Mutex<CriticalSectionRawMutex, DeviceBLETransport>,
@@ -190,30 +249,53 @@ async fn handle_receive_data<P: PacketPool>(
     spawner: Spawner,
 ) {
     info!("[gatt] received {} bytes", data.len());
-    let packet = match from_bytes::<BLEPacket>(data) {
-        Ok(packet) => packet,
+    let packet = match reassembler.push(data, Instant::now().as_millis()) {
+        Ok(Some(packet)) => packet,
+        // Still waiting on the rest of the fragments.
+        Ok(None) => return,
         Err(e) => {
-            warn!("[gatt] failed to decode BLEPacket: {:?}", e);
+            warn!("[gatt] failed to reassemble Packet: {:?}", e);
             return;
         }
     };
 
     let payload = match packet.payload {
-        BLEPacketPayload::HostPacket(payload) => payload,
-        BLEPacketPayload::DevicePacket { .. } => {
+        PacketPayload::HostPacket(payload) => payload,
+        PacketPayload::Hello(remote) => {
+            let local = Capabilities::new(env!("CARGO_PKG_VERSION"), DISPLAY_WIDTH, DISPLAY_HEIGHT);
+            *handshake = local.check_compatible(&remote);
+            if let Err(e) = handshake {
+                warn!("[transport] host is incompatible: {:?}", e);
+            }
+            // Always answer, the host decides whether to hang up and tells the user why.
+            if let Err(e) = send_payload(PacketPayload::Hello(local), packet.id, server, conn).await {
+                error!("[transport] failed to answer hello: {:?}", e);
+            }
+            return;
+        }
+        PacketPayload::DevicePacket { .. } => {
             warn!("[transport] received devicepacket...");
             return;
         }
     };
 
+    if let Err(e) = handshake {
+        warn!("[transport] refusing packet from an incompatible host");
+        if let HostPacket::Command(cmd) = &payload {
+            let resp = DeviceResponse::error_for(cmd, e.clone());
+            let _ = send_packet(DevicePacket::Response(resp), packet.id, server, conn).await;
+        }
+        return;
+    }
+
     match payload {
-        HostBLEPacket::Command(cmd) => {
+        HostPacket::Command(cmd) => {
             handle_host_cmd(
                 cmd, packet.id, server, state, transport, render_tx, spawner, conn,
             )
             .await;
         }
-        HostBLEPacket::Response(resp) => {
+        HostPacket::Response(resp) => {
             // we have a response!
             let index = packet.id as usize; // As long as architecture is >= 32b we chill
             BLE_HOST_RESPONSE[index % MAX_INFLIGHT].signal(resp);
@@ -258,26 +340,40 @@ async fn advertise<'values, 'server, C: Controller>(
 }
 
 async fn send_packet<P: PacketPool>(
-    packet: DeviceBLEPacket,
+    packet: DevicePacket,
+    msg_id: MessageID,
+    server: &Server<'_>,
+    conn: &GattConnection<'_, '_, P>,
+) -> TransResult<()> {
+    send_payload(PacketPayload::DevicePacket(packet), msg_id, server, conn).await
+}
+
+async fn send_payload<P: PacketPool>(
+    payload: PacketPayload,
     msg_id: MessageID,
     server: &Server<'_>,
     conn: &GattConnection<'_, '_, P>,
 ) -> TransResult<()> {
     let tx = server.nus_service.tx;
-    let mut buffer = [0u8; BLE_CHAR_SIZE];
 
-    let packet = BLEPacket {
-        payload: BLEPacketPayload::DevicePacket(packet),
+    let packet = Packet {
+        payload,
         id: msg_id,
     };
 
-    let encoded = to_slice(&packet, &mut buffer).map_err(|_| TransError::SerializationFailure)?;
-    info!("encoded packet info {encoded:?}");
+    let fragments = fragment_packet(&packet, next_transfer_id())?;
+    info!("sending packet in {} fragment(s)", fragments.len());
+
+    for fragment in fragments {
+        // The characteristic is fixed size, the fragment header tells the host where the data ends.
+        let mut buffer = [0u8; BLE_CHAR_SIZE];
+        buffer[..fragment.len()].copy_from_slice(&fragment);
 
-    tx.notify(conn, &buffer).await.map_err(|e| {
-        error!("Internal error: {e:?}");
-        TransError::InternalError
-    })?;
+        tx.notify(conn, &buffer).await.map_err(|e| {
+            error!("Internal error: {e:?}");
+            TransError::InternalError
+        })?;
+    }
 
     Ok(())
 }
@@ -1,6 +1,6 @@
 use ble_device::BLE_CMD_CHANNEL;
 use log::error;
-use transport::ble_types::*;
+use transport::protocol::*;
 use trouble_host::prelude::*;
 
 use crate::ble::{Server, send_packet};
@@ -10,28 +10,13 @@ pub async fn sender_task<P: PacketPool>(server: &Server<'_>, conn: &GattConnecti
 
     loop {
         let outgoing = cmd_rx.receive().await;
-        let msg_id = outgoing.id;
-
-        let _ = match outgoing.cmd {
-            DeviceBLECommand::Ping => {
-                send_packet(
-                    DeviceBLEPacket::Command(DeviceBLECommand::Ping),
-                    msg_id,
-                    server,
-                    conn,
-                )
-                .await
-            }
-            DeviceBLECommand::RefreshData { widget_id } => {
-                send_packet(
-                    DeviceBLEPacket::Command(DeviceBLECommand::RefreshData { widget_id }),
-                    msg_id,
-                    server,
-                    conn,
-                )
-                .await
-            }
-        }
+        let _ = send_packet(
+            DevicePacket::Command(outgoing.cmd.clone()),
+            outgoing.id,
+            server,
+            conn,
+        )
+        .await
         .inspect_err(|e| error!("failed to send outgoing packet: {outgoing:#?}, {e:#?}"));
     }
 }
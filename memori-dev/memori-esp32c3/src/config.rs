@@ -0,0 +1,45 @@
+//! The [`DeviceConfig`] the device is currently running with.
+
+use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, mutex::Mutex};
+use transport::{DeviceConfig, DisplayRotation, RefreshPolicy};
+
+/// Longest name the GAP service accepts.
+pub const MAX_NAME_LEN: usize = 22;
+
+static DEVICE_CONFIG: Mutex<CriticalSectionRawMutex, Option<DeviceConfig>> = Mutex::new(None);
+
+// The display flush callback can't wait on a lock, so it gets what it needs from here.
+static UPSIDE_DOWN: AtomicBool = AtomicBool::new(false);
+static FULL_REFRESH_EVERY: AtomicU8 = AtomicU8::new(1);
+
+/// The current config, the default one until the host sends something.
+pub async fn device_config() -> DeviceConfig {
+    DEVICE_CONFIG.lock().await.clone().unwrap_or_default()
+}
+
+/// Start using `config`, the UI picks it up on its next render.
+pub async fn set_device_config(config: DeviceConfig) {
+    UPSIDE_DOWN.store(
+        config.rotation() == DisplayRotation::UpsideDown,
+        Ordering::Relaxed,
+    );
+    let full_every = match config.refresh_policy() {
+        RefreshPolicy::Full => 1,
+        RefreshPolicy::Fast { full_every } => full_every,
+    };
+    FULL_REFRESH_EVERY.store(full_every, Ordering::Relaxed);
+
+    *DEVICE_CONFIG.lock().await = Some(config);
+}
+
+/// Whether the display should be drawn upside down.
+pub fn upside_down() -> bool {
+    UPSIDE_DOWN.load(Ordering::Relaxed)
+}
+
+/// Do a full refresh every this many updates, `0` for never.
+pub fn full_refresh_every() -> u8 {
+    FULL_REFRESH_EVERY.load(Ordering::Relaxed)
+}
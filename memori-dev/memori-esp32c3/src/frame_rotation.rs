@@ -0,0 +1,34 @@
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::mutex::Mutex;
+use embassy_time::{Duration, Timer};
+use memori_ui::MemoriState;
+
+use crate::RenderTx;
+use crate::config::device_config;
+
+/// Cycles through the frames, showing each one for the state's `frame_time`.
+/// Does nothing while frame rotation is turned off in the config.
+#[embassy_executor::task]
+pub async fn frame_rotation_task(
+    state: &'static Mutex<CriticalSectionRawMutex, MemoriState>,
+    render_tx: RenderTx,
+) {
+    loop {
+        // A frame time of 0 keeps the current frame, check again in a bit in case it changes.
+        let frame_time = state.lock().await.frame_time().max(1);
+        Timer::after(Duration::from_secs(frame_time.into())).await;
+
+        if !device_config().await.frame_rotation() {
+            continue;
+        }
+
+        let mut state = state.lock().await;
+        if state.frame_time() == 0 || state.frames().len() < 2 {
+            continue;
+        }
+        state.next_frame();
+        drop(state);
+
+        render_tx.send(crate::Render {}).await;
+    }
+}
@@ -0,0 +1,147 @@
+//! The three buttons on the device, wired between the pin and ground.
+//!
+//! [`input_task`] reads them and does what a press does to the state right
+//! away, [`input_forward_task`] tells the host about it afterwards so a slow or
+//! missing phone never holds up the buttons.
+
+use core::sync::atomic::Ordering;
+
+use ble_device::{BLE_CONNECTED, DeviceBLETransport};
+use embassy_futures::select::{Either4, select4};
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, channel::Channel, mutex::Mutex};
+use embassy_time::{Duration, Instant, Timer};
+use esp_hal::{
+    gpio::{Input, InputConfig, Pull, WakeEvent},
+    peripherals::{GPIO7, GPIO9, GPIO20},
+};
+use log::{error, info, warn};
+use memori_ui::{
+    MemoriState,
+    input::{Button, InputEffect, InputEvent, PressDetector},
+};
+use transport::input::forward_input;
+
+use crate::{RenderTx, power};
+
+/// How often the buttons are read while one of them is down.
+const POLL_PERIOD: Duration = Duration::from_millis(5);
+
+/// Presses waiting to be sent to the host, more than this and the newest are
+/// dropped.
+static FORWARD_CHANNEL: Channel<CriticalSectionRawMutex, (InputEvent, InputEffect), 4> =
+    Channel::new();
+
+/// Pins the buttons are on, D5, D7 and the XIAO's boot button.
+pub struct ButtonPins {
+    pub prev: GPIO7<'static>,
+    pub next: GPIO20<'static>,
+    pub select: GPIO9<'static>,
+}
+
+/// Reads the buttons and hands their presses to [`MemoriState::handle_input`].
+#[embassy_executor::task]
+pub async fn input_task(
+    pins: ButtonPins,
+    state: &'static Mutex<CriticalSectionRawMutex, MemoriState>,
+    render_tx: RenderTx,
+) {
+    let config = InputConfig::default().with_pull(Pull::Up);
+    let mut buttons = [
+        (Button::Prev, Input::new(pins.prev, config)),
+        (Button::Next, Input::new(pins.next, config)),
+        (Button::Select, Input::new(pins.select, config)),
+    ];
+    let mut detectors: [PressDetector; 3] = Default::default();
+
+    loop {
+        // Sleep until a button goes down instead of reading them all the time.
+        if detectors.iter().all(PressDetector::is_idle) {
+            let [(_, prev), (_, next), (_, select)] = &mut buttons;
+            let woken = select4(
+                prev.wait_for_low(),
+                next.wait_for_low(),
+                select.wait_for_low(),
+                power::going_to_sleep(),
+            )
+            .await;
+
+            // Let a press wake the whole device up, waiting on the pins again
+            // afterwards turns that back off.
+            if let Either4::Fourth(()) = woken {
+                for (button, input) in &mut buttons {
+                    if let Err(e) = input.wakeup_enable(true, WakeEvent::LowLevel) {
+                        warn!("{button:?} can't wake the device up: {e:?}");
+                    }
+                }
+                power::awake().await;
+                continue;
+            }
+        }
+
+        let now = Instant::now().as_millis();
+        for ((button, input), detector) in buttons.iter().zip(detectors.iter_mut()) {
+            let Some(press) = detector.update(input.is_low(), now) else {
+                continue;
+            };
+
+            let event = InputEvent::new(*button, press);
+            info!("button pressed: {event:?}");
+            power::stay_awake().await;
+            let effect = state.lock().await.handle_input(event);
+            if matches!(
+                effect,
+                InputEffect::Redraw | InputEffect::TodoToggled { .. }
+            ) {
+                render_tx.send(crate::Render {}).await;
+            }
+
+            if FORWARD_CHANNEL.try_send((event, effect)).is_err() {
+                warn!("too many presses waiting for the host, dropping {event:?}");
+            }
+        }
+
+        Timer::after(POLL_PERIOD).await;
+    }
+}
+
+/// Tells the host about presses and asks it for whatever they need, like fresh
+/// data for the widgets on the display.
+#[embassy_executor::task]
+pub async fn input_forward_task(
+    transport: &'static Mutex<CriticalSectionRawMutex, DeviceBLETransport>,
+    state: &'static Mutex<CriticalSectionRawMutex, MemoriState>,
+    render_tx: RenderTx,
+) {
+    loop {
+        let (event, effect) = FORWARD_CHANNEL.receive().await;
+
+        if !BLE_CONNECTED.load(Ordering::SeqCst) {
+            warn!("Phone not connected! not telling it about {event:?}");
+            continue;
+        }
+
+        let mut transport = transport.lock().await;
+        let Ok(widgets) = forward_input(&mut *transport, event, &effect)
+            .await
+            .inspect_err(|e| error!("Failed to forward {event:?} to the host: {e:#?}"))
+        else {
+            continue;
+        };
+        drop(transport);
+
+        if widgets.is_empty() {
+            continue;
+        }
+
+        let mut state = state.lock().await;
+        for data in widgets {
+            // The widget may have been removed while we were waiting on the host.
+            if let Some(widget) = state.widgets.get_mut(&data.id) {
+                *widget = data;
+            }
+        }
+        drop(state);
+
+        render_tx.send(crate::Render {}).await;
+    }
+}
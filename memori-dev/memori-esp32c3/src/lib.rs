@@ -1,8 +1,16 @@
 #![no_std]
 extern crate alloc;
 
+pub mod battery;
 pub mod ble;
+pub mod config;
+pub mod frame_rotation;
+pub mod input;
 pub mod local_widget_update;
+pub mod persist;
+pub mod power;
+pub mod wall_clock;
+pub mod widget_tasks;
 
 use alloc::boxed::Box;
 use display_interface_spi::SPIInterface;
@@ -15,9 +23,10 @@ use esp_hal::{
     Blocking,
     delay::Delay,
     gpio::{Input, InputConfig, Level, Output, OutputConfig, Pull},
-    peripherals::{GPIO2, GPIO3, GPIO4, GPIO5, GPIO6},
+    peripherals::{GPIO3, GPIO4, GPIO5, GPIO6},
     spi::master::Spi,
 };
+use memori_ui::overlay::PixelLayer;
 use mousefood::{EmbeddedBackend, EmbeddedBackendConfig};
 use ratatui::Terminal;
 use weact_studio_epd::{
@@ -52,11 +61,23 @@ pub struct MemTermInitPins {
     pub busy_pin: GPIO6<'static>,
 }
 
-/// Set up the terminal with the given SPI device and display.
+/// Turn the display upside down or back. Only affects what's drawn after, see
+/// [`memori_ui::Memori::redraw_all`].
+pub fn set_upside_down(display: &mut MemDisplay, upside_down: bool) {
+    display.set_rotation(if upside_down {
+        DisplayRotation::Rotate270
+    } else {
+        DisplayRotation::Rotate90
+    });
+}
+
+/// Set up the terminal with the given SPI device and display, drawing `pixels`
+/// over the terminal on every update.
 pub fn setup_term<'a>(
     spi: Spi<'static, Blocking>,
     display: &'a mut MemDisplay,
     pins: MemTermInitPins,
+    pixels: PixelLayer,
 ) -> MemTerm<'a> {
     let cs = Output::new(pins.cs_pin, Level::High, OutputConfig::default());
     let busy = Input::new(pins.busy_pin, InputConfig::default().with_pull(Pull::Up));
@@ -71,12 +92,25 @@ pub fn setup_term<'a>(
     display.set_rotation(DisplayRotation::Rotate90);
     driver.init().unwrap();
 
+    // Start with a full refresh, whatever the policy is.
+    let mut updates_since_full = u8::MAX;
+
     let config = EmbeddedBackendConfig {
         font_regular: memori_ui::FONT_REGULAR,
         font_bold: memori_ui::FONT_BOLD,
         font_italic: memori_ui::FONT_ITALIC,
-        flush_callback: Box::new(move |d| {
-            driver.full_update(d).unwrap();
+        flush_callback: Box::new(move |d: &mut MemDisplay| {
+            // Drawing into the frame buffer can't fail.
+            let _ = pixels.draw(d);
+
+            updates_since_full = updates_since_full.saturating_add(1);
+            let full_every = config::full_refresh_every();
+            if full_every != 0 && updates_since_full >= full_every {
+                driver.full_update(d).unwrap();
+                updates_since_full = 0;
+            } else {
+                driver.fast_update(d).unwrap();
+            }
         }),
         ..Default::default()
     };
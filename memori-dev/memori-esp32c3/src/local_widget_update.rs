@@ -5,6 +5,7 @@ use memori_ui::MemoriState;
 use memori_ui::widgets::WidgetId;
 
 use crate::RenderTx;
+use crate::widget_tasks::is_current_generation;
 
 #[embassy_executor::task(pool_size = 16)]
 pub async fn widget_update_task(
@@ -12,10 +13,15 @@ pub async fn widget_update_task(
     widget_id: WidgetId,
     seconds: u64,
     render_tx: RenderTx,
+    my_generation: u32,
 ) {
     loop {
         {
             Timer::after(Duration::from_secs(seconds)).await;
+            if !is_current_generation(widget_id, my_generation).await {
+                return;
+            }
+
             let mut locked_state = state.lock().await;
             if let Some(widget) = locked_state.widgets.get_mut(&widget_id) {
                 widget.update();
@@ -0,0 +1,175 @@
+//! Keeps the last [`MemoriState`] and [`DeviceConfig`] in the `nvs` flash
+//! partition, so the device comes back showing the same widgets after a reboot
+//! instead of asking to be paired again.
+//!
+//! The config gets the first sector of the partition and the state the rest.
+//! Each holds a small header (magic, length, crc32 of the payload) followed by
+//! the postcard encoded value. Anything that doesn't check out is treated as
+//! nothing being stored.
+
+use alloc::vec;
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, mutex::Mutex};
+use embedded_storage::{ReadStorage, Storage};
+use esp_bootloader_esp_idf::partitions::{
+    self, DataPartitionSubType, PARTITION_TABLE_MAX_LEN, PartitionType,
+};
+use esp_hal::peripherals::FLASH;
+use esp_storage::FlashStorage;
+use log::{error, info, warn};
+use memori_ui::MemoriState;
+use serde::{Serialize, de::DeserializeOwned};
+use transport::DeviceConfig;
+
+/// Marks the start of a stored value ("MEMO").
+const MAGIC: u32 = 0x4d45_4d4f;
+
+/// Magic, payload length and payload crc32, each a little endian u32.
+const HEADER_LEN: usize = 12;
+
+/// Room for the config, one flash sector so writing it never touches the state.
+const CONFIG_SLOT_LEN: usize = FlashStorage::SECTOR_SIZE as usize;
+
+static STORE: Mutex<CriticalSectionRawMutex, Option<Store>> = Mutex::new(None);
+
+/// Why something couldn't be saved.
+#[derive(Debug)]
+pub enum PersistError {
+    /// [`init`] never found somewhere to put it.
+    NoStore,
+    Serialization,
+    /// It doesn't fit in its part of the partition.
+    TooLarge,
+    Flash,
+}
+
+/// A region of the partition holding one value.
+#[derive(Clone, Copy)]
+struct Slot {
+    offset: u32,
+    capacity: usize,
+}
+
+struct Store {
+    flash: FlashStorage<'static>,
+    config: Slot,
+    state: Slot,
+}
+
+impl Store {
+    fn new(flash: FLASH<'static>) -> Option<Self> {
+        let mut flash = FlashStorage::new(flash);
+
+        let mut table = [0u8; PARTITION_TABLE_MAX_LEN];
+        let partitions = partitions::read_partition_table(&mut flash, &mut table)
+            .inspect_err(|e| error!("Failed to read the partition table: {e:?}"))
+            .ok()?;
+
+        let Ok(Some(nvs)) =
+            partitions.find_partition(PartitionType::Data(DataPartitionSubType::Nvs))
+        else {
+            error!("No nvs partition, the state won't survive a reboot");
+            return None;
+        };
+
+        let len = nvs.len() as usize;
+        if len <= CONFIG_SLOT_LEN {
+            error!("The nvs partition is too small to keep anything in");
+            return None;
+        }
+
+        Some(Self {
+            flash,
+            config: Slot {
+                offset: nvs.offset(),
+                capacity: CONFIG_SLOT_LEN,
+            },
+            state: Slot {
+                offset: nvs.offset() + CONFIG_SLOT_LEN as u32,
+                capacity: len - CONFIG_SLOT_LEN,
+            },
+        })
+    }
+
+    fn load<T: DeserializeOwned>(&mut self, slot: Slot) -> Option<T> {
+        let mut header = [0u8; HEADER_LEN];
+        self.flash.read(slot.offset, &mut header).ok()?;
+
+        let [magic, len, crc] = [0, 4, 8]
+            .map(|i| u32::from_le_bytes([header[i], header[i + 1], header[i + 2], header[i + 3]]));
+
+        // Erased flash reads back as all ones, so this is the usual case on a new device.
+        if magic != MAGIC || len as usize > slot.capacity - HEADER_LEN {
+            return None;
+        }
+
+        let mut payload = vec![0u8; len as usize];
+        self.flash
+            .read(slot.offset + HEADER_LEN as u32, &mut payload)
+            .ok()?;
+
+        if crc32fast::hash(&payload) != crc {
+            warn!("Stored data failed its checksum, ignoring it");
+            return None;
+        }
+
+        postcard::from_bytes(&payload)
+            .inspect_err(|e| warn!("Stored data is from an older firmware? {e:?}"))
+            .ok()
+    }
+
+    fn save<T: Serialize>(&mut self, slot: Slot, value: &T) -> Result<(), PersistError> {
+        let payload = postcard::to_allocvec(value).map_err(|_| PersistError::Serialization)?;
+        if payload.len() > slot.capacity - HEADER_LEN {
+            return Err(PersistError::TooLarge);
+        }
+
+        // One write for everything, a reboot halfway through just fails the checksum.
+        let mut blob = vec![0u8; HEADER_LEN + payload.len()];
+        blob[0..4].copy_from_slice(&MAGIC.to_le_bytes());
+        blob[4..8].copy_from_slice(&(payload.len() as u32).to_le_bytes());
+        blob[8..12].copy_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+        blob[HEADER_LEN..].copy_from_slice(&payload);
+
+        self.flash
+            .write(slot.offset, &blob)
+            .map_err(|_| PersistError::Flash)
+    }
+}
+
+/// Find the partition to keep things in. Must be called once at boot, before
+/// anything else in here.
+pub async fn init(flash: FLASH<'static>) {
+    *STORE.lock().await = Store::new(flash);
+}
+
+/// The state saved by [`save_state`], if there is a valid one.
+pub async fn load_state() -> Option<MemoriState> {
+    let mut store = STORE.lock().await;
+    let store = store.as_mut()?;
+    let state = store.load(store.state);
+    if state.is_some() {
+        info!("Restored state from flash");
+    }
+    state
+}
+
+/// Write `state` to flash so it is restored on the next boot.
+pub async fn save_state(state: &MemoriState) -> Result<(), PersistError> {
+    let mut store = STORE.lock().await;
+    let store = store.as_mut().ok_or(PersistError::NoStore)?;
+    store.save(store.state, state)
+}
+
+/// The config saved by [`save_config`], if there is a valid one.
+pub async fn load_config() -> Option<DeviceConfig> {
+    let mut store = STORE.lock().await;
+    let store = store.as_mut()?;
+    store.load(store.config)
+}
+
+/// Write `config` to flash so it is restored on the next boot.
+pub async fn save_config(config: &DeviceConfig) -> Result<(), PersistError> {
+    let mut store = STORE.lock().await;
+    let store = store.as_mut().ok_or(PersistError::NoStore)?;
+    store.save(store.config, config)
+}
@@ -0,0 +1,254 @@
+//! Sleeping between updates, the e-paper keeps showing the last frame without
+//! any power.
+//!
+//! [`power_task`] keeps the device awake for [`AWAKE_FOR`] after booting, a
+//! button press or hearing from the host, so the phone gets a chance to connect
+//! and push its data. After that it works out when the display next needs to
+//! change, see [`MemoriState::wake_period`]:
+//!
+//! - With nothing scheduled it light sleeps until a button is pressed.
+//! - With the next change at least [`DEEP_SLEEP_MIN`] away it deep sleeps until
+//!   then. The device boots again on waking, taking the state from flash and
+//!   the time and frame from RTC memory, see [`restore_after_deep_sleep`].
+//! - Otherwise it stays awake, the executor idles the CPU on its own. The timer
+//!   embassy counts time with stops during light sleep, so sleeping lightly
+//!   until the next update would push every other timer back as well.
+//!
+//! None of the buttons are on RTC pins, so only the timer wakes the device up
+//! from deep sleep. [`DeviceConfig::always_connected`] turns all of this off.
+//!
+//! [`DeviceConfig::always_connected`]: transport::DeviceConfig::always_connected
+
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, mutex::Mutex, watch::Watch};
+use embassy_time::{Duration, Instant, Timer};
+use esp_hal::{
+    peripherals::LPWR,
+    rtc_cntl::{
+        Rtc, SocResetReason, reset_reason,
+        sleep::{GpioWakeupSource, TimerWakeupSource},
+    },
+    system::Cpu,
+};
+use log::{info, warn};
+use memori_ui::{MemoriState, widgets::WidgetKind};
+
+use crate::{RenderRx, config::device_config, wall_clock};
+
+/// How long to stay awake after anything happens.
+pub const AWAKE_FOR: Duration = Duration::from_secs(30);
+
+/// Deep sleep only when the display isn't due to change for at least this long.
+/// Waking from it is a reboot, and the first update after one is a full refresh.
+pub const DEEP_SLEEP_MIN: Duration = Duration::from_secs(5 * 60);
+
+/// How often to check whether it's time to sleep.
+const CHECK_PERIOD: Duration = Duration::from_secs(5);
+
+/// Time for the radio and the buttons to get ready before going to sleep.
+const SETTLE_TIME: Duration = Duration::from_millis(200);
+
+/// Marks [`SleepMemory`] as written by us ("ZZZZ").
+const SLEEP_MAGIC: u32 = 0x5a5a_5a5a;
+
+/// Whether the device is about to sleep, the radio goes off and the buttons
+/// get ready to wake it up.
+static GOING_TO_SLEEP: Watch<CriticalSectionRawMutex, bool, 4> = Watch::new_with(false);
+
+/// When something last happened that should keep us awake, booting to begin with.
+static LAST_ACTIVITY: Mutex<CriticalSectionRawMutex, Instant> = Mutex::new(Instant::from_ticks(0));
+
+/// What the device needs to carry on where it left off after a deep sleep,
+/// everything else is gone by then.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct SleepMemory {
+    magic: u32,
+    /// crc32 of the rest, a reset halfway through writing leaves it wrong.
+    crc: u32,
+    utc_epoch_secs: i64,
+    utc_offset_minutes: i32,
+    /// Whether the host ever synced the time, `0` for no.
+    clock_synced: u32,
+    slept_secs: u32,
+    active_frame: u32,
+}
+
+// SAFETY: only integers, without any padding between them.
+unsafe impl esp_hal::Persistable for SleepMemory {}
+
+impl SleepMemory {
+    fn checksum(&self) -> u32 {
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(&self.utc_epoch_secs.to_le_bytes());
+        hasher.update(&self.utc_offset_minutes.to_le_bytes());
+        hasher.update(&self.clock_synced.to_le_bytes());
+        hasher.update(&self.slept_secs.to_le_bytes());
+        hasher.update(&self.active_frame.to_le_bytes());
+        hasher.finalize()
+    }
+}
+
+#[esp_hal::ram(unstable(rtc_fast, persistent))]
+static mut SLEEP_MEMORY: SleepMemory = SleepMemory {
+    magic: 0,
+    crc: 0,
+    utc_epoch_secs: 0,
+    utc_offset_minutes: 0,
+    clock_synced: 0,
+    slept_secs: 0,
+    active_frame: 0,
+};
+
+/// Keep the device awake for another [`AWAKE_FOR`].
+pub async fn stay_awake() {
+    *LAST_ACTIVITY.lock().await = Instant::now();
+}
+
+/// Resolves once the device is about to sleep, stop using the radio.
+pub async fn going_to_sleep() {
+    wait_for_sleeping(true).await;
+}
+
+/// Resolves once the device is awake, right away if it never slept.
+pub async fn awake() {
+    wait_for_sleeping(false).await;
+}
+
+async fn wait_for_sleeping(sleeping: bool) {
+    match GOING_TO_SLEEP.receiver() {
+        Some(mut receiver) => {
+            receiver.get_and(|going| *going == sleeping).await;
+        }
+        None => {
+            // More waiting than there are receivers for, check now and then instead.
+            while GOING_TO_SLEEP.try_get() != Some(sleeping) {
+                Timer::after(CHECK_PERIOD).await;
+            }
+        }
+    }
+}
+
+/// Picks up where the device left off before a deep sleep: the time, the
+/// frame it was on, and whatever came due while it slept. Does nothing after
+/// any other kind of reset.
+pub async fn restore_after_deep_sleep(state: &mut MemoriState) {
+    if reset_reason(Cpu::ProCpu) != Some(SocResetReason::CoreDeepSleep) {
+        return;
+    }
+
+    // SAFETY: nothing else touches it until we go to sleep again.
+    let memory = unsafe { (&raw const SLEEP_MEMORY).read() };
+    if memory.magic != SLEEP_MAGIC || memory.crc != memory.checksum() {
+        warn!("Woke up from deep sleep without knowing where we left off");
+        return;
+    }
+    info!("Woke up after sleeping for {}s", memory.slept_secs);
+
+    if memory.clock_synced != 0 {
+        wall_clock::sync(
+            memory.utc_epoch_secs + i64::from(memory.slept_secs),
+            memory.utc_offset_minutes as i16,
+        )
+        .await;
+    }
+
+    state.set_active_frame(memory.active_frame as usize);
+    let frame_time = state.frame_time();
+    if device_config().await.frame_rotation() && frame_time > 0 && memory.slept_secs >= frame_time {
+        state.next_frame();
+    }
+
+    for widget in state.widgets.values_mut() {
+        if widget
+            .get_local_update_frequency()
+            .to_seconds()
+            .is_some_and(|seconds| seconds <= memory.slept_secs)
+        {
+            widget.update();
+        }
+    }
+}
+
+/// Puts the device to sleep whenever there's nothing for it to do.
+#[embassy_executor::task]
+pub async fn power_task(
+    lpwr: LPWR<'static>,
+    state: &'static Mutex<CriticalSectionRawMutex, MemoriState>,
+    render_rx: RenderRx,
+) {
+    let mut rtc = Rtc::new(lpwr);
+
+    loop {
+        Timer::after(CHECK_PERIOD).await;
+
+        let config = device_config().await;
+        if config.always_connected() {
+            continue;
+        }
+
+        if LAST_ACTIVITY.lock().await.elapsed() < AWAKE_FOR || !render_rx.is_empty() {
+            continue;
+        }
+
+        let state = state.lock().await;
+        // Still waiting to be paired, the phone can't find us while we sleep.
+        if state
+            .widgets
+            .values()
+            .any(|widget| matches!(widget.kind, WidgetKind::Pair(_)))
+        {
+            continue;
+        }
+        let period = state.wake_period(config.frame_rotation());
+        let active_frame = state.active_frame_idx();
+        drop(state);
+
+        match period {
+            None => light_sleep(&mut rtc).await,
+            Some(seconds) if u64::from(seconds) >= DEEP_SLEEP_MIN.as_secs() => {
+                deep_sleep(&mut rtc, seconds, active_frame).await
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+/// Sleeps until a button is pressed, see [`crate::input::input_task`].
+async fn light_sleep(rtc: &mut Rtc<'static>) {
+    info!("Nothing to do, sleeping until a button is pressed");
+    GOING_TO_SLEEP.sender().send(true);
+    Timer::after(SETTLE_TIME).await;
+
+    let before = rtc.time_since_boot();
+    rtc.sleep_light(&[&GpioWakeupSource::new()]);
+    let slept = Duration::from_millis((rtc.time_since_boot() - before).as_millis());
+
+    info!("Woke up after {}s", slept.as_secs());
+    wall_clock::skip(slept).await;
+    GOING_TO_SLEEP.sender().send(false);
+    stay_awake().await;
+}
+
+/// Sleeps for `seconds`, the device boots again after.
+async fn deep_sleep(rtc: &mut Rtc<'static>, seconds: u32, active_frame: usize) -> ! {
+    info!("Nothing to do for {seconds}s, going to deep sleep");
+    GOING_TO_SLEEP.sender().send(true);
+    Timer::after(SETTLE_TIME).await;
+
+    let clock = wall_clock::utc().await;
+    let mut memory = SleepMemory {
+        magic: SLEEP_MAGIC,
+        crc: 0,
+        utc_epoch_secs: clock.map_or(0, |(secs, _)| secs),
+        utc_offset_minutes: clock.map_or(0, |(_, offset)| offset.into()),
+        clock_synced: clock.is_some().into(),
+        slept_secs: seconds,
+        active_frame: active_frame as u32,
+    };
+    memory.crc = memory.checksum();
+    // SAFETY: nothing else touches it, and we never wake up from this.
+    unsafe { (&raw mut SLEEP_MEMORY).write(memory) };
+
+    let timer = TimerWakeupSource::new(core::time::Duration::from_secs(seconds.into()));
+    rtc.sleep_deep(&[&timer])
+}
@@ -0,0 +1,49 @@
+//! The time of day, counted on the device from the last time the host told us.
+
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, mutex::Mutex};
+use embassy_time::{Duration, Instant};
+use memori_ui::time::LocalTime;
+
+/// What the host said the time was, and when it said it.
+#[derive(Clone, Copy)]
+struct Synced {
+    at: Instant,
+    utc_epoch_secs: i64,
+    utc_offset_minutes: i16,
+}
+
+static SYNCED: Mutex<CriticalSectionRawMutex, Option<Synced>> = Mutex::new(None);
+
+/// Start counting from the time the host just sent.
+pub async fn sync(utc_epoch_secs: i64, utc_offset_minutes: i16) {
+    *SYNCED.lock().await = Some(Synced {
+        at: Instant::now(),
+        utc_epoch_secs,
+        utc_offset_minutes,
+    });
+}
+
+/// The local time right now, `None` until the host has synced it since boot.
+pub async fn now() -> Option<LocalTime> {
+    let synced = (*SYNCED.lock().await)?;
+    let elapsed = synced.at.elapsed().as_secs() as i64;
+    Some(LocalTime::from_utc(
+        synced.utc_epoch_secs + elapsed,
+        synced.utc_offset_minutes,
+    ))
+}
+
+/// What the host would say the time is right now, as it passes to [`sync`].
+pub async fn utc() -> Option<(i64, i16)> {
+    let synced = (*SYNCED.lock().await)?;
+    let elapsed = synced.at.elapsed().as_secs() as i64;
+    Some((synced.utc_epoch_secs + elapsed, synced.utc_offset_minutes))
+}
+
+/// Count `slept` as having passed, the timer we count with stops while the
+/// device is in light sleep.
+pub async fn skip(slept: Duration) {
+    if let Some(synced) = SYNCED.lock().await.as_mut() {
+        synced.utc_epoch_secs += slept.as_secs() as i64;
+    }
+}
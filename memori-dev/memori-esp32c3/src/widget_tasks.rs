@@ -0,0 +1,151 @@
+use alloc::collections::BTreeMap;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use ble_device::{BLE_CONNECTED, DeviceBLETransport};
+use embassy_executor::Spawner;
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, mutex::Mutex};
+use embassy_time::{Duration, Instant, Timer};
+use log::{error, info};
+use memori_ui::{
+    MemoriState,
+    widgets::{MemoriWidget, UpdateFrequency, WidgetId},
+};
+use transport::DeviceTransport;
+
+use crate::RenderTx;
+use crate::local_widget_update::widget_update_task;
+
+/// Keeps track of the generation of the tasks that keep each widget up to date,
+/// basically a way to tell older tasks to die when their widget gets new update
+/// frequencies or is removed altogether.
+static TASK_GENERATIONS: Mutex<CriticalSectionRawMutex, BTreeMap<WidgetId, u32>> =
+    Mutex::new(BTreeMap::new());
+
+static NEXT_GENERATION: AtomicU32 = AtomicU32::new(0);
+
+/// When the host last pushed data for each widget on its own.
+static LAST_PUSHES: Mutex<CriticalSectionRawMutex, BTreeMap<WidgetId, Instant>> =
+    Mutex::new(BTreeMap::new());
+
+/// Whether tasks of `generation` are still the ones responsible for `widget_id`.
+pub async fn is_current_generation(widget_id: WidgetId, generation: u32) -> bool {
+    TASK_GENERATIONS.lock().await.get(&widget_id) == Some(&generation)
+}
+
+/// Spawn the refresh and local update tasks a widget needs, replacing any older ones.
+pub async fn spawn_widget_tasks(
+    widget: &MemoriWidget,
+    transport: &'static Mutex<CriticalSectionRawMutex, DeviceBLETransport>,
+    state: &'static Mutex<CriticalSectionRawMutex, MemoriState>,
+    render_tx: RenderTx,
+    spawner: Spawner,
+) {
+    let generation = {
+        // No atomic read-modify-write on this chip, holding the lock keeps this race free.
+        let mut generations = TASK_GENERATIONS.lock().await;
+        let generation = NEXT_GENERATION.load(Ordering::Relaxed);
+        NEXT_GENERATION.store(generation.wrapping_add(1), Ordering::Relaxed);
+        generations.insert(widget.id, generation);
+        generation
+    };
+
+    if let Some(seconds) = widget.get_remote_update_frequency().to_seconds() {
+        let _ = spawner
+            .spawn(refresh_widget_task(widget.id, seconds, transport, state, render_tx.clone(), generation))
+            .inspect_err(|e| error!("Error with spawning refresh task: {e:#?}, aborting spawning refresh for this task, may not work as intended."));
+    }
+
+    let local_seconds = match widget.get_local_update_frequency() {
+        UpdateFrequency::Seconds(s) if s < 60 => Some(s),
+        UpdateFrequency::Minutes(m) => Some(m * 60),
+        _ => None,
+    };
+
+    if let Some(seconds) = local_seconds {
+        let _ = spawner
+            .spawn(widget_update_task(state, widget.id, seconds as u64, render_tx, generation))
+            .inspect_err(|e| error!("Error with spawning widget update task: {e:#?}"));
+    }
+}
+
+/// Tell the tasks of a widget to stop, they exit the next time they wake up.
+pub async fn retire_widget_tasks(widget_id: WidgetId) {
+    TASK_GENERATIONS.lock().await.remove(&widget_id);
+    LAST_PUSHES.lock().await.remove(&widget_id);
+}
+
+/// Tell the tasks of every widget to stop.
+pub async fn retire_all_widget_tasks() {
+    TASK_GENERATIONS.lock().await.clear();
+    LAST_PUSHES.lock().await.clear();
+}
+
+/// Remember that the host just pushed data for this widget.
+pub async fn record_push(widget_id: WidgetId) {
+    LAST_PUSHES.lock().await.insert(widget_id, Instant::now());
+}
+
+/// Whether the host pushed data for this widget in the last `period`.
+async fn pushed_within(widget_id: WidgetId, period: Duration) -> bool {
+    LAST_PUSHES
+        .lock()
+        .await
+        .get(&widget_id)
+        .is_some_and(|pushed_at| pushed_at.elapsed() < period)
+}
+
+/// Refreshes widget data from the host on the interval specified in the widget.
+#[embassy_executor::task(pool_size = 16)]
+async fn refresh_widget_task(
+    widget_id: WidgetId,
+    wait_period: u32,
+    transport: &'static Mutex<CriticalSectionRawMutex, DeviceBLETransport>,
+    state: &'static Mutex<CriticalSectionRawMutex, MemoriState>,
+    render_tx: RenderTx,
+    my_generation: u32,
+) {
+    let wait_period = Duration::from_secs(wait_period.into());
+
+    loop {
+        Timer::after(wait_period).await;
+
+        // If a newer task took over this widget, we just kill ourself lol.
+        if !is_current_generation(widget_id, my_generation).await {
+            info!("Generation increased! killing myself!");
+            return;
+        }
+
+        // The host is keeping this widget up to date on its own, leave the radio alone.
+        if pushed_within(widget_id, wait_period).await {
+            continue;
+        }
+
+        let mut transport = transport.lock().await;
+
+        if !BLE_CONNECTED.load(Ordering::SeqCst) {
+            error!("Phone not connected! cannot refresh!");
+            continue;
+        }
+
+        let Ok(data) = transport
+            .refresh_data(widget_id)
+            .await
+            .inspect_err(|e| error!("Failed to refresh data for widget: {e:#?}"))
+        else {
+            continue;
+        };
+
+        info!("successfully got refresh data: {data:?}");
+
+        // Drop guard as soon as possible.
+        drop(transport);
+
+        let mut state = state.lock().await;
+        // The widget may have been removed while we were waiting on the host.
+        if let Some(widget) = state.widgets.get_mut(&widget_id) {
+            *widget = data;
+        }
+
+        render_tx.send(crate::Render {}).await;
+    }
+}
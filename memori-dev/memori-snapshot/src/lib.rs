@@ -0,0 +1,173 @@
+//! Renders a [`MemoriState`] the way the device would, without a panel or a
+//! simulator window.
+//!
+//! The state goes through mousefood with the same fonts as the firmware into a
+//! 296x128 buffer in memory, which can then be saved as a PNG or dumped as
+//! text for tests and previews.
+
+use std::convert::Infallible;
+
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::*};
+use memori_ui::overlay::PixelLayer;
+use memori_ui::{DISPLAY_HEIGHT, DISPLAY_WIDTH, FONT_REGULAR, Memori, MemoriState, StateError};
+use mousefood::{EmbeddedBackend, EmbeddedBackendConfig};
+use ratatui::{Terminal, buffer::Buffer, layout::Rect, widgets::Widget};
+
+const WIDTH: usize = DISPLAY_WIDTH as usize;
+const HEIGHT: usize = DISPLAY_HEIGHT as usize;
+
+/// Stands in for the e-paper display.
+struct Canvas {
+    /// Row major, `true` where the pixel is on.
+    pixels: Vec<bool>,
+}
+
+impl OriginDimensions for Canvas {
+    fn size(&self) -> Size {
+        Size::new(DISPLAY_WIDTH.into(), DISPLAY_HEIGHT.into())
+    }
+}
+
+impl DrawTarget for Canvas {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            let (Ok(x), Ok(y)) = (usize::try_from(point.x), usize::try_from(point.y)) else {
+                continue;
+            };
+            if x < WIDTH && y < HEIGHT {
+                self.pixels[y * WIDTH + x] = color.is_on();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One rendered frame of a [`MemoriState`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pixels: Vec<bool>,
+    text: String,
+}
+
+impl Snapshot {
+    /// Renders the active frame of `state`, `inverted` like dark mode on the
+    /// device.
+    ///
+    /// Refuses states the device would refuse too, see [`MemoriState::validate`].
+    pub fn render(state: &MemoriState, inverted: bool) -> Result<Self, Box<StateError>> {
+        state.validate()?;
+
+        let mut canvas = Canvas {
+            pixels: vec![false; WIDTH * HEIGHT],
+        };
+        {
+            let pixels = PixelLayer::new();
+            let flush_pixels = pixels.clone();
+            let config = EmbeddedBackendConfig {
+                font_regular: memori_ui::FONT_REGULAR,
+                font_bold: memori_ui::FONT_BOLD,
+                font_italic: memori_ui::FONT_ITALIC,
+                flush_callback: Box::new(move |canvas: &mut Canvas| {
+                    let Ok(()) = flush_pixels.draw(canvas);
+                }),
+                ..Default::default()
+            };
+            let backend = EmbeddedBackend::new(&mut canvas, config);
+            let term = Terminal::new(backend).expect("drawing in memory can't fail");
+
+            let mut memori = Memori::with_pixel_layer(term, pixels);
+            memori.set_inverted(inverted);
+            memori.update(state).expect("drawing in memory can't fail");
+        }
+
+        Ok(Self {
+            pixels: canvas.pixels,
+            text: render_text(state),
+        })
+    }
+
+    /// Whether the pixel at `x`, `y` is on, which is white on the panel.
+    ///
+    /// # Panics
+    ///
+    /// If the pixel is outside of the display.
+    pub fn pixel(&self, x: u16, y: u16) -> bool {
+        assert!(
+            x < DISPLAY_WIDTH && y < DISPLAY_HEIGHT,
+            "pixel out of bounds"
+        );
+        self.pixels[usize::from(y) * WIDTH + usize::from(x)]
+    }
+
+    /// The frame as a grayscale PNG.
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut png = Vec::new();
+
+        let mut encoder = png::Encoder::new(&mut png, DISPLAY_WIDTH.into(), DISPLAY_HEIGHT.into());
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let data: Vec<u8> = self
+            .pixels
+            .iter()
+            .map(|&on| if on { u8::MAX } else { 0 })
+            .collect();
+
+        // Writing into a Vec only fails on a bad header, which these dimensions aren't.
+        let mut writer = encoder.write_header().expect("valid png header");
+        writer
+            .write_image_data(&data)
+            .expect("image data matches header");
+        writer.finish().expect("writing into memory can't fail");
+
+        png
+    }
+
+    /// The frame as ASCII art, one line per row of pixels with `#` for the
+    /// pixels that are on and `.` for the ones that are off.
+    pub fn to_ascii(&self) -> String {
+        let mut ascii = String::with_capacity((WIDTH + 1) * HEIGHT);
+        for row in self.pixels.chunks(WIDTH) {
+            ascii.extend(row.iter().map(|&on| if on { '#' } else { '.' }));
+            ascii.push('\n');
+        }
+        ascii
+    }
+
+    /// The terminal cells the frame was drawn from, one line per row with the
+    /// trailing spaces trimmed.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+/// Renders `state` into a plain ratatui buffer the size of the terminal on the
+/// device and reads the symbols back.
+fn render_text(state: &MemoriState) -> String {
+    let font = FONT_REGULAR.character_size;
+    let area = Rect::new(
+        0,
+        0,
+        DISPLAY_WIDTH / font.width as u16,
+        DISPLAY_HEIGHT / font.height as u16,
+    );
+
+    let mut buf = Buffer::empty(area);
+    state.render(area, &mut buf);
+
+    let mut text = String::new();
+    for y in area.top()..area.bottom() {
+        let line: String = (area.left()..area.right())
+            .map(|x| buf[(x, y)].symbol())
+            .collect();
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+    text
+}
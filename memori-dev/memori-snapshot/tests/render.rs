@@ -0,0 +1,159 @@
+use memori_snapshot::Snapshot;
+use memori_ui::MemoriState;
+use memori_ui::battery::BatteryStatus;
+use memori_ui::layout::{MemoriLayout, SplitDirection, SplitSize};
+use memori_ui::overlay::Bitmap;
+use memori_ui::widgets::{Image, MemoriWidget, Name, Qr, SlotSize, WidgetId, WidgetKind};
+use ratatui::layout::Rect;
+
+#[test]
+fn renders_the_active_frame() {
+    let snapshot = Snapshot::render(&MemoriState::default(), false).unwrap();
+
+    assert!(snapshot.text().starts_with("Hello Surendra!\n"));
+    assert!(snapshot.to_ascii().contains('#'));
+
+    let png = snapshot.to_png();
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+}
+
+#[test]
+fn inverted_flips_every_pixel() {
+    let state = MemoriState::default();
+    let normal = Snapshot::render(&state, false).unwrap();
+    let inverted = Snapshot::render(&state, true).unwrap();
+
+    // The terminal is 42x9 cells of 7x14 pixels, the strips right and below it
+    // are never drawn.
+    for (x, y) in [(0, 0), (3, 5), (150, 64), (293, 125)] {
+        assert_ne!(normal.pixel(x, y), inverted.pixel(x, y));
+    }
+    assert_eq!(normal.text(), inverted.text());
+}
+
+#[test]
+fn qr_codes_are_dark_on_light_either_way() {
+    let widget = MemoriWidget::with_never_update_frequency(
+        0,
+        WidgetKind::Qr(Qr::new("https://example.com", "")),
+    );
+    let state = MemoriState::new(0, [widget], vec![MemoriLayout::Full(WidgetId(0))], 0);
+    let [overlay] = &state.overlays(Rect::new(0, 0, 42, 9))[..] else {
+        panic!("expected the code's overlay");
+    };
+
+    // The quiet zone is two modules wide, then the finder pattern starts.
+    let (x, y) = (overlay.x as u16, overlay.y as u16);
+    let module = overlay.scale * 2;
+    for inverted in [false, true] {
+        let snapshot = Snapshot::render(&state, inverted).unwrap();
+        assert!(snapshot.pixel(x, y), "quiet zone is light");
+        assert!(!snapshot.pixel(x + module, y + module), "finder is dark");
+    }
+}
+
+#[test]
+fn images_fill_their_slot_pixel_for_pixel() {
+    let (width, height) = SlotSize::Full.pixel_size();
+    let mut bitmap = Bitmap::new(width, height);
+    bitmap.set(0, 0, true);
+    bitmap.set(width - 1, height - 1, true);
+    let widget =
+        MemoriWidget::with_never_update_frequency(0, WidgetKind::Image(Image::new(bitmap)));
+    let state = MemoriState::new(0, [widget], vec![MemoriLayout::Full(WidgetId(0))], 0);
+
+    for inverted in [false, true] {
+        let snapshot = Snapshot::render(&state, inverted).unwrap();
+        assert!(!snapshot.pixel(0, 0), "corner is dark");
+        assert!(snapshot.pixel(1, 0), "next to it is light");
+        assert!(
+            !snapshot.pixel(width - 1, height - 1),
+            "other corner is dark"
+        );
+    }
+}
+
+#[test]
+fn battery_sits_in_the_top_right_corner() {
+    let mut state = MemoriState::default();
+    state.set_battery(Some(BatteryStatus::new(50, false)));
+    let [.., overlay] = &state.overlays(Rect::new(0, 0, 42, 9))[..] else {
+        panic!("expected the battery's overlay");
+    };
+    let (x, y) = (overlay.x as u16, overlay.y as u16);
+
+    for inverted in [false, true] {
+        let snapshot = Snapshot::render(&state, inverted).unwrap();
+        let outline = snapshot.pixel(x, y);
+        assert_ne!(outline, snapshot.pixel(x + 1, y + 1), "gap inside outline");
+        assert_eq!(outline, snapshot.pixel(x + 2, y + 2), "half full");
+        assert_ne!(outline, snapshot.pixel(x + 9, y + 2), "half empty");
+        assert_eq!(outline, !inverted, "outline has the color of text");
+    }
+}
+
+fn name(id: u32, name: &str) -> MemoriWidget {
+    MemoriWidget::with_never_update_frequency(id, WidgetKind::Name(Name::new(name)))
+}
+
+#[test]
+fn nested_splits_give_each_child_its_share() {
+    // Three rows, the middle one split 1:2 into two columns.
+    let frame = MemoriLayout::Split {
+        direction: SplitDirection::Vertical,
+        constraints: vec![
+            SplitSize::Length(3),
+            SplitSize::Fill(1),
+            SplitSize::Length(3),
+        ],
+        children: vec![
+            MemoriLayout::Full(WidgetId(0)),
+            MemoriLayout::Split {
+                direction: SplitDirection::Horizontal,
+                constraints: vec![SplitSize::Ratio(1, 3), SplitSize::Ratio(2, 3)],
+                children: vec![
+                    MemoriLayout::Full(WidgetId(1)),
+                    MemoriLayout::Full(WidgetId(2)),
+                ],
+            },
+            MemoriLayout::Full(WidgetId(3)),
+        ],
+    };
+    let widgets = [name(0, "Ada"), name(1, "Bo"), name(2, "Cy"), name(3, "Di")];
+    let state = MemoriState::new(0, widgets, vec![frame], 0);
+
+    let snapshot = Snapshot::render(&state, false).unwrap();
+    let lines: Vec<&str> = snapshot.text().lines().collect();
+    assert_eq!(lines[0], "Hello Ada!");
+    // 42 cells wide, a third of them for the left column.
+    assert_eq!(lines[3], format!("{:<14}Hello Cy!", "Hello Bo!"));
+    assert_eq!(lines[6], "Hello Di!");
+}
+
+#[test]
+fn bad_splits_are_refused() {
+    let frames = vec![
+        MemoriLayout::Full(WidgetId(0)),
+        // The third child has no constraint.
+        MemoriLayout::Split {
+            direction: SplitDirection::Horizontal,
+            constraints: vec![SplitSize::Fill(1); 2],
+            children: vec![MemoriLayout::Full(WidgetId(0)); 3],
+        },
+        // Nested a level down, dividing by zero.
+        MemoriLayout::Split {
+            direction: SplitDirection::Vertical,
+            constraints: vec![SplitSize::Fill(1)],
+            children: vec![MemoriLayout::Split {
+                direction: SplitDirection::Horizontal,
+                constraints: vec![SplitSize::Ratio(1, 0)],
+                children: vec![MemoriLayout::Full(WidgetId(0))],
+            }],
+        },
+    ];
+    let state = MemoriState::new(0, [name(0, "Ada")], frames, 0);
+
+    let err = Snapshot::render(&state, false).unwrap_err();
+    assert_eq!(err.mismatched_splits, [1]);
+    assert_eq!(err.zero_denominator_ratios, [2]);
+}
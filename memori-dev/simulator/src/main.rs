@@ -1,17 +1,48 @@
 use color_eyre::eyre::Result;
 use embedded_graphics::{pixelcolor::BinaryColor, prelude::*};
+use embedded_graphics_simulator::sdl2::Keycode;
 use embedded_graphics_simulator::{OutputSettings, SimulatorDisplay, SimulatorEvent, Window};
-use memori_tcp::{DeviceResponse, DeviceTcpTransport, HostRequest, Sequenced};
+use memori_tcp::{DeviceTcpTransport, Sequenced};
+use memori_ui::input::{Button, InputEffect, InputEvent, PressDetector};
 use memori_ui::layout::MemoriLayout;
+use memori_ui::overlay::PixelLayer;
+use memori_ui::time::LocalTime;
 use memori_ui::widgets::{MemoriWidget, Name, UpdateFrequency, WidgetId, WidgetKind};
-use memori_ui::{Memori, MemoriState};
+use memori_ui::{DISPLAY_HEIGHT, DISPLAY_WIDTH, Memori, MemoriState};
 use mousefood::{EmbeddedBackend, EmbeddedBackendConfig};
-use std::{sync::Arc, time::Duration};
-use tokio::{sync::Mutex, time::sleep};
-use transport::DeviceTransport;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, mpsc};
+use tokio::time::{sleep, timeout};
+use transport::{Capabilities, ConfigApplied, TransError};
+use transport::input::forward_input;
+use transport::protocol::{DeviceResponse, HostCommand};
+use transport::{DeviceConfig, DeviceTransport, DisplayRotation};
 
 use ratatui::Terminal;
-use tracing::{Level, error, info};
+use tracing::{Level, error, info, warn};
+
+/// Where the simulator keeps its config between runs, like the device does in flash.
+const CONFIG_PATH: &str = "simulator-config.bin";
+
+/// What the host said the time was, and when it said it.
+#[derive(Clone, Copy)]
+struct SyncedTime {
+    at: Instant,
+    utc_epoch_secs: i64,
+    utc_offset_minutes: i16,
+}
+
+impl SyncedTime {
+    /// The local time right now, counting from the sync like the device does.
+    fn now(&self) -> LocalTime {
+        LocalTime::from_utc(
+            self.utc_epoch_secs + self.at.elapsed().as_secs() as i64,
+            self.utc_offset_minutes,
+        )
+    }
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -35,7 +66,20 @@ async fn main() -> Result<()> {
 
     let mut display = SimulatorDisplay::<BinaryColor>::new(Size::new(296, 128));
 
-    simulator_window.set_max_fps(1);
+    // Often enough that a quick tap of a key is seen going down and up again.
+    simulator_window.set_max_fps(50);
+
+    let config = load_config();
+    let upside_down = Arc::new(AtomicBool::new(
+        config.rotation() == DisplayRotation::UpsideDown,
+    ));
+    let config = Arc::new(Mutex::new(config));
+
+    let flush_upside_down = upside_down.clone();
+    let pixels = PixelLayer::new();
+    let flush_pixels = pixels.clone();
+    let (press_tx, press_rx) = std::sync::mpsc::channel();
+    let mut buttons = Buttons::new();
 
     let backend_config = EmbeddedBackendConfig {
         font_regular: memori_ui::FONT_REGULAR,
@@ -43,9 +87,22 @@ async fn main() -> Result<()> {
         font_italic: memori_ui::FONT_ITALIC,
         // Define how to display newly rendered widgets to the simulator window
         flush_callback: Box::new(move |display: &mut SimulatorDisplay<BinaryColor>| {
-            simulator_window.update(display);
-            if simulator_window.events().any(|e| e == SimulatorEvent::Quit) {
-                panic!("simulator window closed");
+            let Ok(()) = flush_pixels.draw(display);
+            if flush_upside_down.load(Ordering::Relaxed) {
+                simulator_window.update(&turned_around(display));
+            } else {
+                simulator_window.update(display);
+            }
+            for event in simulator_window.events() {
+                match event {
+                    SimulatorEvent::Quit => panic!("simulator window closed"),
+                    SimulatorEvent::KeyDown { keycode, .. } => buttons.set(keycode, true),
+                    SimulatorEvent::KeyUp { keycode, .. } => buttons.set(keycode, false),
+                    _ => {}
+                }
+            }
+            for press in buttons.presses() {
+                let _ = press_tx.send(press);
             }
         }),
         ..Default::default()
@@ -56,7 +113,7 @@ async fn main() -> Result<()> {
     // Start ratatui with our simulator backend
     let term = Terminal::new(backend).expect("something went wrong");
 
-    let mut memori = Memori::new(term);
+    let mut memori = Memori::with_pixel_layer(term, pixels);
 
     let mem_state = {
         let state = MemoriState::new(
@@ -109,21 +166,153 @@ async fn main() -> Result<()> {
         Arc::new(Mutex::new(state))
     };
 
-    tokio::spawn(state_handler(mem_state.clone()));
+    let synced_time = Arc::new(Mutex::new(None));
+    let (forward_tx, forward_rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(state_handler(
+        mem_state.clone(),
+        config.clone(),
+        upside_down,
+        synced_time.clone(),
+        forward_rx,
+    ));
+
+    tokio::spawn(frame_rotation(mem_state.clone(), config.clone()));
 
     // This loop contains the logic for running the UI
     loop {
-        memori
-            .update(&*mem_state.lock().await)
-            .expect("should have been successfull");
+        memori.set_inverted(config.lock().await.dark_mode());
+        let mut state = mem_state.lock().await;
+        if let Some(synced) = *synced_time.lock().await {
+            state.set_time(synced.now());
+        }
+        memori.update(&state).expect("should have been successfull");
+        drop(state);
+
+        // Same as the device, the state changes right away and the host hears
+        // about it after. The next update draws the change.
+        while let Ok(event) = press_rx.try_recv() {
+            info!("button pressed: {event:?}");
+            let effect = mem_state.lock().await.handle_input(event);
+            let _ = forward_tx.send((event, effect));
+        }
 
         // Thread sleep so it doesn't busy loop
         std::thread::sleep(std::time::Duration::from_millis(30));
     }
 }
 
-async fn state_handler(state: Arc<Mutex<MemoriState>>) -> Result<()> {
-    let transport = DeviceTcpTransport::default();
+/// The arrow keys and space standing in for the device's buttons, fed through
+/// the same [`PressDetector`]s so holding a key down is a long press.
+struct Buttons {
+    started: Instant,
+    buttons: [(Button, bool, PressDetector); 3],
+}
+
+impl Buttons {
+    fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            buttons: [Button::Prev, Button::Next, Button::Select]
+                .map(|button| (button, false, PressDetector::new())),
+        }
+    }
+
+    fn button(keycode: Keycode) -> Option<Button> {
+        match keycode {
+            Keycode::LEFT | Keycode::UP => Some(Button::Prev),
+            Keycode::RIGHT | Keycode::DOWN => Some(Button::Next),
+            Keycode::SPACE => Some(Button::Select),
+            _ => None,
+        }
+    }
+
+    /// A key went down or up, key repeats change nothing.
+    fn set(&mut self, keycode: Keycode, down: bool) {
+        let Some(button) = Self::button(keycode) else {
+            return;
+        };
+        for (b, held, _) in &mut self.buttons {
+            if *b == button {
+                *held = down;
+            }
+        }
+    }
+
+    /// The presses finished since the last call, call it every frame.
+    fn presses(&mut self) -> Vec<InputEvent> {
+        let now = self.started.elapsed().as_millis() as u64;
+        self.buttons
+            .iter_mut()
+            .filter_map(|(button, held, detector)| {
+                detector
+                    .update(*held, now)
+                    .map(|press| InputEvent::new(*button, press))
+            })
+            .collect()
+    }
+}
+
+/// Cycles through the frames like the device does, the UI loop picks up the change.
+async fn frame_rotation(state: Arc<Mutex<MemoriState>>, config: Arc<Mutex<DeviceConfig>>) {
+    loop {
+        // A frame time of 0 keeps the current frame, check again in a bit in case it changes.
+        let frame_time = state.lock().await.frame_time().max(1);
+        sleep(Duration::from_secs(frame_time.into())).await;
+
+        if !config.lock().await.frame_rotation() {
+            continue;
+        }
+
+        let mut state = state.lock().await;
+        if state.frame_time() != 0 && state.frames().len() > 1 {
+            state.next_frame();
+        }
+    }
+}
+
+/// The display turned 180 degrees.
+fn turned_around(display: &SimulatorDisplay<BinaryColor>) -> SimulatorDisplay<BinaryColor> {
+    let size = display.size();
+    let mut turned = SimulatorDisplay::new(size);
+    let max = Point::new(size.width as i32 - 1, size.height as i32 - 1);
+    let pixels = display
+        .bounding_box()
+        .points()
+        .map(|p| Pixel(max - p, display.get_pixel(p)));
+    turned
+        .draw_iter(pixels)
+        .expect("simulator display can't fail to draw");
+    turned
+}
+
+/// The config saved by a previous run, or the default one.
+fn load_config() -> DeviceConfig {
+    let Ok(bytes) = std::fs::read(CONFIG_PATH) else {
+        return DeviceConfig::default();
+    };
+    postcard::from_bytes(&bytes)
+        .inspect_err(|e| warn!("ignoring unreadable {CONFIG_PATH}: {e}"))
+        .unwrap_or_default()
+}
+
+fn save_config(config: &DeviceConfig) -> Result<()> {
+    std::fs::write(CONFIG_PATH, postcard::to_stdvec(config)?)?;
+    Ok(())
+}
+
+async fn state_handler(
+    state: Arc<Mutex<MemoriState>>,
+    config: Arc<Mutex<DeviceConfig>>,
+    upside_down: Arc<AtomicBool>,
+    synced_time: Arc<Mutex<Option<SyncedTime>>>,
+    mut presses: mpsc::UnboundedReceiver<(InputEvent, InputEffect)>,
+) -> Result<()> {
+    let transport = DeviceTcpTransport::with_capabilities(Capabilities::new(
+        env!("CARGO_PKG_VERSION"),
+        DISPLAY_WIDTH,
+        DISPLAY_HEIGHT,
+    ));
 
     let (mut conn, (mut host_req_rx, dev_resp_tx)) = transport.connect().await?;
     loop {
@@ -133,17 +322,109 @@ async fn state_handler(state: Arc<Mutex<MemoriState>>) -> Result<()> {
         if let Ok(req) = host_req_rx.try_recv() {
             info!("received device request! {req:?}");
             let resp = match req.msg_kind {
-                HostRequest::Ping => DeviceResponse::Pong,
-                HostRequest::GetBatteryLevel => DeviceResponse::BatteryLevel(69),
-                HostRequest::SetDeviceConfig(_config) => {
-                    todo!()
+                HostCommand::Ping => DeviceResponse::Ping { result: Ok(()) },
+                HostCommand::GetBatteryLevel => DeviceResponse::BatteryLevel { result: Ok(69) },
+                HostCommand::SetConfig { config: new_config } => {
+                    // There is no panel to refresh or bluetooth name to set here, the
+                    // rest of the settings are kept so they survive a restart.
+                    info!("device name is now {:?}", new_config.name());
+                    upside_down.store(
+                        new_config.rotation() == DisplayRotation::UpsideDown,
+                        Ordering::Relaxed,
+                    );
+                    if let Err(e) = save_config(&new_config) {
+                        error!("failed to save config: {e}");
+                    }
+                    *config.lock().await = new_config;
+                    DeviceResponse::SetConfig {
+                        result: Ok(ConfigApplied::Now),
+                    }
+                }
+                HostCommand::SetState { state: new_state } => {
+                    if let Err(e) = new_state.validate() {
+                        error!("refusing state: {e}");
+                        DeviceResponse::SetState {
+                            result: Err(TransError::InvalidState(e)),
+                        }
+                    } else {
+                        *state.lock().await = new_state;
+                        DeviceResponse::SetState { result: Ok(()) }
+                    }
+                }
+                HostCommand::GetWidget { widget_id } => DeviceResponse::GetWidget {
+                    result: state
+                        .lock()
+                        .await
+                        .widgets
+                        .get(&widget_id)
+                        .cloned()
+                        .ok_or(TransError::WidgetNotFound),
+                },
+                HostCommand::UpsertWidget { widget } => {
+                    state.lock().await.upsert_widget(widget);
+                    DeviceResponse::UpsertWidget { result: Ok(()) }
+                }
+                HostCommand::RemoveWidget { widget_id } => {
+                    let state = &mut *state.lock().await;
+                    let result = if state.is_widget_shown(widget_id) {
+                        Err(TransError::WidgetInUse)
+                    } else {
+                        state
+                            .remove_widget(widget_id)
+                            .map(|_| ())
+                            .ok_or(TransError::WidgetNotFound)
+                    };
+                    DeviceResponse::RemoveWidget { result }
                 }
-                HostRequest::SetState(new_state) => {
+                HostCommand::SetFrames { frames } => {
                     let state = &mut *state.lock().await;
-                    *state = *new_state;
-                    DeviceResponse::Success
+                    // Only walked once the splits are known not to be too deep.
+                    let all_widgets_exist = || {
+                        frames
+                            .iter()
+                            .flat_map(|frame| frame.widget_ids())
+                            .all(|id| state.widgets.contains_key(&id))
+                    };
+
+                    let result = if let Err(e) = MemoriState::validate_splits(&frames) {
+                        error!("refusing frames: {e}");
+                        Err(TransError::InvalidState(e))
+                    } else if !all_widgets_exist() {
+                        Err(TransError::WidgetNotFound)
+                    } else if !state.set_frames(frames) {
+                        Err(TransError::InvalidMessage)
+                    } else {
+                        Ok(())
+                    };
+                    DeviceResponse::SetFrames { result }
+                }
+                HostCommand::SetActiveFrame { index } => DeviceResponse::SetActiveFrame {
+                    result: if state.lock().await.set_active_frame(index) {
+                        Ok(())
+                    } else {
+                        Err(TransError::InvalidMessage)
+                    },
+                },
+                HostCommand::PushWidget { widget } => DeviceResponse::PushWidget {
+                    result: match state.lock().await.widgets.get_mut(&widget.id) {
+                        Some(existing) => {
+                            existing.kind = widget.kind;
+                            Ok(())
+                        }
+                        None => Err(TransError::WidgetNotFound),
+                    },
+                },
+                HostCommand::SyncTime {
+                    utc_epoch_secs,
+                    utc_offset_minutes,
+                } => {
+                    *synced_time.lock().await = Some(SyncedTime {
+                        at: Instant::now(),
+                        utc_epoch_secs,
+                        utc_offset_minutes,
+                    });
+                    DeviceResponse::SyncTime { result: Ok(()) }
                 }
-                HostRequest::GetWidget(_id) => todo!(),
             };
 
             info!("sending response: {resp:#?}");
@@ -153,6 +434,19 @@ async fn state_handler(state: Arc<Mutex<MemoriState>>) -> Result<()> {
                 .unwrap();
         }
 
-        sleep(Duration::from_secs(1)).await;
+        // Wait for the next ping, or less when a button is pressed.
+        if let Ok(Some((event, effect))) = timeout(Duration::from_secs(1), presses.recv()).await {
+            match forward_input(&mut conn, event, &effect).await {
+                Ok(widgets) => {
+                    let mut state = state.lock().await;
+                    for data in widgets {
+                        if let Some(widget) = state.widgets.get_mut(&data.id) {
+                            *widget = data;
+                        }
+                    }
+                }
+                Err(e) => error!("failed to forward {event:?} to the host: {e:?}"),
+            }
+        }
     }
 }
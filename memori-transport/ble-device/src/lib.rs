@@ -5,28 +5,36 @@ use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
 use embassy_sync::channel::{Channel, Sender};
 use embassy_sync::signal::Signal;
 use embassy_time::{Duration, with_timeout};
+use memori_ui::input::InputEvent;
 use memori_ui::widgets::{MemoriWidget, WidgetId};
-use portable_atomic::{AtomicBool, AtomicU32, Ordering};
-use transport::ble_types::*;
+use portable_atomic::{AtomicBool, AtomicU16, AtomicU32, Ordering};
+use transport::fragment::TransferId;
+use transport::protocol::{DeviceCommand, HostResponse, MessageID};
 use transport::{DeviceTransport, TransError, TransResult};
 
 const MAX_INFLIGHT: usize = 4;
 
 pub const BLE_TIMEOUT_DUR: u64 = 5;
 pub static BLE_CMD_CHANNEL: Channel<CriticalSectionRawMutex, OutgoingCommand, 5> = Channel::new();
-pub static BLE_HOST_RESPONSE: [Signal<CriticalSectionRawMutex, HostBLEResponse>; MAX_INFLIGHT] =
+pub static BLE_HOST_RESPONSE: [Signal<CriticalSectionRawMutex, HostResponse>; MAX_INFLIGHT] =
     [const { Signal::new() }; MAX_INFLIGHT];
 
 pub static BLE_CONNECTED: AtomicBool = AtomicBool::new(false);
 pub static MESSAGE_ID_COUNTER: AtomicU32 = AtomicU32::new(0);
+pub static TRANSFER_ID_COUNTER: AtomicU16 = AtomicU16::new(0);
 
 fn get_next_id() -> MessageID {
     MESSAGE_ID_COUNTER.fetch_add(1, Ordering::SeqCst)
 }
 
+/// Get the id to tag the fragments of the next outgoing packet with.
+pub fn next_transfer_id() -> TransferId {
+    TRANSFER_ID_COUNTER.fetch_add(1, Ordering::SeqCst)
+}
+
 #[derive(Debug, Clone)]
 pub struct OutgoingCommand {
-    pub cmd: DeviceBLECommand,
+    pub cmd: DeviceCommand,
     pub id: MessageID,
 }
 
@@ -41,7 +49,7 @@ impl DeviceBLETransport {
         }
     }
 
-    async fn handle_command(&self, cmd: DeviceBLECommand) -> TransResult<HostBLEResponse> {
+    async fn handle_command(&self, cmd: DeviceCommand) -> TransResult<HostResponse> {
         if !BLE_CONNECTED.load(Ordering::SeqCst) {
             return Err(TransError::NotConnected);
         }
@@ -71,20 +79,49 @@ impl Default for DeviceBLETransport {
 
 impl DeviceTransport for DeviceBLETransport {
     async fn refresh_data(&mut self, widget_id: WidgetId) -> TransResult<MemoriWidget> {
-        let command = DeviceBLECommand::RefreshData { widget_id };
+        let command = DeviceCommand::RefreshData { widget_id };
 
         match self.handle_command(command).await {
-            Ok(HostBLEResponse::RefreshData { result }) => result,
+            Ok(HostResponse::RefreshData { result }) => result,
             Ok(_) => Err(TransError::InvalidMessage),
             Err(e) => Err(e),
         }
     }
 
     async fn ping(&mut self) -> TransResult<()> {
-        let command = DeviceBLECommand::Ping;
+        let command = DeviceCommand::Ping;
+
+        match self.handle_command(command).await {
+            Ok(HostResponse::Ping { result }) => result,
+            Ok(_) => Err(TransError::InvalidMessage),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn todo_toggled(
+        &mut self,
+        widget_id: WidgetId,
+        item_id: u32,
+        done: bool,
+    ) -> TransResult<()> {
+        let command = DeviceCommand::TodoToggled {
+            widget_id,
+            item_id,
+            done,
+        };
+
+        match self.handle_command(command).await {
+            Ok(HostResponse::TodoToggled { result }) => result,
+            Ok(_) => Err(TransError::InvalidMessage),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn input(&mut self, event: InputEvent) -> TransResult<()> {
+        let command = DeviceCommand::Input { event };
 
         match self.handle_command(command).await {
-            Ok(HostBLEResponse::Ping { result }) => result,
+            Ok(HostResponse::Input { result }) => result,
             Ok(_) => Err(TransError::InvalidMessage),
             Err(e) => Err(e),
         }
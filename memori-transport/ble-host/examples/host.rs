@@ -1,22 +1,24 @@
 use ble_host::*;
 use memori_ui::{
-    MemoriState,
+    DISPLAY_HEIGHT, DISPLAY_WIDTH, MemoriState,
     layout::MemoriLayout,
     widgets::{MemoriWidget, Weather, WidgetId},
 };
 use std::time::Duration;
 use tokio::time::{self};
-use transport::HostTransport;
+use transport::{Capabilities, HostTransport};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let code = 2479;
-    let (mut transport, address, (_rx, _tx)) = HostBLETransport::connect(&code.to_string(), None)
-        .await
-        .unwrap_or_else(|e| {
-            eprintln!("Fatal: failed to connect to BLE transport: {e:?}");
-            std::process::exit(1);
-        });
+    let capabilities = Capabilities::new(env!("CARGO_PKG_VERSION"), DISPLAY_WIDTH, DISPLAY_HEIGHT);
+    let (mut transport, address, (_rx, _tx)) =
+        HostBLETransport::connect(&code.to_string(), None, capabilities)
+            .await
+            .unwrap_or_else(|e| {
+                eprintln!("Fatal: failed to connect to BLE transport: {e:?}");
+                std::process::exit(1);
+            });
 
     time::sleep(Duration::from_secs(2)).await;
 
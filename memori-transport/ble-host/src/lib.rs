@@ -6,37 +6,167 @@ use btleplug::api::{
 use btleplug::platform::{Adapter, Manager, Peripheral};
 use futures::stream::StreamExt;
 use memori_ui::MemoriState;
+use memori_ui::battery::BatteryStatus;
+use memori_ui::layout::MemoriLayout;
 use memori_ui::widgets::{MemoriWidget, WidgetId};
-use postcard::from_bytes;
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::{Mutex, mpsc, oneshot};
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, RwLock, mpsc, oneshot, watch};
 use tokio::task::JoinHandle;
 use tokio::time::sleep;
-use transport::ble_types::*;
 use transport::ble_types::{
-    BATTERY_LEVEL_CHAR_UUID as BATTERY_CHAR_STR, NUS_RX_CHAR_UUID as NUS_RX_STR,
-    NUS_TX_CHAR_UUID as NUS_TX_STR,
+    BATTERY_LEVEL_CHAR_UUID as BATTERY_CHAR_STR, BATTERY_NOTIFY_CHAR_UUID as BATTERY_STATUS_STR,
+    NUS_RX_CHAR_UUID as NUS_RX_STR,
+    NUS_SERVICE_UUID as NUS_SERVICE_STR, NUS_TX_CHAR_UUID as NUS_TX_STR,
 };
+use transport::fragment::{Reassembler, TransferId, fragment_packet};
+use transport::protocol::*;
 
 use transport::*;
 use uuid::Uuid;
 
+const NUS_SERVICE_UUID: Uuid = Uuid::from_u128(NUS_SERVICE_STR);
 const NUS_RX_CHAR_UUID: Uuid = Uuid::from_u128(NUS_RX_STR);
 const NUS_TX_CHAR_UUID: Uuid = Uuid::from_u128(NUS_TX_STR);
 const BATTERY_LEVEL_CHAR_UUID: Uuid = uuid_from_u16(BATTERY_CHAR_STR);
+const BATTERY_STATUS_CHAR_UUID: Uuid = Uuid::from_u128(BATTERY_STATUS_STR);
+
+// every device advertises itself as memori-<pairing code>
+const NAME_PREFIX: &str = "memori-";
+
+// how long to wait between reconnect attempts, doubling up to the max
+const RECONNECT_BACKOFF_START: Duration = Duration::from_secs(1);
+const RECONNECT_BACKOFF_MAX: Duration = Duration::from_secs(30);
+
+type ResponseMap = Arc<Mutex<HashMap<MessageID, oneshot::Sender<DeviceResponse>>>>;
+type NotificationStream = Pin<Box<dyn futures::Stream<Item = ValueNotification> + Send>>;
+
+/// Where the link to the device is at, see [`HostBLETransport::connection_state`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    Connected,
+    /// The device dropped and we are trying to get it back.
+    Reconnecting { attempt: u32 },
+    /// Either we disconnected on purpose or the device came back incompatible.
+    Disconnected,
+}
+
+// everything tied to one particular BLE connection, swapped out on reconnect
+struct Link {
+    peripheral: Peripheral,
+    rx_char: Characteristic,
+    battery_char: Characteristic,
+    // whether the battery is charging
+    battery_status_char: Characteristic,
+}
 
-type ResponseMap = Arc<Mutex<HashMap<MessageID, oneshot::Sender<DeviceBLEResponse>>>>;
+// what the supervisor needs to put a fresh link in place of a dead one
+struct Supervised {
+    link: Arc<RwLock<Link>>,
+    stream_tx: mpsc::Sender<NotificationStream>,
+    outbound: mpsc::Sender<OutboundPacket>,
+    hello_rx: mpsc::Receiver<Capabilities>,
+    /// What we tell the device we support, again after every reconnect.
+    capabilities: Capabilities,
+    pending_responses: ResponseMap,
+    remote_capabilities: Arc<std::sync::Mutex<Capabilities>>,
+    state_tx: watch::Sender<ConnectionState>,
+    battery_tx: watch::Sender<Option<BatteryStatus>>,
+}
 
 struct OutboundPacket {
-    packet: HostBLEPacket,
+    packet: PacketPayload,
     id: Option<MessageID>, // none = auto-assign, some = use this ID
-    response_tx: Option<oneshot::Sender<DeviceBLEResponse>>,
+    response_tx: Option<oneshot::Sender<DeviceResponse>>,
+}
+
+async fn first_adapter() -> anyhow::Result<Adapter> {
+    let manager = Manager::new().await?;
+    manager
+        .adapters()
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("No BLE adapters found"))
+}
+
+// the address we hand out to callers to reconnect with later, some platforms
+// (macos) hide the real address so we fall back to the platform id
+fn peripheral_address(peripheral: &Peripheral) -> String {
+    let addr = peripheral.address().to_string();
+    if addr == "00:00:00:00:00:00" {
+        peripheral.id().to_string()
+    } else {
+        addr
+    }
+}
+
+/// A Memori device found by [`scan_devices`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredDevice {
+    pub name: Option<String>,
+    /// Pairing code taken from the advertised name, `None` if the device
+    /// only advertised the service.
+    pub code: Option<String>,
+    /// Pass this to [`HostBLETransport::connect`] as the known address.
+    pub address: String,
+    pub rssi: Option<i16>,
+}
+
+/// Scan for nearby Memori devices for `duration`. A device is sent again
+/// every time its advertisement changes, so the RSSI stays fresh. The scan
+/// stops early if the receiver is dropped.
+pub async fn scan_devices(duration: Duration) -> anyhow::Result<mpsc::Receiver<DiscoveredDevice>> {
+    let central = first_adapter().await?;
+    let mut events = central.events().await?;
+    central.start_scan(ScanFilter::default()).await?;
+
+    let (tx, rx) = mpsc::channel(16);
+
+    tokio::spawn(async move {
+        let _ = tokio::time::timeout(duration, async {
+            while let Some(event) = events.next().await {
+                let (CentralEvent::DeviceDiscovered(id) | CentralEvent::DeviceUpdated(id)) = event
+                else {
+                    continue;
+                };
+                let Ok(peripheral) = central.peripheral(&id).await else { continue };
+                let Ok(Some(props)) = peripheral.properties().await else { continue };
+
+                let code = props
+                    .local_name
+                    .as_deref()
+                    .and_then(|name| name.strip_prefix(NAME_PREFIX))
+                    .map(str::to_string);
+                if code.is_none() && !props.services.contains(&NUS_SERVICE_UUID) {
+                    continue;
+                }
+
+                let device = DiscoveredDevice {
+                    name: props.local_name,
+                    code,
+                    address: peripheral_address(&peripheral),
+                    rssi: props.rssi,
+                };
+                if tx.send(device).await.is_err() {
+                    break;
+                }
+            }
+        })
+        .await;
+
+        if let Err(e) = central.stop_scan().await {
+            eprintln!("[ble-host] scan: failed to stop scan: {e}");
+        }
+    });
+
+    Ok(rx)
 }
 
 async fn find_memori(central: &Adapter, code: &str) -> Option<Peripheral> {
-    let looking_for = format!("memori-{}", code.trim());
+    let looking_for = format!("{NAME_PREFIX}{}", code.trim());
     let mut events = central.events().await.ok()?;
     central.start_scan(ScanFilter::default()).await.ok()?;
    
@@ -58,31 +188,37 @@ async fn find_memori(central: &Adapter, code: &str) -> Option<Peripheral> {
 }
 
 async fn send_packet(
-    packet: BLEPacket,
+    packet: Packet,
+    transfer_id: TransferId,
     peripheral: &Peripheral,
     char: &btleplug::api::Characteristic,
 ) -> TransResult<()> {
-    let mut buf = [0u8; BLE_CHAR_SIZE];
-    let encoded = postcard::to_slice(&packet, &mut buf).map_err(|_| TransError::InvalidMessage)?;
+    let fragments = fragment_packet(&packet, transfer_id)?;
 
-    peripheral
-        .write(char, encoded, WriteType::WithoutResponse)
-        .await
-        .map_err(|e| {
-            eprintln!("[ble-host] send_packet error: {:?}", e);
-            TransError::ProtocolIssue
-        })?;
+    for fragment in fragments {
+        peripheral
+            .write(char, &fragment, WriteType::WithoutResponse)
+            .await
+            .map_err(|e| {
+                eprintln!("[ble-host] send_packet error: {:?}", e);
+                TransError::ProtocolIssue
+            })?;
+    }
 
     Ok(())
 }
 
 pub struct HostBLETransport {
     outbound: mpsc::Sender<OutboundPacket>,
-    battery_char: Characteristic,
-    pub peripheral: Peripheral,
+    link: Arc<RwLock<Link>>,
+    remote_capabilities: Arc<std::sync::Mutex<Capabilities>>,
+    state_rx: watch::Receiver<ConnectionState>,
+    state_tx: watch::Sender<ConnectionState>,
+    battery_rx: watch::Receiver<Option<BatteryStatus>>,
     read_handle: JoinHandle<()>,
     write_handle: JoinHandle<()>,
     command_handle: JoinHandle<()>,
+    supervisor_handle: JoinHandle<()>,
 }
 
 async fn find_or_reconnect(central: &Adapter, code: &str, known_id: Option<&str>) -> Option<Peripheral> {
@@ -120,24 +256,21 @@ async fn find_or_reconnect(central: &Adapter, code: &str, known_id: Option<&str>
 }
 
 impl HostBLETransport {
+    /// Connects to the device with pairing `code`, telling it we support
+    /// `capabilities` during the handshake.
     pub async fn connect(
         code: &str,
         known_address: Option<&str>,
+        capabilities: Capabilities,
     ) -> anyhow::Result<(
         Self,
         String,
         (
-            mpsc::UnboundedReceiver<DeviceBLECommand>,
-            mpsc::UnboundedSender<HostBLEResponse>,
+            mpsc::UnboundedReceiver<DeviceCommand>,
+            mpsc::UnboundedSender<HostResponse>,
         ),
     )> {
-        let manager = Manager::new().await?;
-        let central = manager
-            .adapters()
-            .await?
-            .into_iter()
-            .next()
-            .ok_or_else(|| anyhow::anyhow!("No BLE adapters found"))?;
+        let central = first_adapter().await?;
 
         let peripheral = find_or_reconnect(&central, code, known_address)
             .await
@@ -150,7 +283,123 @@ impl HostBLETransport {
             central.stop_scan().await?;
             tokio::time::sleep(Duration::from_millis(500)).await;
         }
-        
+
+        // subscribe before connecting so we can't miss the device dropping
+        let central_events = central.events().await?;
+
+        let (link, notif_stream) = Self::open_link(peripheral).await?;
+        let peripheral = link.peripheral.clone();
+        let (out_tx, out_rx) = mpsc::channel::<OutboundPacket>(16);
+        let (cmd_tx, cmd_rx) = mpsc::channel::<(DeviceCommand, u32)>(16);
+        let (hello_tx, mut hello_rx) = mpsc::channel::<Capabilities>(1);
+        let (stream_tx, stream_rx) = mpsc::channel::<NotificationStream>(1);
+        let (state_tx, state_rx) = watch::channel(ConnectionState::Connected);
+        let (battery_tx, battery_rx) = watch::channel(Self::read_battery(&link).await);
+        let link = Arc::new(RwLock::new(link));
+
+        let (device_command_tx, device_command_rx) = mpsc::unbounded_channel::<DeviceCommand>();
+        let (host_response_tx, host_response_rx) = mpsc::unbounded_channel::<HostResponse>();
+
+        let pending_responses: ResponseMap = Arc::new(Mutex::new(HashMap::new()));
+
+        // can't fail, the reader hasn't even started yet
+        let _ = stream_tx.send(notif_stream).await;
+
+        let read_handle = tokio::spawn(Self::notification_reader(
+            stream_rx,
+            cmd_tx,
+            hello_tx,
+            battery_tx.clone(),
+            pending_responses.clone(),
+        ));
+
+        let write_handle = tokio::spawn(Self::ble_writer(
+            out_rx,
+            link.clone(),
+            pending_responses.clone(),
+        ));
+
+        let command_handle = tokio::spawn(Self::server_command_handler(
+            cmd_rx,
+            out_tx.clone(),
+            device_command_tx,
+            host_response_rx,
+        ));
+
+        let remote_capabilities = match Self::handshake(&capabilities, &out_tx, &mut hello_rx).await {
+            Ok(caps) => caps,
+            Err(e) => {
+                eprintln!("[ble-host] handshake failed: {e}");
+                read_handle.abort();
+                write_handle.abort();
+                command_handle.abort();
+                let _ = peripheral.disconnect().await;
+                return Err(e.into());
+            }
+        };
+        eprintln!("[ble-host] device capabilities: {:?}", remote_capabilities);
+        let remote_capabilities = Arc::new(std::sync::Mutex::new(remote_capabilities));
+
+        let address = peripheral_address(&peripheral);
+
+        let supervisor_handle = tokio::spawn(Self::supervisor(
+            central,
+            central_events,
+            code.to_string(),
+            address.clone(),
+            Supervised {
+                link: link.clone(),
+                stream_tx,
+                outbound: out_tx.clone(),
+                hello_rx,
+                capabilities,
+                pending_responses,
+                remote_capabilities: remote_capabilities.clone(),
+                state_tx: state_tx.clone(),
+                battery_tx,
+            },
+        ));
+
+        Ok((
+            Self {
+                outbound: out_tx,
+                link,
+                remote_capabilities,
+                state_rx,
+                state_tx,
+                battery_rx,
+                read_handle,
+                write_handle,
+                command_handle,
+                supervisor_handle,
+            },
+            address,
+            (device_command_rx, host_response_tx),
+        ))
+    }
+
+    /// What the connected device told us it supports during the handshake.
+    pub fn remote_capabilities(&self) -> Capabilities {
+        self.remote_capabilities.lock().unwrap().clone()
+    }
+
+    /// Watch the link to the device go down and come back up. After a
+    /// reconnect the device may have lost everything we sent it, so callers
+    /// should resend their state when this goes back to
+    /// [`ConnectionState::Connected`].
+    pub fn connection_state(&self) -> watch::Receiver<ConnectionState> {
+        self.state_rx.clone()
+    }
+
+    /// Watch the device's battery, `None` until we managed to read it. The
+    /// device notifies us whenever it changes, so there's no need to poll
+    /// [`HostTransport::get_battery_level`].
+    pub fn battery(&self) -> watch::Receiver<Option<BatteryStatus>> {
+        self.battery_rx.clone()
+    }
+
+    // Connect to the peripheral and get everything we need to talk to it
+    async fn open_link(peripheral: Peripheral) -> anyhow::Result<(Link, NotificationStream)> {
         peripheral.connect().await?;
         eprintln!("[ble-host] connected, discovering services...");
         
@@ -179,66 +428,189 @@ impl HostBLETransport {
             .find(|c| c.uuid == BATTERY_LEVEL_CHAR_UUID)
             .ok_or_else(|| anyhow::anyhow!("Battery level characteristic not found"))?
             .clone();
+        let battery_status_char = chars
+            .iter()
+            .find(|c| c.uuid == BATTERY_STATUS_CHAR_UUID)
+            .ok_or_else(|| anyhow::anyhow!("Battery status characteristic not found"))?
+            .clone();
 
         peripheral.subscribe(&tx_char).await?;
+        peripheral.subscribe(&battery_char).await?;
+        peripheral.subscribe(&battery_status_char).await?;
+        let notif_stream = peripheral.notifications().await?;
 
-        let (out_tx, out_rx) = mpsc::channel::<OutboundPacket>(16);
-        let (cmd_tx, cmd_rx) = mpsc::channel::<(DeviceBLECommand, u32)>(16);
+        Ok((
+            Link {
+                peripheral,
+                rx_char,
+                battery_char,
+                battery_status_char,
+            },
+            notif_stream,
+        ))
+    }
 
-        let (device_command_tx, device_command_rx) = mpsc::unbounded_channel::<DeviceBLECommand>();
-        let (host_response_tx, host_response_rx) = mpsc::unbounded_channel::<HostBLEResponse>();
+    // The device only notifies us when the battery changes, so read it once
+    // to start with
+    async fn read_battery(link: &Link) -> Option<BatteryStatus> {
+        let level = link.peripheral.read(&link.battery_char).await.ok()?;
+        let status = link.peripheral.read(&link.battery_status_char).await.ok()?;
 
-        let pending_responses: ResponseMap = Arc::new(Mutex::new(HashMap::new()));
-        let notif_stream = peripheral.notifications().await?;
+        match (level.as_slice(), status.as_slice()) {
+            ([percent], [charging]) => Some(BatteryStatus::new(*percent, *charging != 0)),
+            _ => {
+                eprintln!("[ble-host] unexpected battery characteristic values");
+                None
+            }
+        }
+    }
 
-        let read_handle = tokio::spawn(Self::notification_reader(
-            notif_stream,
-            cmd_tx,
-            pending_responses.clone(),
-        ));
+    // Waits for the device to drop and brings the link back up, retrying
+    // with backoff until it works or we are aborted by disconnect()
+    async fn supervisor(
+        central: Adapter,
+        mut central_events: Pin<Box<dyn futures::Stream<Item = CentralEvent> + Send>>,
+        code: String,
+        address: String,
+        mut supervised: Supervised,
+    ) {
+        while let Some(event) = central_events.next().await {
+            let CentralEvent::DeviceDisconnected(id) = event else {
+                continue;
+            };
+            let peripheral = supervised.link.read().await.peripheral.clone();
+            if id != peripheral.id() {
+                continue;
+            }
+            // left over from a failed attempt that we tore down ourselves
+            if peripheral.is_connected().await.unwrap_or(false) {
+                continue;
+            }
 
-        let write_handle = tokio::spawn(Self::ble_writer(
-            out_rx,
-            peripheral.clone(),
-            rx_char.clone(),
-            pending_responses.clone(),
-        ));
+            eprintln!("[ble-host] supervisor: device dropped, reconnecting...");
 
-        let command_handle = tokio::spawn(Self::server_command_handler(
-            cmd_rx,
-            out_tx.clone(),
-            device_command_tx,
-            host_response_rx,
-        ));
-        
-        let address = {
-            let addr = peripheral.address().to_string();
-            if addr == "00:00:00:00:00:00" {
-                peripheral.id().to_string()
-            } else {
-                addr
+            // nobody is going to answer these anymore
+            supervised.pending_responses.lock().await.clear();
+
+            let mut backoff = RECONNECT_BACKOFF_START;
+            let mut attempt = 1;
+            loop {
+                let _ = supervised
+                    .state_tx
+                    .send(ConnectionState::Reconnecting { attempt });
+
+                match Self::reconnect(&central, &code, &address, &mut supervised).await {
+                    Ok(()) => break,
+                    Err(TransError::IncompatibleProtocol { local, remote }) => {
+                        eprintln!(
+                            "[ble-host] supervisor: device came back with protocol {remote}, we speak {local}, giving up"
+                        );
+                        let _ = supervised.state_tx.send(ConnectionState::Disconnected);
+                        return;
+                    }
+                    Err(e) => {
+                        eprintln!("[ble-host] supervisor: attempt {attempt} failed: {e}");
+                    }
+                }
+
+                sleep(backoff).await;
+                backoff = (backoff * 2).min(RECONNECT_BACKOFF_MAX);
+                attempt += 1;
+            }
+
+            eprintln!("[ble-host] supervisor: reconnected after {attempt} attempt(s)");
+            let _ = supervised.state_tx.send(ConnectionState::Connected);
+        }
+    }
+
+    // One attempt at bringing the link back up
+    async fn reconnect(
+        central: &Adapter,
+        code: &str,
+        address: &str,
+        supervised: &mut Supervised,
+    ) -> TransResult<()> {
+        let peripheral = find_or_reconnect(central, code, Some(address))
+            .await
+            .ok_or(TransError::NotConnected)?;
+
+        let (link, notif_stream) = Self::open_link(peripheral).await.map_err(|e| {
+            eprintln!("[ble-host] supervisor: failed to open link: {e}");
+            TransError::NotConnected
+        })?;
+        let peripheral = link.peripheral.clone();
+
+        // it may well have charged or drained while we were gone
+        if let Some(battery) = Self::read_battery(&link).await {
+            let _ = supervised.battery_tx.send(Some(battery));
+        }
+
+        *supervised.link.write().await = link;
+        supervised
+            .stream_tx
+            .send(notif_stream)
+            .await
+            .map_err(|_| TransError::ProtocolIssue)?;
+
+        // a stale hello from the old link would confuse the handshake
+        while supervised.hello_rx.try_recv().is_ok() {}
+
+        match Self::handshake(
+            &supervised.capabilities,
+            &supervised.outbound,
+            &mut supervised.hello_rx,
+        )
+        .await
+        {
+            Ok(caps) => {
+                *supervised.remote_capabilities.lock().unwrap() = caps;
+                Ok(())
             }
+            Err(e) => {
+                let _ = peripheral.disconnect().await;
+                Err(e)
+            }
+        }
+    }
+
+    // Exchange capabilities with the device, failing if we can't talk to it
+    async fn handshake(
+        local: &Capabilities,
+        outbound: &mpsc::Sender<OutboundPacket>,
+        hello_rx: &mut mpsc::Receiver<Capabilities>,
+    ) -> TransResult<Capabilities> {
+        let packet = OutboundPacket {
+            packet: PacketPayload::Hello(local.clone()),
+            id: None,
+            response_tx: None,
         };
-        Ok((
-            Self {
-                outbound: out_tx,
-                battery_char,
-                peripheral,
-                read_handle,
-                write_handle,
-                command_handle,
-            },
-            address,
-            (device_command_rx, host_response_tx),
-        ))
+
+        outbound
+            .send(packet)
+            .await
+            .map_err(|_| TransError::ProtocolIssue)?;
+
+        // firmware from before the handshake existed will never answer
+        let remote = match tokio::time::timeout(Duration::from_secs(5), hello_rx.recv()).await {
+            Ok(Some(remote)) => remote,
+            Ok(None) => return Err(TransError::ProtocolIssue),
+            Err(_) => return Err(TransError::Timeout),
+        };
+
+        local.check_compatible(&remote)?;
+        Ok(remote)
     }
 
     // Send a command and wait for response
-    async fn send_command(&self, command: HostBLECommand) -> TransResult<DeviceBLEResponse> {
+    async fn send_command(&self, command: HostCommand) -> TransResult<DeviceResponse> {
+        if *self.state_rx.borrow() != ConnectionState::Connected {
+            return Err(TransError::NotConnected);
+        }
+
         let (tx, rx) = oneshot::channel();
 
         let packet = OutboundPacket {
-            packet: HostBLEPacket::Command(command),
+            packet: PacketPayload::HostPacket(HostPacket::Command(command)),
             id: None, // Let blewriter assign the id
             response_tx: Some(tx),
         };
@@ -255,33 +627,82 @@ impl HostBLETransport {
         }
     }
 
+    // Reads one notification stream after the other, the supervisor hands us
+    // a new one every time it reconnects
     async fn notification_reader(
-        mut notif_stream: impl futures::Stream<Item = ValueNotification> + Unpin,
-        cmd_tx: mpsc::Sender<(DeviceBLECommand, MessageID)>,
+        mut stream_rx: mpsc::Receiver<NotificationStream>,
+        cmd_tx: mpsc::Sender<(DeviceCommand, MessageID)>,
+        hello_tx: mpsc::Sender<Capabilities>,
+        battery_tx: watch::Sender<Option<BatteryStatus>>,
         pending_responses: ResponseMap,
     ) {
+        while let Some(notif_stream) = stream_rx.recv().await {
+            Self::read_notifications(
+                notif_stream,
+                &cmd_tx,
+                &hello_tx,
+                &battery_tx,
+                &pending_responses,
+            )
+            .await;
+            eprintln!("[ble-host] notif-reader: notification stream ended");
+        }
+    }
+
+    async fn read_notifications(
+        mut notif_stream: NotificationStream,
+        cmd_tx: &mpsc::Sender<(DeviceCommand, MessageID)>,
+        hello_tx: &mpsc::Sender<Capabilities>,
+        battery_tx: &watch::Sender<Option<BatteryStatus>>,
+        pending_responses: &ResponseMap,
+    ) {
+        let started = Instant::now();
+        // half a packet from the old link is never getting finished
+        let mut reassembler = Reassembler::new();
+
         while let Some(notification) = notif_stream.next().await {
+            if notification.uuid == BATTERY_LEVEL_CHAR_UUID
+                || notification.uuid == BATTERY_STATUS_CHAR_UUID
+            {
+                Self::update_battery(battery_tx, &notification);
+                continue;
+            }
             if notification.uuid != NUS_TX_CHAR_UUID {
                 continue;
             }
 
-            let Ok(packet) = from_bytes::<BLEPacket>(&notification.value) else {
-                eprintln!("[ble-host] notif-reader: failed to parse BLEPacket");
-                continue;
+            let now_ms = started.elapsed().as_millis() as u64;
+            let packet = match reassembler.push(&notification.value, now_ms) {
+                Ok(Some(packet)) => packet,
+                // waiting on more fragments
+                Ok(None) => continue,
+                Err(e) => {
+                    eprintln!("[ble-host] notif-reader: failed to reassemble Packet: {e}");
+                    continue;
+                }
             };
 
-            let BLEPacketPayload::DevicePacket(device_packet) = packet.payload else {
-                eprintln!("[ble-host] notif-reader: received unexpected HostPacket from device");
-                continue;
+            let device_packet = match packet.payload {
+                PacketPayload::DevicePacket(device_packet) => device_packet,
+                PacketPayload::Hello(caps) => {
+                    if hello_tx.try_send(caps).is_err() {
+                        eprintln!("[ble-host] notif-reader: received unexpected Hello from device");
+                    }
+                    continue;
+                }
+                PacketPayload::HostPacket(_) => {
+                    eprintln!("[ble-host] notif-reader: received unexpected HostPacket from device");
+                    continue;
+                }
             };
 
             match device_packet {
-                DeviceBLEPacket::Command(cmd) => {
+                DevicePacket::Command(cmd) => {
                     if let Err(e) = cmd_tx.send((cmd, packet.id)).await {
                         eprintln!("[ble-host] notif-reader: Failed to send command: {:?}", e);
                     }
                 }
-                DeviceBLEPacket::Response(resp) => {
+                DevicePacket::Response(resp) => {
                     println!(
                         "[ble-host] notif-reader: Received response: {:?} (id: {})",
                         resp, packet.id
@@ -305,13 +726,31 @@ impl HostBLETransport {
         }
     }
 
+    // The level and whether it's charging come in as separate notifications
+    fn update_battery(battery_tx: &watch::Sender<Option<BatteryStatus>>, notification: &ValueNotification) {
+        let [value] = notification.value[..] else {
+            eprintln!("[ble-host] notif-reader: unexpected battery notification: {:?}", notification.value);
+            return;
+        };
+
+        battery_tx.send_modify(|battery| {
+            let mut status = battery.unwrap_or_default();
+            if notification.uuid == BATTERY_LEVEL_CHAR_UUID {
+                status = BatteryStatus::new(value, status.charging);
+            } else {
+                status.charging = value != 0;
+            }
+            *battery = Some(status);
+        });
+    }
+
     async fn ble_writer(
         mut outbound_rx: mpsc::Receiver<OutboundPacket>,
-        peripheral: Peripheral,
-        rx_char: Characteristic,
+        link: Arc<RwLock<Link>>,
         pending_responses: ResponseMap,
     ) {
         let mut next_msg_id: MessageID = 0;
+        let mut next_transfer_id: TransferId = 0;
 
         while let Some(outbound) = outbound_rx.recv().await {
             let id = outbound.id.unwrap_or_else(|| {
@@ -324,12 +763,16 @@ impl HostBLETransport {
                 pending_responses.lock().await.insert(id, response_tx);
             }
 
-            let packet = BLEPacket {
-                payload: BLEPacketPayload::HostPacket(outbound.packet),
+            let packet = Packet {
+                payload: outbound.packet,
                 id,
             };
 
-            if let Err(e) = send_packet(packet, &peripheral, &rx_char).await {
+            let transfer_id = next_transfer_id;
+            next_transfer_id = next_transfer_id.wrapping_add(1);
+
+            let link = link.read().await;
+            if let Err(e) = send_packet(packet, transfer_id, &link.peripheral, &link.rx_char).await {
                 eprintln!("[ble-host] BLE write failed: {:?}", e);
                 // error handling
                 pending_responses.lock().await.remove(&id);
@@ -338,10 +781,10 @@ impl HostBLETransport {
     }
 
     async fn server_command_handler(
-        mut cmd_rx: mpsc::Receiver<(DeviceBLECommand, MessageID)>,
+        mut cmd_rx: mpsc::Receiver<(DeviceCommand, MessageID)>,
         outbound_tx: mpsc::Sender<OutboundPacket>,
-        device_command_tx: mpsc::UnboundedSender<DeviceBLECommand>,
-        mut host_response_rx: mpsc::UnboundedReceiver<HostBLEResponse>,
+        device_command_tx: mpsc::UnboundedSender<DeviceCommand>,
+        mut host_response_rx: mpsc::UnboundedReceiver<HostResponse>,
     ) {
         while let Some((cmd, id)) = cmd_rx.recv().await {
             if let Err(e) = device_command_tx.send(cmd) {
@@ -359,7 +802,7 @@ impl HostBLETransport {
             };
 
             let packet = OutboundPacket {
-                packet: HostBLEPacket::Response(response),
+                packet: PacketPayload::HostPacket(HostPacket::Response(response)),
                 id: Some(id),
                 response_tx: None,
             };
@@ -371,8 +814,13 @@ impl HostBLETransport {
     }
 
     pub async fn disconnect(self) {
+        // stop the supervisor first so it doesn't bring the link right back
+        self.supervisor_handle.abort();
+        let _ = self.state_tx.send(ConnectionState::Disconnected);
+
+        let peripheral = self.link.read().await.peripheral.clone();
         tokio::spawn(async move {
-            if let Err(e) = self.peripheral.disconnect().await {
+            if let Err(e) = peripheral.disconnect().await {
                 eprintln!("[ble-host] disconnect: failed to disconnect: {}", e);
             }
         });
@@ -385,11 +833,11 @@ impl HostBLETransport {
 
 impl HostTransport for HostBLETransport {
     async fn set_state(&mut self, state: MemoriState) -> TransResult<()> {
-        let command = HostBLECommand::SetState { state };
+        let command = HostCommand::SetState { state };
         let response = self.send_command(command).await?;
 
         match response {
-            DeviceBLEResponse::SetState { result } => result,
+            DeviceResponse::SetState { result } => result,
             _ => {
                 eprintln!("[host_transport] Unexpected response type");
                 Err(TransError::ProtocolIssue)
@@ -398,11 +846,11 @@ impl HostTransport for HostBLETransport {
     }
 
     async fn get_widget(&mut self, id: WidgetId) -> TransResult<MemoriWidget> {
-        let command = HostBLECommand::GetWidget { widget_id: id };
+        let command = HostCommand::GetWidget { widget_id: id };
         let response = self.send_command(command).await?;
 
         match response {
-            DeviceBLEResponse::WidgetGet { result } => result,
+            DeviceResponse::GetWidget { result } => result,
             _ => {
                 eprintln!("[host_transport] Unexpected response type");
                 Err(TransError::ProtocolIssue)
@@ -411,9 +859,14 @@ impl HostTransport for HostBLETransport {
     }
 
     async fn get_battery_level(&mut self) -> TransResult<u8> {
-        let data = self
+        if *self.state_rx.borrow() != ConnectionState::Connected {
+            return Err(TransError::NotConnected);
+        }
+
+        let link = self.link.read().await;
+        let data = link
             .peripheral
-            .read(&self.battery_char)
+            .read(&link.battery_char)
             .await
             .map_err(|_| {
                 eprintln!("[ble-host] Failed to read battery characteristic");
@@ -426,12 +879,75 @@ impl HostTransport for HostBLETransport {
         }
     }
 
-    async fn set_device_config(&mut self, config: DeviceConfig) -> TransResult<()> {
-        let command = HostBLECommand::SetConfig { config };
+    async fn set_device_config(&mut self, config: DeviceConfig) -> TransResult<ConfigApplied> {
+        let command = HostCommand::SetConfig { config };
+        let response = self.send_command(command).await?;
+
+        match response {
+            DeviceResponse::SetConfig { result } => result,
+            _ => Err(TransError::ProtocolIssue),
+        }
+    }
+
+    async fn upsert_widget(&mut self, widget: MemoriWidget) -> TransResult<()> {
+        let command = HostCommand::UpsertWidget { widget };
+        let response = self.send_command(command).await?;
+
+        match response {
+            DeviceResponse::UpsertWidget { result } => result,
+            _ => Err(TransError::ProtocolIssue),
+        }
+    }
+
+    async fn remove_widget(&mut self, id: WidgetId) -> TransResult<()> {
+        let command = HostCommand::RemoveWidget { widget_id: id };
+        let response = self.send_command(command).await?;
+
+        match response {
+            DeviceResponse::RemoveWidget { result } => result,
+            _ => Err(TransError::ProtocolIssue),
+        }
+    }
+
+    async fn set_frames(&mut self, frames: Vec<MemoriLayout>) -> TransResult<()> {
+        let command = HostCommand::SetFrames { frames };
+        let response = self.send_command(command).await?;
+
+        match response {
+            DeviceResponse::SetFrames { result } => result,
+            _ => Err(TransError::ProtocolIssue),
+        }
+    }
+
+    async fn set_active_frame(&mut self, index: usize) -> TransResult<()> {
+        let command = HostCommand::SetActiveFrame { index };
+        let response = self.send_command(command).await?;
+
+        match response {
+            DeviceResponse::SetActiveFrame { result } => result,
+            _ => Err(TransError::ProtocolIssue),
+        }
+    }
+
+    async fn push_widget(&mut self, widget: MemoriWidget) -> TransResult<()> {
+        let command = HostCommand::PushWidget { widget };
+        let response = self.send_command(command).await?;
+
+        match response {
+            DeviceResponse::PushWidget { result } => result,
+            _ => Err(TransError::ProtocolIssue),
+        }
+    }
+
+    async fn sync_time(&mut self, utc_epoch_secs: i64, utc_offset_minutes: i16) -> TransResult<()> {
+        let command = HostCommand::SyncTime {
+            utc_epoch_secs,
+            utc_offset_minutes,
+        };
         let response = self.send_command(command).await?;
 
         match response {
-            DeviceBLEResponse::DeviceConfigSet { result } => result,
+            DeviceResponse::SyncTime { result } => result,
             _ => Err(TransError::ProtocolIssue),
         }
     }
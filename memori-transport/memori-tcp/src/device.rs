@@ -1,5 +1,6 @@
 use std::{collections::HashMap, sync::Arc};
 
+use memori_ui::input::InputEvent;
 use memori_ui::widgets::{MemoriWidget, WidgetId};
 use postcard::{from_bytes, to_allocvec};
 use tokio::{
@@ -16,42 +17,47 @@ use tokio::{
     task::JoinHandle,
 };
 use tracing::{debug, error};
-use transport::{TransError, TransResult};
+use transport::{
+    Capabilities, TransError, TransResult,
+    protocol::{
+        DeviceCommand, DeviceResponse, HostCommand, HostPacket, HostResponse, MessageID, Packet,
+        PacketPayload,
+    },
+};
 
 pub use transport::DeviceTransport;
 
-use crate::{
-    DeviceRequest, DeviceResponse, DeviceTcpTransport, HostRequest, HostResponse, Message,
-    MessageKind, Sequenced, TCP_ADDR, TcpTransportResult,
-};
+use crate::{DeviceTcpTransport, Sequenced, TCP_ADDR, TcpTransportResult, device_handshake};
 
-pub struct HostDisconnected {}
+pub struct HostDisconnected {
+    capabilities: Capabilities,
+}
 
-type Responses = Arc<Mutex<HashMap<u32, oneshot::Sender<HostResponse>>>>;
+type Responses = Arc<Mutex<HashMap<MessageID, oneshot::Sender<HostResponse>>>>;
 pub struct HostConnected {
+    remote_capabilities: Capabilities,
     device_response_task: JoinHandle<()>,
-    msg_sender: UnboundedSender<Message>,
+    msg_sender: UnboundedSender<Packet>,
     recv_task: JoinHandle<()>,
     responses: Responses,
     send_task: JoinHandle<()>,
-    seq_num: u32,
+    seq_num: MessageID,
 }
 
-impl Default for DeviceTcpTransport<HostDisconnected> {
-    fn default() -> Self {
-        DeviceTcpTransport::<HostDisconnected> {
-            state: HostDisconnected {},
+impl DeviceTcpTransport<HostDisconnected> {
+    /// Create a transport that advertises `capabilities` during the handshake.
+    pub fn with_capabilities(capabilities: Capabilities) -> Self {
+        DeviceTcpTransport {
+            state: HostDisconnected { capabilities },
         }
     }
-}
 
-impl DeviceTcpTransport<HostDisconnected> {
     pub async fn connect(
         self,
     ) -> TcpTransportResult<(
         DeviceTcpTransport<HostConnected>,
         (
-            UnboundedReceiver<Sequenced<HostRequest>>,
+            UnboundedReceiver<Sequenced<HostCommand>>,
             UnboundedSender<Sequenced<DeviceResponse>>,
         ),
     )> {
@@ -61,30 +67,36 @@ impl DeviceTcpTransport<HostDisconnected> {
             .inspect_err(|e| error!("{:#?}", e))?;
 
         // form tcp stream
-        let (stream, _) = listener
+        let (mut stream, _) = listener
             .accept()
             .await
             .inspect_err(|e| error!("{:#?}", e))?;
 
+        let remote_capabilities = device_handshake(&mut stream, &self.state.capabilities)
+            .await
+            .inspect_err(|e| error!("{e}"))?;
+        debug!("host capabilities: {remote_capabilities:?}");
+
         // split it up
         let (stream_rx, stream_tx) = stream.into_split();
 
         // channel for host requests
-        let (host_request_tx, host_request_rx) =
-            mpsc::unbounded_channel::<Sequenced<HostRequest>>();
+        let (host_command_tx, host_command_rx) =
+            mpsc::unbounded_channel::<Sequenced<HostCommand>>();
 
         // channel for sending device responses
         let (device_response_tx, device_response_rx) =
             mpsc::unbounded_channel::<Sequenced<DeviceResponse>>();
 
         // channel for sending messages
-        let (msg_sender_tx, msg_sender_rx): (UnboundedSender<Message>, UnboundedReceiver<Message>) =
-            mpsc::unbounded_channel::<Message>();
+        let (msg_sender_tx, msg_sender_rx): (UnboundedSender<Packet>, UnboundedReceiver<Packet>) =
+            mpsc::unbounded_channel::<Packet>();
 
         // data structure to store responses
-        let responses = Arc::new(Mutex::new(
-            HashMap::<u32, oneshot::Sender<HostResponse>>::new(),
-        ));
+        let responses = Arc::new(Mutex::new(HashMap::<
+            MessageID,
+            oneshot::Sender<HostResponse>,
+        >::new()));
 
         // task to take responses and send it back out the wire
         let device_response_task = tokio::spawn(Self::resp_handler(
@@ -95,7 +107,7 @@ impl DeviceTcpTransport<HostDisconnected> {
         // task to handle receiving messages from the other side of the wire
         let recv_task = tokio::spawn(Self::recv_handler(
             stream_rx,
-            host_request_tx,
+            host_command_tx,
             responses.clone(),
         ));
 
@@ -105,6 +117,7 @@ impl DeviceTcpTransport<HostDisconnected> {
         Ok((
             DeviceTcpTransport::<HostConnected> {
                 state: HostConnected {
+                    remote_capabilities,
                     msg_sender: msg_sender_tx,
                     responses,
                     send_task,
@@ -113,7 +126,7 @@ impl DeviceTcpTransport<HostDisconnected> {
                     device_response_task,
                 },
             },
-            (host_request_rx, device_response_tx),
+            (host_command_rx, device_response_tx),
         ))
     }
 
@@ -121,7 +134,7 @@ impl DeviceTcpTransport<HostDisconnected> {
     ///
     ///**Warning**: This function should be called from a `tokio::spawn` as it will loop forever.
     async fn resp_handler(
-        msg_sender_tx: UnboundedSender<Message>,
+        msg_sender_tx: UnboundedSender<Packet>,
         mut device_response_rx: UnboundedReceiver<Sequenced<DeviceResponse>>,
     ) {
         while let Some(resp) = device_response_rx.recv().await {
@@ -137,7 +150,7 @@ impl DeviceTcpTransport<HostDisconnected> {
     ///**Warning**: This function should be called from a `tokio::spawn` as it will loop forever.
     async fn recv_handler(
         mut stream_rx: OwnedReadHalf,
-        host_request_tx: UnboundedSender<Sequenced<HostRequest>>,
+        host_command_tx: UnboundedSender<Sequenced<HostCommand>>,
         responses: Responses,
     ) {
         loop {
@@ -166,24 +179,24 @@ impl DeviceTcpTransport<HostDisconnected> {
 
             // this should only ever receive a device tcp request
             // actually it could be a device_tcp_request or a host tcp response
-            let Ok(message): Result<Message, postcard::Error> =
+            let Ok(packet): Result<Packet, postcard::Error> =
                 from_bytes(&buf).inspect_err(|e| error!("Failed to deserialize bytes {e:#?}"))
             else {
                 continue;
             };
 
-            debug!("received message: {message:#?}");
+            debug!("received packet: {packet:#?}");
 
-            let seq_num = message.seq_num;
+            let seq_num = packet.id;
 
-            match message.kind {
-                MessageKind::HostRequest(req) => {
-                    host_request_tx
+            match packet.payload {
+                PacketPayload::HostPacket(HostPacket::Command(req)) => {
+                    host_command_tx
                         .send(Sequenced::new(seq_num, req))
                         .expect("this should not be closed");
                 }
 
-                MessageKind::HostResponse(resp) => {
+                PacketPayload::HostPacket(HostPacket::Response(resp)) => {
                     let mut responses = responses.lock().await;
                     let tx = responses.remove(&seq_num ).expect("Invariant broken, expecting to have a oneshot channel to sent to for this response");
                     tx.send(resp).expect("receiver should not be closed");
@@ -202,7 +215,7 @@ impl DeviceTcpTransport<HostDisconnected> {
     ///**Warning**: This function should be called from a `tokio::spawn` as it will loop forever.
     async fn trans_handler(
         mut stream_tx: OwnedWriteHalf,
-        mut msg_sender_rx: UnboundedReceiver<Message>,
+        mut msg_sender_rx: UnboundedReceiver<Packet>,
     ) {
         while let Some(msg) = msg_sender_rx.recv().await {
             let msg_bytes = to_allocvec(&msg)
@@ -226,70 +239,82 @@ impl DeviceTcpTransport<HostDisconnected> {
 }
 
 impl DeviceTcpTransport<HostConnected> {
-    /// Helper function to send requests to the other side of the wire,
-    /// namely exists to deal with sequence numbers for requests and responses.
-    async fn send_request(
-        &mut self,
-        msg: MessageKind,
-    ) -> TransResult<oneshot::Receiver<HostResponse>> {
+    /// Helper function to send commands to the other side of the wire and wait for the
+    /// response, namely exists to deal with sequence numbers for commands and responses.
+    async fn send_command(&mut self, cmd: DeviceCommand) -> TransResult<HostResponse> {
         self.state.seq_num = self.state.seq_num.saturating_add(2);
 
         let seq_num = self.state.seq_num;
 
-        let msg = Message { seq_num, kind: msg };
-
-        let mut responses = self.state.responses.lock().await;
-
         let (resp_tx, resp_rx) = oneshot::channel::<HostResponse>();
-        responses.insert(seq_num, resp_tx);
+        self.state.responses.lock().await.insert(seq_num, resp_tx);
 
-        self.state.msg_sender.send(msg).map_err(|e| {
-            error!("Failed to send into message sender! {e}");
-            TransError::InternalError
-        })?;
+        self.state
+            .msg_sender
+            .send(Sequenced::new(seq_num, cmd).into())
+            .map_err(|e| {
+                error!("Failed to send into message sender! {e}");
+                TransError::InternalError
+            })?;
 
-        Ok(resp_rx)
+        resp_rx
+            .await
+            .inspect_err(|e| error!("error receiving message: {e}"))
+            .map_err(|_| TransError::InternalError)
     }
 }
 
 impl DeviceTransport for DeviceTcpTransport<HostConnected> {
-    async fn refresh_data(&mut self, widget_id: WidgetId) -> transport::TransResult<MemoriWidget> {
-        let resp = self
-            .send_request(MessageKind::DeviceRequest(DeviceRequest::RefreshData(
-                widget_id,
-            )))
+    async fn refresh_data(&mut self, widget_id: WidgetId) -> TransResult<MemoriWidget> {
+        match self
+            .send_command(DeviceCommand::RefreshData { widget_id })
             .await?
-            .await
-            .inspect_err(|e| error!("error receiving message: {e}"))
-            .map_err(|_| TransError::InternalError)?;
-
-        if let HostResponse::UpdatedWidget(data) = resp {
-            Ok(*data)
-        } else {
-            panic!(
-                "Invariant failed! the same seq_num had a different response type than the request"
-            );
+        {
+            HostResponse::RefreshData { result } => result,
+            _ => Err(TransError::ProtocolIssue),
+        }
+    }
+
+    async fn ping(&mut self) -> TransResult<()> {
+        match self.send_command(DeviceCommand::Ping).await? {
+            HostResponse::Ping { result } => result,
+            _ => Err(TransError::ProtocolIssue),
         }
     }
 
-    async fn ping(&mut self) -> transport::TransResult<()> {
-        let resp = self
-            .send_request(MessageKind::DeviceRequest(DeviceRequest::Ping))
+    async fn todo_toggled(
+        &mut self,
+        widget_id: WidgetId,
+        item_id: u32,
+        done: bool,
+    ) -> TransResult<()> {
+        match self
+            .send_command(DeviceCommand::TodoToggled {
+                widget_id,
+                item_id,
+                done,
+            })
             .await?
-            .await
-            .inspect_err(|e| error!("error receiving message: {e}"))
-            .map_err(|_| TransError::InternalError)?;
-
-        if let HostResponse::Pong = resp {
-            Ok(())
-        } else {
-            panic!(
-                "Invariant failed! the same seq_num had a different response type than the request"
-            );
+        {
+            HostResponse::TodoToggled { result } => result,
+            _ => Err(TransError::ProtocolIssue),
+        }
+    }
+
+    async fn input(&mut self, event: InputEvent) -> TransResult<()> {
+        match self.send_command(DeviceCommand::Input { event }).await? {
+            HostResponse::Input { result } => result,
+            _ => Err(TransError::ProtocolIssue),
         }
     }
 }
+
 impl DeviceTcpTransport<HostConnected> {
+    /// What the other side told us it supports during the handshake.
+    pub fn remote_capabilities(&self) -> &Capabilities {
+        &self.state.remote_capabilities
+    }
+
     pub fn disconnect(self) {
         // aborting the tasks so they dont run in the backgrund when transport is dropped
         self.state.send_task.abort();
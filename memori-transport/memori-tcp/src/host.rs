@@ -2,6 +2,7 @@ use std::{collections::HashMap, sync::Arc};
 
 use memori_ui::{
     MemoriState,
+    layout::MemoriLayout,
     widgets::{MemoriWidget, WidgetId},
 };
 use tracing::{debug, error, info};
@@ -21,66 +22,77 @@ use tokio::{
 };
 
 use postcard::{from_bytes, to_allocvec};
-use transport::{HostTransport, TransError, TransResult};
-
-use crate::{
-    DeviceRequest, DeviceResponse, HostRequest, HostResponse, HostTcpTransport, Message,
-    MessageKind, Sequenced, TCP_ADDR, TcpTransportResult,
+use transport::{
+    Capabilities, ConfigApplied, DeviceConfig, HostTransport, TransError, TransResult,
+    protocol::{
+        DeviceCommand, DevicePacket, DeviceResponse, HostCommand, HostResponse, MessageID, Packet,
+        PacketPayload,
+    },
 };
 
+use crate::{HostTcpTransport, Sequenced, TCP_ADDR, TcpTransportResult, host_handshake};
+
 #[derive(Debug)]
-pub struct DeviceDisconnected {}
+pub struct DeviceDisconnected {
+    capabilities: Capabilities,
+}
 
-type Responses = Arc<Mutex<HashMap<u32, oneshot::Sender<DeviceResponse>>>>;
+type Responses = Arc<Mutex<HashMap<MessageID, oneshot::Sender<DeviceResponse>>>>;
 
 #[derive(Debug)]
 pub struct DeviceConnected {
+    remote_capabilities: Capabilities,
     host_response_task: JoinHandle<()>,
-    msg_sender: UnboundedSender<Message>,
+    msg_sender: UnboundedSender<Packet>,
     recv_task: JoinHandle<()>,
     responses: Responses,
     send_task: JoinHandle<()>,
-    seq_num: u32,
+    seq_num: MessageID,
 }
 
-impl Default for HostTcpTransport<DeviceDisconnected> {
-    fn default() -> Self {
-        HostTcpTransport::<DeviceDisconnected> {
-            state: DeviceDisconnected {},
+impl HostTcpTransport<DeviceDisconnected> {
+    /// Create a transport that advertises `capabilities` during the handshake.
+    pub fn with_capabilities(capabilities: Capabilities) -> Self {
+        HostTcpTransport {
+            state: DeviceDisconnected { capabilities },
         }
     }
-}
 
-impl HostTcpTransport<DeviceDisconnected> {
     pub async fn connect(
         &self,
     ) -> TcpTransportResult<(
         HostTcpTransport<DeviceConnected>,
         (
-            UnboundedReceiver<Sequenced<DeviceRequest>>,
+            UnboundedReceiver<Sequenced<DeviceCommand>>,
             UnboundedSender<Sequenced<HostResponse>>,
         ),
     )> {
-        let stream = TcpStream::connect(TCP_ADDR).await?;
+        let mut stream = TcpStream::connect(TCP_ADDR).await?;
+
+        let remote_capabilities = host_handshake(&mut stream, &self.state.capabilities)
+            .await
+            .inspect_err(|e| error!("{e}"))?;
+        debug!("device capabilities: {remote_capabilities:?}");
 
         // tcp stream
         let (stream_rx, stream_tx) = stream.into_split();
 
         // channel for device requests
-        let (device_request_tx, device_request_rx) =
-            mpsc::unbounded_channel::<Sequenced<DeviceRequest>>();
+        let (device_command_tx, device_command_rx) =
+            mpsc::unbounded_channel::<Sequenced<DeviceCommand>>();
 
         // channel for sending host responses
         let (host_response_tx, host_response_rx) =
             mpsc::unbounded_channel::<Sequenced<HostResponse>>();
 
         // channel for sending messages
-        let (msg_sender_tx, msg_sender_rx) = mpsc::unbounded_channel::<Message>();
+        let (msg_sender_tx, msg_sender_rx) = mpsc::unbounded_channel::<Packet>();
 
         // data structure to store responses
-        let responses = Arc::new(Mutex::new(
-            HashMap::<u32, oneshot::Sender<DeviceResponse>>::new(),
-        ));
+        let responses = Arc::new(Mutex::new(HashMap::<
+            MessageID,
+            oneshot::Sender<DeviceResponse>,
+        >::new()));
 
         // task to take responses and send it back out the wire
         let host_response_task =
@@ -89,7 +101,7 @@ impl HostTcpTransport<DeviceDisconnected> {
         // task to handle receiving messages from the other side of the wire
         let recv_task = tokio::spawn(Self::recv_handler(
             stream_rx,
-            device_request_tx,
+            device_command_tx,
             responses.clone(),
         ));
 
@@ -99,6 +111,7 @@ impl HostTcpTransport<DeviceDisconnected> {
         Ok((
             HostTcpTransport {
                 state: DeviceConnected {
+                    remote_capabilities,
                     msg_sender: msg_sender_tx,
                     responses,
                     send_task,
@@ -107,7 +120,7 @@ impl HostTcpTransport<DeviceDisconnected> {
                     seq_num: 0,
                 },
             },
-            (device_request_rx, host_response_tx),
+            (device_command_rx, host_response_tx),
         ))
     }
 
@@ -115,7 +128,7 @@ impl HostTcpTransport<DeviceDisconnected> {
     ///
     ///**Warning**: This function should be called from a `tokio::spawn` as it will loop forever.
     async fn resp_handler(
-        msg_sender_tx: UnboundedSender<Message>,
+        msg_sender_tx: UnboundedSender<Packet>,
         mut host_response_rx: UnboundedReceiver<Sequenced<HostResponse>>,
     ) {
         while let Some(resp) = host_response_rx.recv().await {
@@ -131,7 +144,7 @@ impl HostTcpTransport<DeviceDisconnected> {
     ///**Warning**: This function should be called from a `tokio::spawn` as it will loop forever.
     async fn recv_handler(
         mut stream_rx: OwnedReadHalf,
-        device_request_tx: UnboundedSender<Sequenced<DeviceRequest>>,
+        device_command_tx: UnboundedSender<Sequenced<DeviceCommand>>,
         responses: Responses,
     ) {
         loop {
@@ -160,24 +173,24 @@ impl HostTcpTransport<DeviceDisconnected> {
 
             // this should only ever receive a device tcp request
             // actually it could be a device_tcp_request or a host tcp response
-            let message: Message = from_bytes(&buf)
+            let packet: Packet = from_bytes(&buf)
                 .inspect_err(|e| error!("Failed to deserialize bytes {e:#?}"))
                 .unwrap();
 
-            debug!("received message: {message:#?}");
+            debug!("received packet: {packet:#?}");
 
-            let seq_num = message.seq_num;
+            let seq_num = packet.id;
 
-            match message.kind {
-                MessageKind::DeviceRequest(req) => {
-                    info!("received request: {req:?}");
+            match packet.payload {
+                PacketPayload::DevicePacket(DevicePacket::Command(cmd)) => {
+                    info!("received command: {cmd:?}");
 
-                    let _ = device_request_tx.send(Sequenced::new(seq_num, req));
+                    let _ = device_command_tx.send(Sequenced::new(seq_num, cmd));
                 }
-                MessageKind::DeviceResponse(resp) => {
+                PacketPayload::DevicePacket(DevicePacket::Response(resp)) => {
                     let mut responses = responses.lock().await;
                     let tx = responses.remove(&seq_num ).expect("Invariant broken, expecting to have a oneshot channel to sent to for this response");
-                    tx.send(*resp).expect("receiver should not be closed");
+                    tx.send(resp).expect("receiver should not be closed");
                 }
                 _ => {
                     error!("Received invalid message type");
@@ -192,7 +205,7 @@ impl HostTcpTransport<DeviceDisconnected> {
     ///**Warning**: This function should be called from a `tokio::spawn` as it will loop forever.
     async fn trans_handler(
         mut stream_tx: OwnedWriteHalf,
-        mut msg_sender_rx: UnboundedReceiver<Message>,
+        mut msg_sender_rx: UnboundedReceiver<Packet>,
     ) {
         while let Some(msg) = msg_sender_rx.recv().await {
             let msg_bytes = to_allocvec(&msg)
@@ -216,110 +229,122 @@ impl HostTcpTransport<DeviceDisconnected> {
 }
 
 impl HostTcpTransport<DeviceConnected> {
-    /// Helper function to send requests to the other side of the wire,
-    /// namely exists to deal with sequence numbers for requests and responses.
-    async fn send_request(
-        &mut self,
-        msg: MessageKind,
-    ) -> TransResult<oneshot::Receiver<DeviceResponse>> {
+    /// Helper function to send commands to the other side of the wire and wait for the
+    /// response, namely exists to deal with sequence numbers for commands and responses.
+    async fn send_command(&mut self, cmd: HostCommand) -> TransResult<DeviceResponse> {
         self.state.seq_num = self.state.seq_num.saturating_add(2);
 
         let seq_num = self.state.seq_num;
 
-        let msg = Message { seq_num, kind: msg };
-
-        let mut responses = self.state.responses.lock().await;
-
         let (resp_tx, resp_rx) = oneshot::channel::<DeviceResponse>();
-        responses.insert(seq_num, resp_tx);
+        self.state.responses.lock().await.insert(seq_num, resp_tx);
 
-        self.state.msg_sender.send(msg).map_err(|e| {
-            error!("Failed to send into message sender! {e}");
-            TransError::InternalError
-        })?;
+        self.state
+            .msg_sender
+            .send(Sequenced::new(seq_num, cmd).into())
+            .map_err(|e| {
+                error!("Failed to send into message sender! {e}");
+                TransError::InternalError
+            })?;
 
-        Ok(resp_rx)
+        resp_rx
+            .await
+            .inspect_err(|e| error!("error receiving message: {e}"))
+            .map_err(|_| TransError::InternalError)
     }
 }
 
 impl HostTransport for HostTcpTransport<DeviceConnected> {
-    async fn set_state(&mut self, state: MemoriState) -> transport::TransResult<()> {
-        let resp = self
-            .send_request(MessageKind::HostRequest(HostRequest::SetState(Box::new(
-                state,
-            ))))
-            .await?
-            .await
-            .inspect_err(|e| error!("error receiving message: {e}"))
-            .map_err(|_| TransError::InternalError)?;
-
-        if let DeviceResponse::Success = resp {
-            Ok(())
-        } else {
-            panic!(
-                "Invariant failed! the same seq_num had a different response type than the request"
-            );
+    async fn set_state(&mut self, state: MemoriState) -> TransResult<()> {
+        match self.send_command(HostCommand::SetState { state }).await? {
+            DeviceResponse::SetState { result } => result,
+            _ => Err(TransError::ProtocolIssue),
         }
     }
 
-    async fn get_widget(&mut self, id: WidgetId) -> transport::TransResult<MemoriWidget> {
-        let resp = self
-            .send_request(MessageKind::HostRequest(HostRequest::GetWidget(id)))
+    async fn get_widget(&mut self, id: WidgetId) -> TransResult<MemoriWidget> {
+        match self
+            .send_command(HostCommand::GetWidget { widget_id: id })
             .await?
-            .await
-            .inspect_err(|e| error!("error receiving message: {e}"))
-            .map_err(|_| TransError::InternalError)?;
-
-        if let DeviceResponse::Widget(data) = resp {
-            Ok(*data)
-        } else {
-            panic!(
-                "Invariant failed! the same seq_num had a different response type than the request"
-            );
+        {
+            DeviceResponse::GetWidget { result } => result,
+            _ => Err(TransError::ProtocolIssue),
+        }
+    }
+
+    async fn get_battery_level(&mut self) -> TransResult<u8> {
+        match self.send_command(HostCommand::GetBatteryLevel).await? {
+            DeviceResponse::BatteryLevel { result } => result,
+            _ => Err(TransError::ProtocolIssue),
         }
     }
 
-    async fn get_battery_level(&mut self) -> transport::TransResult<u8> {
-        let resp = self
-            .send_request(MessageKind::HostRequest(HostRequest::GetBatteryLevel))
+    async fn set_device_config(&mut self, config: DeviceConfig) -> TransResult<ConfigApplied> {
+        match self.send_command(HostCommand::SetConfig { config }).await? {
+            DeviceResponse::SetConfig { result } => result,
+            _ => Err(TransError::ProtocolIssue),
+        }
+    }
+
+    async fn upsert_widget(&mut self, widget: MemoriWidget) -> TransResult<()> {
+        match self.send_command(HostCommand::UpsertWidget { widget }).await? {
+            DeviceResponse::UpsertWidget { result } => result,
+            _ => Err(TransError::ProtocolIssue),
+        }
+    }
+
+    async fn remove_widget(&mut self, id: WidgetId) -> TransResult<()> {
+        match self
+            .send_command(HostCommand::RemoveWidget { widget_id: id })
             .await?
-            .await
-            .inspect_err(|e| error!("error receiving message: {e}"))
-            .map_err(|_| TransError::InternalError)?;
-
-        if let DeviceResponse::BatteryLevel(batt) = resp {
-            Ok(batt)
-        } else {
-            panic!(
-                "Invariant failed! the same seq_num had a different response type than the request"
-            );
+        {
+            DeviceResponse::RemoveWidget { result } => result,
+            _ => Err(TransError::ProtocolIssue),
+        }
+    }
+
+    async fn set_frames(&mut self, frames: Vec<MemoriLayout>) -> TransResult<()> {
+        match self.send_command(HostCommand::SetFrames { frames }).await? {
+            DeviceResponse::SetFrames { result } => result,
+            _ => Err(TransError::ProtocolIssue),
         }
     }
 
-    async fn set_device_config(
-        &mut self,
-        config: transport::DeviceConfig,
-    ) -> transport::TransResult<()> {
-        let resp = self
-            .send_request(MessageKind::HostRequest(HostRequest::SetDeviceConfig(
-                config,
-            )))
+    async fn set_active_frame(&mut self, index: usize) -> TransResult<()> {
+        match self
+            .send_command(HostCommand::SetActiveFrame { index })
             .await?
-            .await
-            .inspect_err(|e| error!("error receiving message: {e}"))
-            .map_err(|_| TransError::InternalError)?;
-
-        if let DeviceResponse::Success = resp {
-            Ok(())
-        } else {
-            panic!(
-                "Invariant failed! the same seq_num had a different response type than the request"
-            );
+        {
+            DeviceResponse::SetActiveFrame { result } => result,
+            _ => Err(TransError::ProtocolIssue),
+        }
+    }
+
+    async fn push_widget(&mut self, widget: MemoriWidget) -> TransResult<()> {
+        match self.send_command(HostCommand::PushWidget { widget }).await? {
+            DeviceResponse::PushWidget { result } => result,
+            _ => Err(TransError::ProtocolIssue),
+        }
+    }
+
+    async fn sync_time(&mut self, utc_epoch_secs: i64, utc_offset_minutes: i16) -> TransResult<()> {
+        let command = HostCommand::SyncTime {
+            utc_epoch_secs,
+            utc_offset_minutes,
+        };
+        match self.send_command(command).await? {
+            DeviceResponse::SyncTime { result } => result,
+            _ => Err(TransError::ProtocolIssue),
         }
     }
 }
 
 impl HostTcpTransport<DeviceConnected> {
+    /// What the other side told us it supports during the handshake.
+    pub fn remote_capabilities(&self) -> &Capabilities {
+        &self.state.remote_capabilities
+    }
+
     pub fn disconnect(self) {
         // aborting the tasks so they dont run in the backgrund when transport is dropped
         self.state.send_task.abort();
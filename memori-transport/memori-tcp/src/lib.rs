@@ -1,131 +1,158 @@
-use std::io;
+use std::{io, time::Duration};
 
-use memori_ui::{
-    MemoriState,
-    widgets::{MemoriWidget, WidgetId},
-};
-use serde::{Deserialize, Serialize};
+use postcard::{from_bytes, to_allocvec};
 use thiserror::Error;
-use transport::DeviceConfig;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    time::timeout,
+};
+use transport::{
+    Capabilities, TransError,
+    protocol::{
+        DeviceCommand, DevicePacket, DeviceResponse, HostCommand, HostPacket, HostResponse,
+        MessageID, Packet, PacketPayload,
+    },
+};
 
 pub mod device;
 pub mod host;
 
 const TCP_ADDR: &str = "127.0.0.1:6942";
 
+/// How long to wait for the other side to answer our hello.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
 #[derive(Debug, Error)]
 pub enum TcpTransportError {
     #[error("IO Error!")]
     IOError(#[from] io::Error),
+    #[error("Handshake failed: {0}")]
+    Handshake(#[from] TransError),
 }
 
 pub type TcpTransportResult<T> = Result<T, TcpTransportError>;
 
-/// Composition of a TCP Message
-#[derive(Serialize, Deserialize, Debug)]
-pub struct Message {
-    /// Sequence number for this message. A response's sequence number is always
-    /// equal to its requests' sequence number. Additionally requests sent from the
-    /// device always have odd sequence numbers, while events sent from the host
-    /// always have even sequence numbers
-    seq_num: u32,
-    kind: MessageKind,
-}
-
-/// Helper generic to pass a sequence number with any sub enum of the `MessageKind` enum.
+/// Helper generic to pass a sequence number with a command or response.
+///
+/// The sequence number ends up as the [`Packet`] id. A response's sequence number is
+/// always equal to its requests' sequence number. Additionally commands sent from the
+/// device always have odd sequence numbers, while commands sent from the host
+/// always have even sequence numbers.
 #[derive(Debug)]
 pub struct Sequenced<T> {
-    pub seq_num: u32,
-    /// will be an enum variant of message kind
+    pub seq_num: MessageID,
+    /// will be one of the command or response enums from [`transport::protocol`]
     pub msg_kind: T,
 }
 
 impl<T> Sequenced<T> {
     /// If creating a response from this, please use the same `seq_num` from the request.
-    pub fn new(seq_num: u32, msg_kind: T) -> Self {
+    pub fn new(seq_num: MessageID, msg_kind: T) -> Self {
         Self { seq_num, msg_kind }
     }
 }
 
-macro_rules! impl_sequenced_to_message {
-    // for types that need to be boxed
-    ($inner_type:ty, $variant: ident, boxed) => {
-        impl From<Sequenced<$inner_type>> for Message {
+macro_rules! impl_sequenced_to_packet {
+    ($inner_type:ty, $packet: ident, $variant: ident) => {
+        impl From<Sequenced<$inner_type>> for Packet {
             fn from(value: Sequenced<$inner_type>) -> Self {
-                Message {
-                    seq_num: value.seq_num,
-                    kind: MessageKind::$variant(Box::new(value.msg_kind)),
-                }
-            }
-        }
-    };
-    // for non-boxed types
-    ($inner_type:ty, $variant: ident) => {
-        impl From<Sequenced<$inner_type>> for Message {
-            fn from(value: Sequenced<$inner_type>) -> Self {
-                Message {
-                    seq_num: value.seq_num,
-                    kind: MessageKind::$variant(value.msg_kind),
+                Packet {
+                    payload: PacketPayload::$packet($packet::$variant(value.msg_kind)),
+                    id: value.seq_num,
                 }
             }
         }
     };
 }
 
-impl_sequenced_to_message!(DeviceRequest, DeviceRequest);
-impl_sequenced_to_message!(DeviceResponse, DeviceResponse, boxed);
-impl_sequenced_to_message!(HostRequest, HostRequest);
-impl_sequenced_to_message!(HostResponse, HostResponse);
-
-/// The different kinds of messages we support.
-#[derive(Serialize, Deserialize, Debug)]
-pub enum MessageKind {
-    DeviceRequest(DeviceRequest),
-    DeviceResponse(Box<DeviceResponse>),
-    HostRequest(HostRequest),
-    HostResponse(HostResponse),
+impl_sequenced_to_packet!(DeviceCommand, DevicePacket, Command);
+impl_sequenced_to_packet!(DeviceResponse, DevicePacket, Response);
+impl_sequenced_to_packet!(HostCommand, HostPacket, Command);
+impl_sequenced_to_packet!(HostResponse, HostPacket, Response);
+
+#[derive(Debug)]
+pub struct HostTcpTransport<State> {
+    state: State,
 }
 
-/// These are requests a device can send
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-pub enum DeviceRequest {
-    RefreshData(WidgetId),
-    Ping,
+#[derive(Debug)]
+pub struct DeviceTcpTransport<State> {
+    state: State,
 }
 
-/// These are responses a device can receive
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-pub enum HostResponse {
-    UpdatedWidget(Box<MemoriWidget>),
-    Pong,
+/// Writes a single length-prefixed packet to the stream.
+async fn write_packet(
+    stream: &mut (impl AsyncWriteExt + Unpin),
+    packet: &Packet,
+) -> TcpTransportResult<()> {
+    let msg_bytes = to_allocvec(packet).map_err(|_| TransError::SerializationFailure)?;
+    let header_bytes = (msg_bytes.len() as u32).to_be_bytes();
+
+    stream
+        .write_all(&[&header_bytes[..], &msg_bytes].concat())
+        .await?;
+    Ok(())
 }
 
-/// These are requests a host can send
-#[derive(Serialize, Deserialize, Debug)]
-pub enum HostRequest {
-    GetBatteryLevel,
-    Ping,
-    SetDeviceConfig(DeviceConfig),
-    //NOTE: this will change in the future
-    SetState(Box<MemoriState>),
-    GetWidget(WidgetId),
+/// Reads a single length-prefixed packet from the stream.
+async fn read_packet(stream: &mut (impl AsyncReadExt + Unpin)) -> TcpTransportResult<Packet> {
+    let mut msg_len_buf = [0; size_of::<u32>()];
+    stream.read_exact(&mut msg_len_buf).await?;
+
+    let mut buf = vec![0u8; u32::from_be_bytes(msg_len_buf) as usize];
+    stream.read_exact(&mut buf).await?;
+
+    Ok(from_bytes(&buf).map_err(|_| TransError::SerializationFailure)?)
 }
-/// These are responses a host can receive
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-pub enum DeviceResponse {
-    BatteryLevel(u8),
-    Widget(Box<MemoriWidget>),
-    Pong,
-    /// General success message for any updates sent by host
-    Success,
+
+/// Waits for the other side's hello, failing if it doesn't arrive in time.
+async fn read_hello(stream: &mut TcpStream) -> TcpTransportResult<Capabilities> {
+    let packet = timeout(HANDSHAKE_TIMEOUT, read_packet(stream))
+        .await
+        .map_err(|_| TransError::Timeout)??;
+
+    match packet.payload {
+        PacketPayload::Hello(caps) => Ok(caps),
+        _ => Err(TransError::ProtocolIssue.into()),
+    }
 }
 
-#[derive(Debug)]
-pub struct HostTcpTransport<State> {
-    state: State,
+/// Host side of the handshake, we say hello first and check the device's answer.
+async fn host_handshake(
+    stream: &mut TcpStream,
+    local: &Capabilities,
+) -> TcpTransportResult<Capabilities> {
+    write_packet(
+        stream,
+        &Packet {
+            payload: PacketPayload::Hello(local.clone()),
+            id: 0,
+        },
+    )
+    .await?;
+
+    let remote = read_hello(stream).await?;
+    local.check_compatible(&remote)?;
+    Ok(remote)
 }
 
-#[derive(Debug)]
-pub struct DeviceTcpTransport<State> {
-    state: State,
+/// Device side of the handshake, we always answer so the host knows why we hang up.
+async fn device_handshake(
+    stream: &mut TcpStream,
+    local: &Capabilities,
+) -> TcpTransportResult<Capabilities> {
+    let remote = read_hello(stream).await?;
+
+    write_packet(
+        stream,
+        &Packet {
+            payload: PacketPayload::Hello(local.clone()),
+            id: 0,
+        },
+    )
+    .await?;
+
+    local.check_compatible(&remote)?;
+    Ok(remote)
 }
@@ -1,11 +1,10 @@
-use memori_tcp::DeviceResponse;
 use memori_tcp::DeviceTcpTransport;
-use memori_tcp::HostRequest;
 use memori_tcp::HostTcpTransport;
 use memori_tcp::Sequenced;
 use std::time::Duration;
 use tokio::time::sleep;
-use transport::HostTransport;
+use transport::{Capabilities, HostTransport};
+use transport::protocol::{DeviceResponse, HostCommand};
 
 #[test]
 pub fn battery_transmission_test() {
@@ -20,18 +19,18 @@ pub fn battery_transmission_test() {
     std::thread::spawn(move || {
         let rt = tokio::runtime::Runtime::new().unwrap();
         rt.block_on(async move {
-            let device = DeviceTcpTransport::default();
+            let device = DeviceTcpTransport::with_capabilities(Capabilities::new("0.1.0", 296, 128));
 
             let (_, (mut host_req_rx, dev_resp_tx)) = device.connect().await.unwrap();
 
             tokio::spawn(async move {
                 while let Some(req) = host_req_rx.recv().await {
                     let resp = match req.msg_kind {
-                        HostRequest::GetBatteryLevel => {
-                            DeviceResponse::BatteryLevel(expected_battery)
-                        }
-                        HostRequest::Ping => DeviceResponse::Pong,
-                        HostRequest::SetState(_) => todo!(),
+                        HostCommand::GetBatteryLevel => DeviceResponse::BatteryLevel {
+                            result: Ok(expected_battery),
+                        },
+                        HostCommand::Ping => DeviceResponse::Ping { result: Ok(()) },
+                        HostCommand::SetState { .. } => todo!(),
                         _ => todo!(),
                     };
 
@@ -47,7 +46,7 @@ pub fn battery_transmission_test() {
     let host_thread = std::thread::spawn(|| {
         let rt = tokio::runtime::Runtime::new().unwrap();
         rt.block_on(async {
-            let host = HostTcpTransport::default();
+            let host = HostTcpTransport::with_capabilities(Capabilities::new("0.1.0", 296, 128));
             let (mut conn, (_, _)) = host.connect().await.unwrap();
 
             conn.get_battery_level()
@@ -0,0 +1,279 @@
+//! Chunking layer for sending packets bigger than a single BLE characteristic write.
+//!
+//! Every [`Packet`] is postcard-encoded and split into fragments of at most
+//! [`BLE_CHAR_SIZE`] bytes. Each fragment starts with a [`FragmentHeader`] so the
+//! receiving side can put the packet back together with a [`Reassembler`].
+
+use alloc::vec::Vec;
+use postcard::{from_bytes, to_allocvec};
+
+use crate::ble_types::BLE_CHAR_SIZE;
+use crate::protocol::Packet;
+use crate::{TransError, TransResult};
+
+/// Size of the header at the start of every fragment.
+pub const FRAGMENT_HEADER_SIZE: usize = 8;
+
+/// Amount of packet bytes that fit in a single fragment.
+pub const FRAGMENT_PAYLOAD_SIZE: usize = BLE_CHAR_SIZE - FRAGMENT_HEADER_SIZE;
+
+/// Largest encoded packet that gets sent or put back together, a whole
+/// [`crate::protocol::HostCommand::SetState`] with a few full screen images has
+/// to fit.
+pub const MAX_PACKET_SIZE: usize = 16 * 1024;
+
+/// Most fragments a packet can be split into, headers claiming more are refused
+/// before anything is allocated for them.
+pub const MAX_FRAGMENTS: usize = MAX_PACKET_SIZE.div_ceil(FRAGMENT_PAYLOAD_SIZE);
+
+/// How long a partially received packet is kept around before it gets dropped.
+pub const REASSEMBLY_TIMEOUT_MS: u64 = 5_000;
+
+/// Amount of packets that can be in the middle of being reassembled at once.
+const MAX_PARTIAL_PACKETS: usize = 4;
+
+/// Identifies all fragments belonging to one packet, unique per sender.
+pub type TransferId = u16;
+
+/// A single fragment, ready to be written to the characteristic.
+pub type Fragment = heapless::Vec<u8, BLE_CHAR_SIZE>;
+
+/// Header written in front of every fragment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FragmentHeader {
+    pub transfer_id: TransferId,
+    /// Position of this fragment in the packet, starting at 0.
+    pub index: u16,
+    /// Total amount of fragments in the packet.
+    pub count: u16,
+    /// Amount of payload bytes following the header, anything after that is padding.
+    pub len: u16,
+}
+
+impl FragmentHeader {
+    pub fn to_bytes(&self) -> [u8; FRAGMENT_HEADER_SIZE] {
+        let mut bytes = [0u8; FRAGMENT_HEADER_SIZE];
+        bytes[0..2].copy_from_slice(&self.transfer_id.to_le_bytes());
+        bytes[2..4].copy_from_slice(&self.index.to_le_bytes());
+        bytes[4..6].copy_from_slice(&self.count.to_le_bytes());
+        bytes[6..8].copy_from_slice(&self.len.to_le_bytes());
+        bytes
+    }
+
+    /// Parses a header from the start of `bytes`, returns `None` if there are not enough bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes = bytes.get(..FRAGMENT_HEADER_SIZE)?;
+        let read = |i: usize| u16::from_le_bytes([bytes[i], bytes[i + 1]]);
+
+        Some(Self {
+            transfer_id: read(0),
+            index: read(2),
+            count: read(4),
+            len: read(6),
+        })
+    }
+}
+
+/// Encodes `packet` and splits it up into fragments that each fit in [`BLE_CHAR_SIZE`].
+///
+/// # Errors
+/// Returns [`TransError::InvalidMessage`] when the packet is bigger than
+/// [`MAX_PACKET_SIZE`].
+pub fn fragment_packet(packet: &Packet, transfer_id: TransferId) -> TransResult<Fragments> {
+    let encoded = to_allocvec(packet).map_err(|_| TransError::SerializationFailure)?;
+    if encoded.len() > MAX_PACKET_SIZE {
+        return Err(TransError::InvalidMessage);
+    }
+
+    let count = encoded.len().div_ceil(FRAGMENT_PAYLOAD_SIZE).max(1);
+    let count = u16::try_from(count).map_err(|_| TransError::InvalidMessage)?;
+
+    Ok(Fragments {
+        encoded,
+        transfer_id,
+        count,
+        next_index: 0,
+    })
+}
+
+/// Iterator over the fragments of a single encoded packet.
+#[derive(Debug)]
+pub struct Fragments {
+    encoded: Vec<u8>,
+    transfer_id: TransferId,
+    count: u16,
+    next_index: u16,
+}
+
+impl Iterator for Fragments {
+    type Item = Fragment;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_index >= self.count {
+            return None;
+        }
+
+        let start = self.next_index as usize * FRAGMENT_PAYLOAD_SIZE;
+        let end = (start + FRAGMENT_PAYLOAD_SIZE).min(self.encoded.len());
+        let payload = &self.encoded[start..end];
+
+        let header = FragmentHeader {
+            transfer_id: self.transfer_id,
+            index: self.next_index,
+            count: self.count,
+            len: payload.len() as u16,
+        };
+        self.next_index += 1;
+
+        let mut fragment = Fragment::new();
+        // Both fit by construction, header + payload is at most BLE_CHAR_SIZE.
+        let _ = fragment.extend_from_slice(&header.to_bytes());
+        let _ = fragment.extend_from_slice(payload);
+
+        Some(fragment)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.count - self.next_index) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Fragments {}
+
+/// A packet that has received some, but not all, of its fragments.
+#[derive(Debug)]
+struct PartialPacket {
+    transfer_id: TransferId,
+    count: u16,
+    next_index: u16,
+    started_ms: u64,
+    data: Vec<u8>,
+}
+
+/// Puts fragments produced by [`fragment_packet`] back together.
+///
+/// Fragments of a packet have to arrive in order, but fragments of different
+/// packets may be interleaved. Partial packets that don't complete within
+/// [`REASSEMBLY_TIMEOUT_MS`] are dropped.
+#[derive(Debug, Default)]
+pub struct Reassembler {
+    partials: heapless::Vec<PartialPacket, MAX_PARTIAL_PACKETS>,
+}
+
+impl Reassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a received fragment into the reassembler.
+    ///
+    /// `now_ms` is any monotonic millisecond timestamp, it is only used to expire
+    /// partial packets. Returns the decoded packet once its last fragment arrived.
+    ///
+    /// # Errors
+    /// Returns [`TransError::InvalidMessage`] for malformed fragments, or ones
+    /// claiming more than [`MAX_FRAGMENTS`], and
+    /// [`TransError::ProtocolIssue`] when fragments of a packet went missing.
+    pub fn push(&mut self, fragment: &[u8], now_ms: u64) -> TransResult<Option<Packet>> {
+        self.expire(now_ms);
+
+        let header = FragmentHeader::from_bytes(fragment).ok_or(TransError::InvalidMessage)?;
+        let payload = fragment
+            .get(FRAGMENT_HEADER_SIZE..FRAGMENT_HEADER_SIZE + header.len as usize)
+            .ok_or(TransError::InvalidMessage)?;
+
+        if header.count == 0
+            || header.index >= header.count
+            || header.count as usize > MAX_FRAGMENTS
+        {
+            return Err(TransError::InvalidMessage);
+        }
+
+        // Fast path, the whole packet fits in this fragment.
+        if header.count == 1 {
+            return decode(payload).map(Some);
+        }
+
+        let existing = self
+            .partials
+            .iter()
+            .position(|p| p.transfer_id == header.transfer_id);
+
+        let pos = match existing {
+            // A new first fragment replaces whatever was left over from a previous packet.
+            Some(pos) if header.index == 0 => {
+                self.partials[pos] = PartialPacket::new(&header, now_ms);
+                pos
+            }
+            Some(pos) => pos,
+            None if header.index == 0 => {
+                if self.partials.is_full() {
+                    self.drop_oldest();
+                }
+                // Can't fail, we just made room.
+                let _ = self.partials.push(PartialPacket::new(&header, now_ms));
+                self.partials.len() - 1
+            }
+            // We never saw the start of this packet.
+            None => return Err(TransError::ProtocolIssue),
+        };
+
+        let partial = &mut self.partials[pos];
+        if header.index != partial.next_index || header.count != partial.count {
+            self.partials.swap_remove(pos);
+            return Err(TransError::ProtocolIssue);
+        }
+
+        partial.data.extend_from_slice(payload);
+        partial.next_index += 1;
+
+        if partial.next_index < partial.count {
+            return Ok(None);
+        }
+
+        let partial = self.partials.swap_remove(pos);
+        decode(&partial.data).map(Some)
+    }
+
+    /// Drops any partial packets that have been waiting for longer than [`REASSEMBLY_TIMEOUT_MS`].
+    pub fn expire(&mut self, now_ms: u64) {
+        self.partials
+            .retain(|p| now_ms.saturating_sub(p.started_ms) < REASSEMBLY_TIMEOUT_MS);
+    }
+
+    /// Amount of packets that are currently partially received.
+    pub fn pending(&self) -> usize {
+        self.partials.len()
+    }
+
+    fn drop_oldest(&mut self) {
+        if let Some(oldest) = self
+            .partials
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, p)| p.started_ms)
+            .map(|(i, _)| i)
+        {
+            self.partials.swap_remove(oldest);
+        }
+    }
+}
+
+impl PartialPacket {
+    fn new(header: &FragmentHeader, now_ms: u64) -> Self {
+        Self {
+            transfer_id: header.transfer_id,
+            count: header.count,
+            next_index: 0,
+            started_ms: now_ms,
+            // Grows as fragments arrive, the header's count can't be trusted
+            // with an allocation up front.
+            data: Vec::new(),
+        }
+    }
+}
+
+fn decode(bytes: &[u8]) -> TransResult<Packet> {
+    from_bytes(bytes).map_err(|_| TransError::SerializationFailure)
+}
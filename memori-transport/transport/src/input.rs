@@ -0,0 +1,43 @@
+//! The part of handling a button press that needs the host, shared by the
+//! device and the simulator so they behave the same.
+
+use alloc::vec::Vec;
+use memori_ui::input::{InputEffect, InputEvent};
+use memori_ui::widgets::MemoriWidget;
+
+use crate::{DeviceTransport, TransResult};
+
+/// Tells the host about `event` and does what's left of its `effect`, see
+/// [`MemoriState::handle_input`]. Returns the widgets the host sent fresh data
+/// for, to put in the state.
+///
+/// The host mirrors the press on its copy of the state, so it hears about the
+/// press before it hears about a to-do item it checked off. Errors only come
+/// from the effect: the state already changed on our side, and a check-off the
+/// host never hears about would leave the two copies disagreeing for good.
+///
+/// [`MemoriState::handle_input`]: memori_ui::MemoriState::handle_input
+pub async fn forward_input<T: DeviceTransport>(
+    transport: &mut T,
+    event: InputEvent,
+    effect: &InputEffect,
+) -> TransResult<Vec<MemoriWidget>> {
+    // Missing a press only leaves the host on the wrong frame until the next one.
+    let _ = transport.input(event).await;
+
+    let mut refreshed = Vec::new();
+    match effect {
+        InputEffect::None | InputEffect::Redraw => {}
+        InputEffect::TodoToggled {
+            widget_id,
+            item_id,
+            done,
+        } => transport.todo_toggled(*widget_id, *item_id, *done).await?,
+        InputEffect::Refresh(widget_ids) => {
+            for &widget_id in widget_ids {
+                refreshed.push(transport.refresh_data(widget_id).await?);
+            }
+        }
+    }
+    Ok(refreshed)
+}
@@ -1,10 +1,21 @@
 #![no_std]
 
+extern crate alloc;
+
 pub mod ble_types;
+pub mod fragment;
+pub mod input;
+pub mod protocol;
 
-use memori_ui::MemoriState;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use memori_ui::input::InputEvent;
+use memori_ui::layout::MemoriLayout;
 use memori_ui::widgets::MemoriWidget;
 use memori_ui::widgets::WidgetId;
+use memori_ui::widgets::WidgetKindTag;
+use memori_ui::{MemoriState, StateError};
 use serde::Deserialize;
 use serde::Serialize;
 
@@ -19,11 +30,20 @@ pub enum TransError {
     InternalError,
     NoAck,
     WidgetNotFound,
+    /// The widget can't be removed while a frame still shows it.
+    WidgetInUse,
     SerializationFailure,
     NotConnected,
     Timeout,
     InvalidMessage,
     ProtocolIssue,
+    /// The other side speaks a different version of the protocol.
+    IncompatibleProtocol {
+        local: u16,
+        remote: u16,
+    },
+    /// The state doesn't hold together, see [`MemoriState::validate`].
+    InvalidState(Box<StateError>),
 }
 
 impl Display for TransError {
@@ -35,6 +55,7 @@ impl Display for TransError {
                 "No Ack, also know we might send these errors for no reason"
             ),
             TransError::WidgetNotFound => write!(f, "Widget not found! possible invalid WidgetID!"),
+            TransError::WidgetInUse => write!(f, "Widget is still shown in one of the frames!"),
             TransError::SerializationFailure => {
                 write!(f, "Failed to draw widget")
             }
@@ -42,6 +63,11 @@ impl Display for TransError {
             TransError::Timeout => write!(f, "Timeout reached on transport!"),
             TransError::InvalidMessage => write!(f, "Invalid message sent through transport!"),
             TransError::ProtocolIssue => write!(f, "Something wrong happened with the protocol!"),
+            TransError::IncompatibleProtocol { local, remote } => write!(
+                f,
+                "Incompatible protocol version! we speak v{local}, the other side speaks v{remote}"
+            ),
+            TransError::InvalidState(e) => write!(f, "Refused state, {e}!"),
         }
     }
 }
@@ -51,10 +77,181 @@ impl Error for TransError {}
 /// Result type for transport errors.
 pub type TransResult<T> = Result<T, TransError>;
 
+/// Version of the wire protocol, bump this whenever a message changes shape.
+pub const PROTOCOL_VERSION: u16 = 14;
+
+/// What one side of a connection supports, exchanged right after connecting.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Capabilities {
+    pub protocol_version: u16,
+    /// Version of the firmware, or of the app when sent by the host.
+    pub firmware_version: String,
+    pub display_width: u16,
+    pub display_height: u16,
+    /// Widgets that can be rendered (device) or provided (host).
+    pub widget_kinds: Vec<WidgetKindTag>,
+}
+
+impl Capabilities {
+    /// Capabilities for the current protocol version, supporting every known widget kind.
+    pub fn new(
+        firmware_version: impl Into<String>,
+        display_width: u16,
+        display_height: u16,
+    ) -> Self {
+        Self {
+            protocol_version: PROTOCOL_VERSION,
+            firmware_version: firmware_version.into(),
+            display_width,
+            display_height,
+            widget_kinds: WidgetKindTag::ALL.to_vec(),
+        }
+    }
+
+    /// Checks whether we can talk to a peer that sent `remote`.
+    pub fn check_compatible(&self, remote: &Capabilities) -> TransResult<()> {
+        if self.protocol_version != remote.protocol_version {
+            return Err(TransError::IncompatibleProtocol {
+                local: self.protocol_version,
+                remote: remote.protocol_version,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Which way up the display is.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum DisplayRotation {
+    #[default]
+    Normal,
+    /// Turned 180 degrees, for when the device is mounted the other way around.
+    UpsideDown,
+}
+
+/// How the e-paper display gets refreshed when something changes.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum RefreshPolicy {
+    /// Always do a full refresh, it flashes but never leaves ghosting behind.
+    #[default]
+    Full,
+    /// Fast refreshes, with a full one every `full_every` updates to clean up
+    /// the ghosting. `0` never does a full refresh.
+    Fast { full_every: u8 },
+}
+
+/// When a [`DeviceConfig`] the device accepted takes effect.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ConfigApplied {
+    /// Everything right away.
+    Now,
+    /// The name changed, it only shows up once the device restarts. The rest
+    /// took effect right away.
+    AfterRestart,
+}
+
 /// Device configuration options
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct DeviceConfig {
     dark_mode: bool,
+    rotation: DisplayRotation,
+    utc_offset_minutes: i16,
+    frame_rotation: bool,
+    refresh_policy: RefreshPolicy,
+    name: String,
+    always_connected: bool,
+}
+
+impl Default for DeviceConfig {
+    fn default() -> Self {
+        Self {
+            dark_mode: false,
+            rotation: DisplayRotation::default(),
+            utc_offset_minutes: 0,
+            frame_rotation: true,
+            refresh_policy: RefreshPolicy::default(),
+            name: String::from("Memori"),
+            always_connected: false,
+        }
+    }
+}
+
+impl DeviceConfig {
+    /// The default configuration, same as [`DeviceConfig::default`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Draw white on black instead of black on white.
+    pub fn with_dark_mode(mut self, dark_mode: bool) -> Self {
+        self.dark_mode = dark_mode;
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: DisplayRotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Offset of the user's timezone from UTC, in minutes.
+    pub fn with_utc_offset_minutes(mut self, utc_offset_minutes: i16) -> Self {
+        self.utc_offset_minutes = utc_offset_minutes;
+        self
+    }
+
+    /// Whether the device cycles through its frames on its own.
+    pub fn with_frame_rotation(mut self, frame_rotation: bool) -> Self {
+        self.frame_rotation = frame_rotation;
+        self
+    }
+
+    pub fn with_refresh_policy(mut self, refresh_policy: RefreshPolicy) -> Self {
+        self.refresh_policy = refresh_policy;
+        self
+    }
+
+    /// Name the device shows up as once connected.
+    ///
+    /// The device only picks up a new name when it restarts, setting the
+    /// config tells with [`ConfigApplied::AfterRestart`].
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Never sleep and keep the radio on, for development.
+    pub fn with_always_connected(mut self, always_connected: bool) -> Self {
+        self.always_connected = always_connected;
+        self
+    }
+
+    pub fn dark_mode(&self) -> bool {
+        self.dark_mode
+    }
+
+    pub fn rotation(&self) -> DisplayRotation {
+        self.rotation
+    }
+
+    pub fn utc_offset_minutes(&self) -> i16 {
+        self.utc_offset_minutes
+    }
+
+    pub fn frame_rotation(&self) -> bool {
+        self.frame_rotation
+    }
+
+    pub fn refresh_policy(&self) -> RefreshPolicy {
+        self.refresh_policy
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn always_connected(&self) -> bool {
+        self.always_connected
+    }
 }
 
 pub trait HostTransport {
@@ -75,6 +272,39 @@ pub trait HostTransport {
     fn set_device_config(
         &mut self,
         config: DeviceConfig,
+    ) -> impl Future<Output = TransResult<ConfigApplied>> + Send;
+
+    /// Add a widget to the device, or replace the one with the same id.
+    /// Update timers of the widget keep running if its update frequencies didn't change.
+    fn upsert_widget(
+        &mut self,
+        widget: MemoriWidget,
+    ) -> impl Future<Output = TransResult<()>> + Send;
+
+    /// Remove a widget from the device, it can't be shown by any frame.
+    fn remove_widget(&mut self, id: WidgetId) -> impl Future<Output = TransResult<()>> + Send;
+
+    /// Replace the frames the device cycles through, leaving the widgets alone.
+    fn set_frames(
+        &mut self,
+        frames: Vec<MemoriLayout>,
+    ) -> impl Future<Output = TransResult<()>> + Send;
+
+    /// Switch the device to the frame at `index`.
+    fn set_active_frame(&mut self, index: usize) -> impl Future<Output = TransResult<()>> + Send;
+
+    /// Push freshly fetched data for a widget that is already on the device.
+    /// Unlike [`HostTransport::upsert_widget`] only the data is replaced, and the device
+    /// stops asking for data for this widget while the host keeps pushing it.
+    fn push_widget(&mut self, widget: MemoriWidget)
+    -> impl Future<Output = TransResult<()>> + Send;
+
+    /// Tell the device the time, `utc_epoch_secs` seconds since the unix epoch
+    /// in UTC, with local time `utc_offset_minutes` ahead of that.
+    fn sync_time(
+        &mut self,
+        utc_epoch_secs: i64,
+        utc_offset_minutes: i16,
     ) -> impl Future<Output = TransResult<()>> + Send;
 }
 
@@ -87,4 +317,15 @@ pub trait DeviceTransport {
 
     /// Ping the host to ensure they are still connected.
     fn ping(&mut self) -> impl Future<Output = TransResult<()>>;
+
+    /// Tell the host the to-do item `item_id` on `widget_id` is now `done`, or not.
+    fn todo_toggled(
+        &mut self,
+        widget_id: WidgetId,
+        item_id: u32,
+        done: bool,
+    ) -> impl Future<Output = TransResult<()>>;
+
+    /// Tell the host a button was pressed, see [`input::forward_input`].
+    fn input(&mut self, event: InputEvent) -> impl Future<Output = TransResult<()>>;
 }
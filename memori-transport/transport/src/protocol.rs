@@ -0,0 +1,123 @@
+//! Messages exchanged between the host and the device.
+//!
+//! Every transport (BLE, TCP) carries the exact same [`Packet`]s, only the way
+//! they are put on the wire differs.
+
+use crate::{Capabilities, ConfigApplied, DeviceConfig, TransError, TransResult};
+use alloc::vec::Vec;
+use memori_ui::{
+    MemoriState,
+    input::InputEvent,
+    layout::MemoriLayout,
+    widgets::{MemoriWidget, WidgetId},
+};
+use serde::{Deserialize, Serialize};
+
+/// Identifies a command, the response to it carries the same id.
+pub type MessageID = u32;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Packet {
+    pub payload: PacketPayload,
+    pub id: MessageID,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum PacketPayload {
+    /// Sent by the host right after connecting, the device answers with its own.
+    /// Until it got a compatible one, the device answers every command with
+    /// [`TransError::IncompatibleProtocol`].
+    Hello(Capabilities),
+    DevicePacket(DevicePacket),
+    HostPacket(HostPacket),
+}
+
+/// Packets sent by the device.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum DevicePacket {
+    Command(DeviceCommand),
+    Response(DeviceResponse),
+}
+
+/// Packets sent by the host.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum HostPacket {
+    Command(HostCommand),
+    Response(HostResponse),
+}
+
+/// Commands the device can send to the host.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum DeviceCommand {
+    RefreshData { widget_id: WidgetId },
+    Ping,
+    /// An item on a to-do widget was checked off, or unchecked, on the device.
+    TodoToggled {
+        widget_id: WidgetId,
+        item_id: u32,
+        done: bool,
+    },
+    /// A button was pressed on the device, after the device did what it does.
+    Input { event: InputEvent },
+}
+
+/// Responses the host sends back for a [`DeviceCommand`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum HostResponse {
+    RefreshData { result: TransResult<MemoriWidget> },
+    Ping { result: TransResult<()> },
+    TodoToggled { result: TransResult<()> },
+    Input { result: TransResult<()> },
+}
+
+/// Commands the host can send to the device.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum HostCommand {
+    SetState { state: MemoriState },
+    GetWidget { widget_id: WidgetId },
+    SetConfig { config: DeviceConfig },
+    GetBatteryLevel,
+    Ping,
+    UpsertWidget { widget: MemoriWidget },
+    RemoveWidget { widget_id: WidgetId },
+    SetFrames { frames: Vec<MemoriLayout> },
+    SetActiveFrame { index: usize },
+    PushWidget { widget: MemoriWidget },
+    /// The current time, the device keeps counting from it.
+    SyncTime { utc_epoch_secs: i64, utc_offset_minutes: i16 },
+}
+
+/// Responses the device sends back for a [`HostCommand`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum DeviceResponse {
+    SetState { result: TransResult<()> },
+    GetWidget { result: TransResult<MemoriWidget> },
+    SetConfig { result: TransResult<ConfigApplied> },
+    BatteryLevel { result: TransResult<u8> },
+    Ping { result: TransResult<()> },
+    UpsertWidget { result: TransResult<()> },
+    RemoveWidget { result: TransResult<()> },
+    SetFrames { result: TransResult<()> },
+    SetActiveFrame { result: TransResult<()> },
+    PushWidget { result: TransResult<()> },
+    SyncTime { result: TransResult<()> },
+}
+
+impl DeviceResponse {
+    /// The response to `cmd` for when it couldn't be carried out at all.
+    pub fn error_for(cmd: &HostCommand, error: TransError) -> Self {
+        match cmd {
+            HostCommand::SetState { .. } => Self::SetState { result: Err(error) },
+            HostCommand::GetWidget { .. } => Self::GetWidget { result: Err(error) },
+            HostCommand::SetConfig { .. } => Self::SetConfig { result: Err(error) },
+            HostCommand::GetBatteryLevel => Self::BatteryLevel { result: Err(error) },
+            HostCommand::Ping => Self::Ping { result: Err(error) },
+            HostCommand::UpsertWidget { .. } => Self::UpsertWidget { result: Err(error) },
+            HostCommand::RemoveWidget { .. } => Self::RemoveWidget { result: Err(error) },
+            HostCommand::SetFrames { .. } => Self::SetFrames { result: Err(error) },
+            HostCommand::SetActiveFrame { .. } => Self::SetActiveFrame { result: Err(error) },
+            HostCommand::PushWidget { .. } => Self::PushWidget { result: Err(error) },
+            HostCommand::SyncTime { .. } => Self::SyncTime { result: Err(error) },
+        }
+    }
+}
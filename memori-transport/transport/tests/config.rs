@@ -0,0 +1,28 @@
+use transport::{DeviceConfig, DisplayRotation, RefreshPolicy};
+
+#[test]
+fn config_survives_serialization() {
+    let config = DeviceConfig::new()
+        .with_dark_mode(true)
+        .with_rotation(DisplayRotation::UpsideDown)
+        .with_utc_offset_minutes(-480)
+        .with_frame_rotation(false)
+        .with_refresh_policy(RefreshPolicy::Fast { full_every: 10 })
+        .with_name("desk")
+        .with_always_connected(true);
+
+    let bytes = postcard::to_allocvec(&config).unwrap();
+    let decoded: DeviceConfig = postcard::from_bytes(&bytes).unwrap();
+
+    assert_eq!(decoded, config);
+    assert!(decoded.dark_mode());
+    assert_eq!(decoded.rotation(), DisplayRotation::UpsideDown);
+    assert_eq!(decoded.utc_offset_minutes(), -480);
+    assert!(!decoded.frame_rotation());
+    assert_eq!(
+        decoded.refresh_policy(),
+        RefreshPolicy::Fast { full_every: 10 }
+    );
+    assert_eq!(decoded.name(), "desk");
+    assert!(decoded.always_connected());
+}
@@ -0,0 +1,127 @@
+use memori_ui::{
+    MemoriState,
+    layout::MemoriLayout,
+    widgets::{Github, MemoriWidget, WidgetId, WidgetKind},
+};
+use transport::TransError;
+use transport::ble_types::BLE_CHAR_SIZE;
+use transport::fragment::{
+    FragmentHeader, MAX_FRAGMENTS, REASSEMBLY_TIMEOUT_MS, Reassembler, fragment_packet,
+};
+use transport::protocol::{HostCommand, HostPacket, Packet, PacketPayload};
+
+fn big_state_packet() -> Packet {
+    let widgets = (0..8).map(|i| {
+        MemoriWidget::with_never_update_frequency(
+            i,
+            WidgetKind::Github(Github::new(
+                "some-long-github-username".to_owned(),
+                Some("cse115a-Memori/Memori".to_owned()),
+            )),
+        )
+    });
+
+    let frames = (0..8).map(|i| MemoriLayout::Full(WidgetId(i))).collect();
+
+    Packet {
+        payload: PacketPayload::HostPacket(HostPacket::Command(HostCommand::SetState {
+            state: MemoriState::new(0, widgets, frames, 5),
+        })),
+        id: 7,
+    }
+}
+
+fn small_packet() -> Packet {
+    Packet {
+        payload: PacketPayload::HostPacket(HostPacket::Command(HostCommand::GetWidget {
+            widget_id: WidgetId(3),
+        })),
+        id: 1,
+    }
+}
+
+#[test]
+fn large_packet_round_trip() {
+    let fragments: Vec<_> = fragment_packet(&big_state_packet(), 0).unwrap().collect();
+    assert!(fragments.len() > 1);
+    assert!(fragments.iter().all(|f| f.len() <= BLE_CHAR_SIZE));
+
+    let mut reassembler = Reassembler::new();
+    let (last, rest) = fragments.split_last().unwrap();
+    for fragment in rest {
+        assert!(reassembler.push(fragment, 0).unwrap().is_none());
+    }
+
+    let packet = reassembler
+        .push(last, 0)
+        .unwrap()
+        .expect("should be complete");
+    assert_eq!(packet.id, 7);
+    assert_eq!(reassembler.pending(), 0);
+}
+
+#[test]
+fn interleaved_packets_and_padding() {
+    let big: Vec<_> = fragment_packet(&big_state_packet(), 1).unwrap().collect();
+    let small: Vec<_> = fragment_packet(&small_packet(), 2).unwrap().collect();
+    assert_eq!(small.len(), 1);
+
+    let mut reassembler = Reassembler::new();
+    assert!(reassembler.push(&big[0], 0).unwrap().is_none());
+
+    // the device pads notifications to the full characteristic size
+    let mut padded = [0u8; BLE_CHAR_SIZE];
+    padded[..small[0].len()].copy_from_slice(&small[0]);
+    let packet = reassembler.push(&padded, 0).unwrap().unwrap();
+    assert_eq!(packet.id, 1);
+
+    let mut result = None;
+    for fragment in &big[1..] {
+        result = reassembler.push(fragment, 0).unwrap();
+    }
+    assert_eq!(result.unwrap().id, 7);
+}
+
+#[test]
+fn missing_fragment_is_rejected() {
+    let fragments: Vec<_> = fragment_packet(&big_state_packet(), 3).unwrap().collect();
+
+    let mut reassembler = Reassembler::new();
+    reassembler.push(&fragments[0], 0).unwrap();
+    assert_eq!(
+        reassembler.push(&fragments[2], 0).unwrap_err(),
+        TransError::ProtocolIssue
+    );
+    assert_eq!(reassembler.pending(), 0);
+}
+
+#[test]
+fn partial_packets_time_out() {
+    let fragments: Vec<_> = fragment_packet(&big_state_packet(), 4).unwrap().collect();
+
+    let mut reassembler = Reassembler::new();
+    reassembler.push(&fragments[0], 0).unwrap();
+    assert_eq!(reassembler.pending(), 1);
+
+    reassembler.expire(REASSEMBLY_TIMEOUT_MS);
+    assert_eq!(reassembler.pending(), 0);
+}
+
+#[test]
+fn oversized_header_is_rejected() {
+    let header = FragmentHeader {
+        transfer_id: 5,
+        index: 0,
+        count: MAX_FRAGMENTS as u16 + 1,
+        len: 4,
+    };
+    let mut fragment = header.to_bytes().to_vec();
+    fragment.extend_from_slice(&[0; 4]);
+
+    let mut reassembler = Reassembler::new();
+    assert_eq!(
+        reassembler.push(&fragment, 0).unwrap_err(),
+        TransError::InvalidMessage
+    );
+    assert_eq!(reassembler.pending(), 0);
+}
@@ -0,0 +1,40 @@
+use transport::protocol::{DeviceResponse, HostCommand};
+use transport::{Capabilities, PROTOCOL_VERSION, TransError};
+
+#[test]
+fn same_version_is_compatible() {
+    let host = Capabilities::new("0.1.0", 296, 128);
+    let device = Capabilities::new("0.2.0", 296, 128);
+
+    assert!(host.check_compatible(&device).is_ok());
+}
+
+#[test]
+fn different_version_is_rejected() {
+    let host = Capabilities::new("0.1.0", 296, 128);
+    let device = Capabilities {
+        protocol_version: PROTOCOL_VERSION + 1,
+        ..host.clone()
+    };
+
+    assert_eq!(
+        host.check_compatible(&device),
+        Err(TransError::IncompatibleProtocol {
+            local: PROTOCOL_VERSION,
+            remote: PROTOCOL_VERSION + 1,
+        })
+    );
+}
+
+#[test]
+fn refused_commands_get_a_matching_response() {
+    let err = TransError::IncompatibleProtocol {
+        local: PROTOCOL_VERSION,
+        remote: 0,
+    };
+
+    assert_eq!(
+        DeviceResponse::error_for(&HostCommand::GetBatteryLevel, err.clone()),
+        DeviceResponse::BatteryLevel { result: Err(err) }
+    );
+}
@@ -0,0 +1,109 @@
+use memori_ui::layout::{MAX_SPLIT_DEPTH, MemoriLayout, SplitDirection, SplitSize};
+use memori_ui::widgets::{MemoriWidget, Name, UpdateFrequency, WidgetId, WidgetKind};
+use memori_ui::{MemoriState, StateError};
+use transport::TransError;
+
+fn name_widget(id: u32) -> MemoriWidget {
+    MemoriWidget::new(
+        WidgetId(id),
+        WidgetKind::Name(Name::new("test")),
+        UpdateFrequency::Never,
+        UpdateFrequency::Never,
+    )
+}
+
+#[test]
+fn valid_state_is_accepted() {
+    let state = MemoriState::try_new(
+        1,
+        [name_widget(0), name_widget(1)],
+        vec![
+            MemoriLayout::Full(WidgetId(0)),
+            MemoriLayout::VSplit {
+                left: WidgetId(0),
+                right: WidgetId(1),
+            },
+        ],
+        5,
+    );
+
+    assert!(state.is_ok());
+}
+
+#[test]
+fn invalid_state_lists_every_problem() {
+    let err = MemoriState::try_new(
+        4,
+        [name_widget(0)],
+        vec![
+            MemoriLayout::HSplit {
+                top: WidgetId(0),
+                bottom: WidgetId(7),
+            },
+            MemoriLayout::Split {
+                direction: SplitDirection::Horizontal,
+                constraints: vec![],
+                children: vec![],
+            },
+            MemoriLayout::Full(WidgetId(7)),
+            MemoriLayout::Split {
+                direction: SplitDirection::Vertical,
+                constraints: vec![SplitSize::Fill(1)],
+                children: vec![
+                    MemoriLayout::Full(WidgetId(0)),
+                    MemoriLayout::Full(WidgetId(0)),
+                ],
+            },
+        ],
+        5,
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        *err,
+        StateError {
+            dangling_widgets: vec![WidgetId(7)],
+            empty_frames: vec![1],
+            bad_active_frame: Some(4),
+            mismatched_splits: vec![3],
+            zero_denominator_ratios: vec![],
+            too_deep_frames: vec![],
+        }
+    );
+
+    // The device sends it back to the host like this.
+    let err = TransError::InvalidState(err);
+    let bytes = postcard::to_allocvec(&err).unwrap();
+    let decoded: TransError = postcard::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded, err);
+}
+
+/// `depth` splits nested in each other around a single widget.
+fn nested_splits(depth: usize) -> MemoriLayout {
+    (0..depth).fold(MemoriLayout::Full(WidgetId(0)), |child, _| {
+        MemoriLayout::Split {
+            direction: SplitDirection::Vertical,
+            constraints: vec![SplitSize::Fill(1)],
+            children: vec![child],
+        }
+    })
+}
+
+#[test]
+fn too_deep_splits_are_refused() {
+    let err = MemoriState::try_new(
+        0,
+        [name_widget(0)],
+        vec![nested_splits(MAX_SPLIT_DEPTH), nested_splits(MAX_SPLIT_DEPTH + 1)],
+        5,
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        *err,
+        StateError {
+            too_deep_frames: vec![1],
+            ..StateError::default()
+        }
+    );
+}
@@ -0,0 +1,91 @@
+//! How full the battery is, and the little battery drawn in the corner of the
+//! display to show it.
+
+use alloc::vec;
+use ratatui::layout::Rect;
+use serde::{Deserialize, Serialize};
+
+use crate::overlay::{Bitmap, Overlay, OverlayColors, cells_to_pixels};
+
+/// At or below this many percent the battery counts as low.
+pub const LOW_BATTERY_PERCENT: u8 = 15;
+
+/// Width and height of the battery drawn by [`BatteryStatus::indicator`], in pixels.
+const ICON_SIZE: (u16, u16) = (14, 7);
+
+/// Pixels between the battery and the edges of the display.
+const ICON_MARGIN: i32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct BatteryStatus {
+    /// How full the battery is, 0 to 100.
+    pub percent: u8,
+    pub charging: bool,
+}
+
+impl BatteryStatus {
+    pub fn new(percent: u8, charging: bool) -> Self {
+        Self {
+            percent: percent.min(100),
+            charging,
+        }
+    }
+
+    /// Whether the battery is about to run out, charging doesn't count.
+    pub fn is_low(&self) -> bool {
+        !self.charging && self.percent <= LOW_BATTERY_PERCENT
+    }
+
+    /// A battery in the top right corner of `area`, filled as much as the
+    /// battery is, or with a plus in it while charging.
+    pub fn indicator(&self, area: Rect) -> Overlay {
+        let (width, height) = ICON_SIZE;
+        let mut bitmap = Bitmap::new(width, height);
+
+        // The outline, with the terminal nub on the right.
+        let body = width - 2;
+        for x in 0..body {
+            bitmap.set(x, 0, true);
+            bitmap.set(x, height - 1, true);
+        }
+        for y in 0..height {
+            bitmap.set(0, y, true);
+            bitmap.set(body - 1, y, true);
+        }
+        for y in 2..height - 2 {
+            bitmap.set(body, y, true);
+            bitmap.set(body + 1, y, true);
+        }
+
+        // Inside, with a pixel of space all around.
+        let (inner_x, inner_y) = (2, 2);
+        let (inner_width, inner_height) = (body - 4, height - 4);
+        if self.charging {
+            let (mid_x, mid_y) = (inner_x + inner_width / 2, inner_y + inner_height / 2);
+            for x in mid_x - 1..=mid_x + 1 {
+                bitmap.set(x, mid_y, true);
+            }
+            for y in inner_y..inner_y + inner_height {
+                bitmap.set(mid_x, y, true);
+            }
+        } else {
+            // Any charge left shows at least one column.
+            let filled = (u16::from(self.percent) * inner_width).div_ceil(100);
+            for x in inner_x..inner_x + filled {
+                for y in inner_y..inner_y + inner_height {
+                    bitmap.set(x, y, true);
+                }
+            }
+        }
+
+        let pixels = cells_to_pixels(area);
+        Overlay {
+            x: pixels.top_left.x + pixels.size.width as i32 - i32::from(width) - ICON_MARGIN,
+            y: pixels.top_left.y + ICON_MARGIN,
+            scale: 1,
+            bitmap,
+            colors: OverlayColors::Theme,
+        }
+    }
+}
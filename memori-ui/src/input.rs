@@ -0,0 +1,186 @@
+//! The buttons on the device and what pressing them does.
+//!
+//! Whatever reads the buttons feeds [`PressDetector`]s, one per button, and
+//! hands the presses they report to [`MemoriState::handle_input`]. The
+//! [`InputEffect`] it returns says what's left to do that needs the host.
+
+use alloc::{vec, vec::Vec};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    MemoriState,
+    widgets::{Todo, WidgetId, WidgetKind},
+};
+
+/// Milliseconds a button has to stay up or down before we believe it.
+pub const DEBOUNCE_MS: u64 = 30;
+
+/// Milliseconds a button has to be held down for a [`Press::Long`].
+pub const LONG_PRESS_MS: u64 = 600;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub enum Button {
+    Prev,
+    Next,
+    Select,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub enum Press {
+    /// Let go before [`LONG_PRESS_MS`].
+    Short,
+    /// Held for [`LONG_PRESS_MS`], reported right away rather than on release.
+    Long,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct InputEvent {
+    pub button: Button,
+    pub press: Press,
+}
+
+impl InputEvent {
+    pub fn new(button: Button, press: Press) -> Self {
+        Self { button, press }
+    }
+}
+
+/// Turns the raw readings of one button into presses, ignoring bounces.
+#[derive(Debug, Clone, Default)]
+pub struct PressDetector {
+    /// Where the button settled, `true` for down.
+    down: bool,
+    /// The last reading, and since when it's been that.
+    reading: bool,
+    reading_since: u64,
+    /// When the button settled down.
+    down_since: u64,
+    /// Whether this press already was a long one.
+    long_reported: bool,
+}
+
+impl PressDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes a reading of the button at `now_ms`, returns the press it finished.
+    /// Call it every few milliseconds while [`PressDetector::is_idle`] is `false`.
+    pub fn update(&mut self, down: bool, now_ms: u64) -> Option<Press> {
+        if down != self.reading {
+            self.reading = down;
+            self.reading_since = now_ms;
+        }
+
+        if self.reading != self.down && now_ms - self.reading_since >= DEBOUNCE_MS {
+            self.down = self.reading;
+            if self.down {
+                self.down_since = self.reading_since;
+                self.long_reported = false;
+            } else if !self.long_reported {
+                return Some(Press::Short);
+            }
+        }
+
+        if self.down && !self.long_reported && now_ms - self.down_since >= LONG_PRESS_MS {
+            self.long_reported = true;
+            return Some(Press::Long);
+        }
+
+        None
+    }
+
+    /// Whether the button is up and settled, nothing happens until it goes down.
+    pub fn is_idle(&self) -> bool {
+        !self.down && !self.reading
+    }
+}
+
+/// What's left to do after [`MemoriState::handle_input`], besides drawing the
+/// state again when it changed.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum InputEffect {
+    /// Nothing changed.
+    None,
+    /// Only the state changed.
+    Redraw,
+    /// Ask the host for fresh data for these widgets.
+    Refresh(Vec<WidgetId>),
+    /// The state changed, and the host has to know the item is done, or not.
+    TodoToggled {
+        widget_id: WidgetId,
+        item_id: u32,
+        done: bool,
+    },
+}
+
+impl MemoriState {
+    /// Does what pressing a button does:
+    ///
+    /// - Prev and Next switch frames, held they move the selection of a to-do
+    ///   list in the frame instead.
+    /// - Select checks off the selected to-do item, or with no list in the
+    ///   frame refreshes what's shown. Held it always refreshes.
+    pub fn handle_input(&mut self, event: InputEvent) -> InputEffect {
+        match (event.button, event.press) {
+            (Button::Prev | Button::Next, Press::Short) => {
+                if self.frames().len() < 2 {
+                    return InputEffect::None;
+                }
+                if event.button == Button::Next {
+                    self.next_frame();
+                } else {
+                    self.prev_frame();
+                }
+                InputEffect::Redraw
+            }
+            (Button::Prev | Button::Next, Press::Long) => match self.shown_todo() {
+                Some((_, todo)) => {
+                    if event.button == Button::Next {
+                        todo.select_next();
+                    } else {
+                        todo.select_prev();
+                    }
+                    InputEffect::Redraw
+                }
+                None => InputEffect::None,
+            },
+            (Button::Select, Press::Short) => match self.shown_todo() {
+                Some((widget_id, todo)) => match todo.toggle_selected() {
+                    Some((item_id, done)) => InputEffect::TodoToggled {
+                        widget_id,
+                        item_id,
+                        done,
+                    },
+                    None => InputEffect::None,
+                },
+                None => InputEffect::Refresh(self.shown_widgets()),
+            },
+            (Button::Select, Press::Long) => InputEffect::Refresh(self.shown_widgets()),
+        }
+    }
+
+    /// Every widget in the active frame, once.
+    pub fn shown_widgets(&self) -> Vec<WidgetId> {
+        let mut ids = self.active_frame().widget_ids();
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+
+    /// The first to-do list in the active frame, the one the buttons control.
+    fn shown_todo(&mut self) -> Option<(WidgetId, &mut Todo)> {
+        let id = self.active_frame().widget_ids().into_iter().find(|id| {
+            self.widgets
+                .get(id)
+                .is_some_and(|widget| matches!(widget.kind, WidgetKind::Todo(_)))
+        })?;
+        match &mut self.widgets.get_mut(&id)?.kind {
+            WidgetKind::Todo(todo) => Some((id, todo)),
+            _ => None,
+        }
+    }
+}
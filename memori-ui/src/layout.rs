@@ -4,8 +4,61 @@ use crate::widgets::WidgetId;
 use alloc::vec;
 use alloc::vec::Vec;
 use core as std;
+use ratatui::layout::{Constraint, Direction};
 use serde::{Deserialize, Serialize};
 
+/// How many [`MemoriLayout::Split`]s may be nested in each other. Everything
+/// walking a layout recurses, so deeper trees could run the device out of stack.
+pub const MAX_SPLIT_DEPTH: usize = 8;
+
+/// Which way a [`MemoriLayout::Split`] lays out its children.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub enum SplitDirection {
+    /// Side by side, left to right.
+    Horizontal,
+    /// Stacked, top to bottom.
+    Vertical,
+}
+
+impl From<SplitDirection> for Direction {
+    fn from(direction: SplitDirection) -> Self {
+        match direction {
+            SplitDirection::Horizontal => Direction::Horizontal,
+            SplitDirection::Vertical => Direction::Vertical,
+        }
+    }
+}
+
+/// How much room one child of a [`MemoriLayout::Split`] gets.
+///
+/// Lengths are in terminal cells, the way the whole UI is laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub enum SplitSize {
+    Percentage(u16),
+    /// `numerator / denominator` of the space.
+    Ratio(u32, u32),
+    /// Exactly this many cells.
+    Length(u16),
+    /// At least this many cells.
+    Min(u16),
+    /// Whatever is left, shared with the other `Fill`s by weight.
+    Fill(u16),
+}
+
+impl From<SplitSize> for Constraint {
+    fn from(size: SplitSize) -> Self {
+        match size {
+            SplitSize::Percentage(p) => Constraint::Percentage(p),
+            SplitSize::Ratio(numerator, denominator) => Constraint::Ratio(numerator, denominator),
+            SplitSize::Length(cells) => Constraint::Length(cells),
+            SplitSize::Min(cells) => Constraint::Min(cells),
+            SplitSize::Fill(weight) => Constraint::Fill(weight),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all_fields = "camelCase")]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
@@ -123,4 +176,177 @@ pub enum MemoriLayout {
         bottom_left: WidgetId,
         bottom_right: WidgetId,
     },
+
+    /// Any number of layouts next to or on top of each other, sized by
+    /// `constraints` (one per child). Nest these for anything the shapes above
+    /// can't do.
+    ///
+    /// ┌─────┬───────────┬─────┐
+    /// │     │           │     │
+    /// │     │           │     │
+    /// │ 25% │    50%    │ 25% │
+    /// │     │           │     │
+    /// │     │           │     │
+    /// └─────┴───────────┴─────┘
+    #[cfg_attr(feature = "specta", specta(rename_all = "camelCase"))]
+    Split {
+        direction: SplitDirection,
+        constraints: Vec<SplitSize>,
+        children: Vec<MemoriLayout>,
+    },
+}
+
+impl MemoriLayout {
+    /// Two equally sized layouts in `direction`.
+    fn halves(direction: SplitDirection, first: MemoriLayout, second: MemoriLayout) -> Self {
+        MemoriLayout::Split {
+            direction,
+            constraints: vec![SplitSize::Percentage(50); 2],
+            children: vec![first, second],
+        }
+    }
+
+    /// The shorthand layouts written out as [`MemoriLayout::Split`]s, `Full`
+    /// and `Split` come back as they are.
+    pub fn expand(&self) -> MemoriLayout {
+        use MemoriLayout::*;
+        use SplitDirection::*;
+
+        match self.clone() {
+            VSplit { left, right } => Self::halves(Horizontal, Full(left), Full(right)),
+            HSplit { top, bottom } => Self::halves(Vertical, Full(top), Full(bottom)),
+            VSplitWithRightHSplit {
+                left,
+                right_top,
+                right_bottom,
+            } => Self::halves(
+                Horizontal,
+                Full(left),
+                Self::halves(Vertical, Full(right_top), Full(right_bottom)),
+            ),
+            HSplitWithTopVSplit {
+                bottom,
+                top_right,
+                top_left,
+            } => Self::halves(
+                Vertical,
+                Self::halves(Horizontal, Full(top_left), Full(top_right)),
+                Full(bottom),
+            ),
+            VSplitWithLeftHSplit {
+                left_top,
+                left_bottom,
+                right,
+            } => Self::halves(
+                Horizontal,
+                Self::halves(Vertical, Full(left_top), Full(left_bottom)),
+                Full(right),
+            ),
+            HSplitWithBottomVSplit {
+                top,
+                bottom_left,
+                bottom_right,
+            } => Self::halves(
+                Vertical,
+                Full(top),
+                Self::halves(Horizontal, Full(bottom_left), Full(bottom_right)),
+            ),
+            Fourths {
+                top_left,
+                top_right,
+                bottom_left,
+                bottom_right,
+            } => Self::halves(
+                Vertical,
+                Self::halves(Horizontal, Full(top_left), Full(top_right)),
+                Self::halves(Horizontal, Full(bottom_left), Full(bottom_right)),
+            ),
+            layout @ (Full(_) | Split { .. }) => layout,
+        }
+    }
+
+    /// Whether more than [`MAX_SPLIT_DEPTH`] [`MemoriLayout::Split`]s are
+    /// nested in each other. Only looks that far down, so it's safe to call on
+    /// any layout, unlike the other checks.
+    pub fn is_too_deep(&self) -> bool {
+        self.deeper_than(MAX_SPLIT_DEPTH)
+    }
+
+    fn deeper_than(&self, depth: usize) -> bool {
+        match self {
+            MemoriLayout::Split { children, .. } => {
+                depth == 0 || children.iter().any(|child| child.deeper_than(depth - 1))
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether a [`MemoriLayout::Split`] in here doesn't have exactly one
+    /// constraint per child, the extra children would never be drawn.
+    pub fn has_mismatched_split(&self) -> bool {
+        self.any_split(&|constraints, children| constraints.len() != children.len())
+    }
+
+    /// Whether a [`MemoriLayout::Split`] in here has a [`SplitSize::Ratio`]
+    /// with a denominator of `0`.
+    pub fn has_zero_ratio(&self) -> bool {
+        self.any_split(&|constraints, _| {
+            constraints
+                .iter()
+                .any(|size| matches!(size, SplitSize::Ratio(_, 0)))
+        })
+    }
+
+    /// Whether `check` holds for any [`MemoriLayout::Split`] in here, however
+    /// deeply nested. The shorthands are always fine.
+    fn any_split(&self, check: &impl Fn(&[SplitSize], &[MemoriLayout]) -> bool) -> bool {
+        match self {
+            MemoriLayout::Split {
+                constraints,
+                children,
+                ..
+            } => {
+                check(constraints, children) || children.iter().any(|child| child.any_split(check))
+            }
+            _ => false,
+        }
+    }
+
+    /// Every widget shown by this layout, in no particular order.
+    pub fn widget_ids(&self) -> Vec<WidgetId> {
+        match *self {
+            MemoriLayout::Full(id) => vec![id],
+            MemoriLayout::VSplit { left, right } => vec![left, right],
+            MemoriLayout::HSplit { top, bottom } => vec![top, bottom],
+            MemoriLayout::VSplitWithRightHSplit {
+                left,
+                right_top,
+                right_bottom,
+            } => vec![left, right_top, right_bottom],
+            MemoriLayout::HSplitWithTopVSplit {
+                bottom,
+                top_right,
+                top_left,
+            } => vec![top_left, top_right, bottom],
+            MemoriLayout::VSplitWithLeftHSplit {
+                left_top,
+                left_bottom,
+                right,
+            } => vec![left_top, left_bottom, right],
+            MemoriLayout::HSplitWithBottomVSplit {
+                top,
+                bottom_left,
+                bottom_right,
+            } => vec![top, bottom_left, bottom_right],
+            MemoriLayout::Fourths {
+                top_left,
+                top_right,
+                bottom_left,
+                bottom_right,
+            } => vec![top_left, top_right, bottom_left, bottom_right],
+            MemoriLayout::Split { ref children, .. } => {
+                children.iter().flat_map(MemoriLayout::widget_ids).collect()
+            }
+        }
+    }
 }
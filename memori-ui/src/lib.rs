@@ -2,11 +2,16 @@
 
 use embedded_graphics::mono_font::MonoFont;
 use embedded_graphics_unicodefonts::*;
+use overlay::PixelLayer;
 use ratatui::prelude::*;
 
 extern crate alloc;
 
+pub mod battery;
+pub mod input;
 pub mod layout;
+pub mod overlay;
+pub mod time;
 pub mod widgets;
 
 mod state;
@@ -19,9 +24,16 @@ pub const FONT_BOLD: Option<MonoFont<'static>> = Some(MONO_7X14_BOLD);
 /// Italic font.
 pub const FONT_ITALIC: Option<MonoFont<'static>> = None;
 
+/// Width of the Memori e-paper display in pixels.
+pub const DISPLAY_WIDTH: u16 = 296;
+/// Height of the Memori e-paper display in pixels.
+pub const DISPLAY_HEIGHT: u16 = 128;
+
 
 pub struct Memori<B: Backend> {
     term: Terminal<B>,
+    inverted: bool,
+    pixels: Option<PixelLayer>,
 }
 
 impl<B> Memori<B>
@@ -29,13 +41,51 @@ where
     B: Backend,
 {
     pub fn new(term: Terminal<B>) -> Self {
-        Self { term }
+        Self {
+            term,
+            inverted: false,
+            pixels: None,
+        }
+    }
+
+    /// Like [`Memori::new`], also handing the overlays of every update to
+    /// `pixels`, which the display's flush callback has to draw.
+    pub fn with_pixel_layer(term: Terminal<B>, pixels: PixelLayer) -> Self {
+        Self {
+            term,
+            inverted: false,
+            pixels: Some(pixels),
+        }
+    }
+
+    /// The backend the terminal draws with, e.g. to get at the display behind it.
+    pub fn backend_mut(&mut self) -> &mut B {
+        self.term.backend_mut()
+    }
+
+    /// Draw every cell on the next update, not only the ones that changed. For
+    /// when the display changed under the terminal, like being turned around.
+    pub fn redraw_all(&mut self) -> Result<(), B::Error> {
+        self.term.clear()
+    }
+
+    /// Draw white on black (dark mode), takes effect on the next update.
+    pub fn set_inverted(&mut self, inverted: bool) {
+        self.inverted = inverted;
     }
 
     pub fn update(&mut self, state: &MemoriState) -> Result<(), B::Error> {
         self.term
             .draw(|f| {
+                // Before rendering, the flush at the end of the draw needs them.
+                if let Some(pixels) = &self.pixels {
+                    pixels.set(state.overlays(f.area()), self.inverted);
+                }
                 f.render_widget(state, f.area());
+                if self.inverted {
+                    let area = f.area();
+                    f.buffer_mut().set_style(area, Modifier::REVERSED);
+                }
             })
             .map(|_| ())
     }
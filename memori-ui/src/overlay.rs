@@ -0,0 +1,242 @@
+//! Pixels drawn straight onto the display, over the character grid.
+//!
+//! A cell is 7x14 pixels, too coarse for things like QR codes. Widgets that
+//! need more hand out an [`Overlay`] for their slot, [`Memori`] collects them
+//! into a [`PixelLayer`] on every update, and the display's flush callback
+//! draws the layer on top of the cells right before the panel is refreshed.
+//!
+//! The cells under an overlay should be left blank. Drawing over the display
+//! only works out with mousefood's `framebuffer` feature, which redraws every
+//! pixel on each flush, otherwise the overlay stays behind after it's gone.
+//!
+//! [`Memori`]: crate::Memori
+
+use alloc::{rc::Rc, vec, vec::Vec};
+use core::cell::RefCell;
+use embedded_graphics::{pixelcolor::Rgb888, prelude::*, primitives::Rectangle};
+use ratatui::layout::Rect;
+use serde::{Deserialize, Serialize};
+
+use crate::FONT_REGULAR;
+
+/// A 1-bit image, packed 8 pixels to a byte with the leftmost pixel in the
+/// highest bit, every row starting on a new byte.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct Bitmap {
+    width: u16,
+    height: u16,
+    bits: Vec<u8>,
+}
+
+impl Bitmap {
+    /// A bitmap with every pixel off.
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            bits: vec![0; Self::bytes_per_row(width) * usize::from(height)],
+        }
+    }
+
+    /// A bitmap from packed bits, `None` if there are too few or too many of them.
+    pub fn from_bits(width: u16, height: u16, bits: Vec<u8>) -> Option<Self> {
+        (bits.len() == Self::bytes_per_row(width) * usize::from(height)).then_some(Self {
+            width,
+            height,
+            bits,
+        })
+    }
+
+    fn bytes_per_row(width: u16) -> usize {
+        usize::from(width).div_ceil(8)
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// The packed bits, see [`Bitmap`].
+    pub fn bits(&self) -> &[u8] {
+        &self.bits
+    }
+
+    /// Whether the pixel at `x`, `y` is on, `false` outside of the bitmap.
+    pub fn get(&self, x: u16, y: u16) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        let byte = usize::from(y) * Self::bytes_per_row(self.width) + usize::from(x / 8);
+        self.bits[byte] & (0x80 >> (x % 8)) != 0
+    }
+
+    /// Every `factor`th pixel of every `factor`th row, a quick way to make the
+    /// bitmap `factor` times smaller.
+    pub fn downscale(&self, factor: u16) -> Self {
+        let factor = factor.max(1);
+        let mut smaller = Self::new(self.width / factor, self.height / factor);
+        for y in 0..smaller.height {
+            for x in 0..smaller.width {
+                smaller.set(x, y, self.get(x * factor, y * factor));
+            }
+        }
+        smaller
+    }
+
+    /// Turns the pixel at `x`, `y` on or off, does nothing outside of the bitmap.
+    pub fn set(&mut self, x: u16, y: u16, on: bool) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let byte = usize::from(y) * Self::bytes_per_row(self.width) + usize::from(x / 8);
+        let mask = 0x80 >> (x % 8);
+        if on {
+            self.bits[byte] |= mask;
+        } else {
+            self.bits[byte] &= !mask;
+        }
+    }
+}
+
+/// What the pixels of an [`Overlay`] look like.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum OverlayColors {
+    /// Pixels that are on are black and the rest white, dark mode or not.
+    /// For things that have to be read by a camera, like QR codes.
+    Ink,
+    /// Pixels that are on have the color of text, the rest of the background.
+    Theme,
+}
+
+/// A [`Bitmap`] placed on the display.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Overlay {
+    /// Left edge on the display, in pixels.
+    pub x: i32,
+    /// Top edge on the display, in pixels.
+    pub y: i32,
+    /// Every pixel of the bitmap is drawn as a `scale` by `scale` square.
+    pub scale: u16,
+    pub bitmap: Bitmap,
+    pub colors: OverlayColors,
+}
+
+impl Overlay {
+    /// The overlay `bitmap` as big as it fits in the cells of `area`, centered,
+    /// `None` if it doesn't fit at all.
+    pub fn fit(bitmap: Bitmap, area: Rect, colors: OverlayColors) -> Option<Self> {
+        let pixels = cells_to_pixels(area);
+        let (width, height) = (u32::from(bitmap.width), u32::from(bitmap.height));
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        let scale = (pixels.size.width / width).min(pixels.size.height / height);
+        if scale == 0 {
+            return None;
+        }
+
+        Some(Self {
+            x: pixels.top_left.x + ((pixels.size.width - width * scale) / 2) as i32,
+            y: pixels.top_left.y + ((pixels.size.height - height * scale) / 2) as i32,
+            scale: scale as u16,
+            bitmap,
+            colors,
+        })
+    }
+
+    /// The pixels the overlay covers.
+    pub fn bounding_box(&self) -> Rectangle {
+        let scale = u32::from(self.scale);
+        Rectangle::new(
+            Point::new(self.x, self.y),
+            Size::new(
+                u32::from(self.bitmap.width) * scale,
+                u32::from(self.bitmap.height) * scale,
+            ),
+        )
+    }
+
+    /// Draws the overlay, `inverted` like dark mode.
+    pub fn draw<D, C>(&self, display: &mut D, inverted: bool) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+        C: PixelColor + From<Rgb888>,
+    {
+        // Same colors as mousefood's default theme, text is white.
+        let (on, off) = match (self.colors, inverted) {
+            (OverlayColors::Ink, _) | (OverlayColors::Theme, true) => {
+                (Rgb888::BLACK, Rgb888::WHITE)
+            }
+            (OverlayColors::Theme, false) => (Rgb888::WHITE, Rgb888::BLACK),
+        };
+
+        let bounds = self.bounding_box();
+        let scale = u32::from(self.scale.max(1));
+        let colors = bounds.points().map(|point| {
+            let x = (point.x - self.x) as u32 / scale;
+            let y = (point.y - self.y) as u32 / scale;
+            C::from(if self.bitmap.get(x as u16, y as u16) {
+                on
+            } else {
+                off
+            })
+        });
+        display.fill_contiguous(&bounds, colors)
+    }
+}
+
+/// The pixels of the display covered by the cells of `area`.
+pub fn cells_to_pixels(area: Rect) -> Rectangle {
+    let cell = FONT_REGULAR.character_size;
+    Rectangle::new(
+        Point::new(
+            i32::from(area.x) * cell.width as i32,
+            i32::from(area.y) * cell.height as i32,
+        ),
+        Size::new(
+            u32::from(area.width) * cell.width,
+            u32::from(area.height) * cell.height,
+        ),
+    )
+}
+
+#[derive(Debug, Default)]
+struct Layer {
+    overlays: Vec<Overlay>,
+    inverted: bool,
+}
+
+/// The overlays of the last frame drawn by [`Memori`], for the display's flush
+/// callback to draw with [`PixelLayer::draw`]. Clones share the same overlays.
+///
+/// [`Memori`]: crate::Memori
+#[derive(Debug, Clone, Default)]
+pub struct PixelLayer(Rc<RefCell<Layer>>);
+
+impl PixelLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn set(&self, overlays: Vec<Overlay>, inverted: bool) {
+        *self.0.borrow_mut() = Layer { overlays, inverted };
+    }
+
+    /// Draws the overlays of the last frame.
+    pub fn draw<D, C>(&self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+        C: PixelColor + From<Rgb888>,
+    {
+        let layer = self.0.borrow();
+        for overlay in &layer.overlays {
+            overlay.draw(display, layer.inverted)?;
+        }
+        Ok(())
+    }
+}
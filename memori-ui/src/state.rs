@@ -1,14 +1,20 @@
+use alloc::boxed::Box;
+use alloc::format;
 use alloc::vec::Vec;
+use core::fmt::Display;
 use hashbrown::HashMap;
 use ratatui::{
     buffer::Buffer,
     layout::{Constraint, Layout, Rect},
-    widgets::Widget,
+    widgets::{Block, Paragraph, Widget, Wrap},
 };
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    layout::MemoriLayout,
+    battery::BatteryStatus,
+    layout::{MAX_SPLIT_DEPTH, MemoriLayout},
+    overlay::Overlay,
+    time::LocalTime,
     widgets::{MemoriWidget, Name, UpdateFrequency, WidgetId, WidgetKind},
 };
 
@@ -17,10 +23,80 @@ pub struct MemoriState {
     active_frame_idx: usize,
     pub widgets: HashMap<WidgetId, MemoriWidget>,
     frames: Vec<MemoriLayout>,
-    #[allow(dead_code)]
+    /// Seconds each frame is shown for before moving on to the next one, `0` to stay put.
     frame_time: u32,
+    /// Shown in the corner when set, the device fills it in itself.
+    #[serde(skip)]
+    battery: Option<BatteryStatus>,
 }
 
+/// Everything wrong with a [`MemoriState`], see [`MemoriState::validate`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct StateError {
+    /// Widgets shown by a frame that aren't in the state.
+    pub dangling_widgets: Vec<WidgetId>,
+    /// Indices of the frames that don't show any widget.
+    pub empty_frames: Vec<usize>,
+    /// The active frame index, when it doesn't point at a frame.
+    pub bad_active_frame: Option<usize>,
+    /// Indices of the frames with a split that doesn't have one constraint per child.
+    pub mismatched_splits: Vec<usize>,
+    /// Indices of the frames with a split sized by a ratio with a denominator of `0`.
+    pub zero_denominator_ratios: Vec<usize>,
+    /// Indices of the frames nesting splits deeper than [`MAX_SPLIT_DEPTH`],
+    /// nothing else is checked for those.
+    pub too_deep_frames: Vec<usize>,
+}
+
+impl StateError {
+    fn is_empty(&self) -> bool {
+        self.dangling_widgets.is_empty()
+            && self.empty_frames.is_empty()
+            && self.bad_active_frame.is_none()
+            && self.mismatched_splits.is_empty()
+            && self.zero_denominator_ratios.is_empty()
+            && self.too_deep_frames.is_empty()
+    }
+}
+
+impl Display for StateError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut problems = Vec::new();
+        if !self.dangling_widgets.is_empty() {
+            let ids: Vec<u32> = self.dangling_widgets.iter().map(|id| id.0).collect();
+            problems.push(format!("frames show missing widgets {ids:?}"));
+        }
+        if !self.empty_frames.is_empty() {
+            problems.push(format!("frames {:?} are empty", self.empty_frames));
+        }
+        if let Some(idx) = self.bad_active_frame {
+            problems.push(format!("active frame {idx} doesn't exist"));
+        }
+        if !self.mismatched_splits.is_empty() {
+            problems.push(format!(
+                "frames {:?} split into a different number of children than constraints",
+                self.mismatched_splits
+            ));
+        }
+        if !self.zero_denominator_ratios.is_empty() {
+            problems.push(format!(
+                "frames {:?} have ratios dividing by zero",
+                self.zero_denominator_ratios
+            ));
+        }
+        if !self.too_deep_frames.is_empty() {
+            problems.push(format!(
+                "frames {:?} nest splits more than {MAX_SPLIT_DEPTH} deep",
+                self.too_deep_frames
+            ));
+        }
+
+        write!(f, "invalid state: {}", problems.join(", "))
+    }
+}
+
+impl core::error::Error for StateError {}
+
 impl Default for MemoriState {
     fn default() -> Self {
         let mut widgets = HashMap::new();
@@ -43,11 +119,16 @@ impl Default for MemoriState {
             frames,
             widgets,
             frame_time: 5,
+            battery: None,
         }
     }
 }
 
 impl MemoriState {
+    /// # Panics
+    ///
+    /// If `active_frame_idx` isn't an index into `frames`, use
+    /// [`MemoriState::try_new`] for anything that didn't come from this code.
     pub fn new(
         active_frame_idx: usize,
         widgets: impl IntoIterator<Item = MemoriWidget>,
@@ -64,6 +145,81 @@ impl MemoriState {
             widgets: widgets.into_iter().map(|w| (w.id, w)).collect(),
             frames,
             frame_time,
+            battery: None,
+        }
+    }
+
+    /// Like [`MemoriState::new`], but checks the result with [`MemoriState::validate`].
+    pub fn try_new(
+        active_frame_idx: usize,
+        widgets: impl IntoIterator<Item = MemoriWidget>,
+        frames: Vec<MemoriLayout>,
+        frame_time: u32,
+    ) -> Result<Self, Box<StateError>> {
+        let state = Self {
+            active_frame_idx,
+            widgets: widgets.into_iter().map(|w| (w.id, w)).collect(),
+            frames,
+            frame_time,
+            battery: None,
+        };
+        state.validate()?;
+        Ok(state)
+    }
+
+    /// Checks that every frame shows at least one widget, that those widgets
+    /// exist and that the active frame does too, and that every split has one
+    /// constraint per child and no ratio with a denominator of `0`, nested no
+    /// deeper than [`MAX_SPLIT_DEPTH`].
+    ///
+    /// A deserialized state hasn't been through [`MemoriState::new`], so check
+    /// it with this before showing it.
+    pub fn validate(&self) -> Result<(), Box<StateError>> {
+        let mut error = StateError::default();
+        Self::check_splits(&self.frames, &mut error);
+
+        for (idx, frame) in self.frames.iter().enumerate() {
+            if error.too_deep_frames.contains(&idx) {
+                continue;
+            }
+            let ids = frame.widget_ids();
+            if ids.is_empty() {
+                error.empty_frames.push(idx);
+            }
+            for id in ids {
+                if !self.widgets.contains_key(&id) && !error.dangling_widgets.contains(&id) {
+                    error.dangling_widgets.push(id);
+                }
+            }
+        }
+
+        if self.active_frame_idx >= self.frames.len() {
+            error.bad_active_frame = Some(self.active_frame_idx);
+        }
+
+        if error.is_empty() { Ok(()) } else { Err(Box::new(error)) }
+    }
+
+    /// Checks the splits of frames that aren't part of a state yet, the way
+    /// [`MemoriState::validate`] does.
+    pub fn validate_splits(frames: &[MemoriLayout]) -> Result<(), Box<StateError>> {
+        let mut error = StateError::default();
+        Self::check_splits(frames, &mut error);
+        if error.is_empty() { Ok(()) } else { Err(Box::new(error)) }
+    }
+
+    fn check_splits(frames: &[MemoriLayout], error: &mut StateError) {
+        for (idx, frame) in frames.iter().enumerate() {
+            if frame.is_too_deep() {
+                error.too_deep_frames.push(idx);
+                continue;
+            }
+            if frame.has_mismatched_split() {
+                error.mismatched_splits.push(idx);
+            }
+            if frame.has_zero_ratio() {
+                error.zero_denominator_ratios.push(idx);
+            }
         }
     }
 
@@ -72,182 +228,208 @@ impl MemoriState {
             .get(self.active_frame_idx)
             .expect("invariant failure! active_frame_idx is not a index into frames!")
     }
-}
-impl Widget for &MemoriState {
-    //TODO: remove after we finish this
-    #[allow(unused)]
-    fn render(self, area: Rect, buf: &mut Buffer)
-    where
-        Self: Sized,
-    {
-        match self.active_frame() {
-            MemoriLayout::Full(id) => self
-                .widgets
-                .get(id)
-                .expect("WidgetId should exist in hashmap")
-                .render(area, buf),
-            MemoriLayout::HSplit { top, bottom } => {
-                let rects =
-                    Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)])
-                        .split(area);
-                self.widgets
-                    .get(top)
-                    .expect("WidgetId should exist in hashmap")
-                    .render(rects[0], buf);
-                self.widgets
-                    .get(bottom)
-                    .expect("WidgetId should exist in hashmap")
-                    .render(rects[1], buf);
-            }
-            MemoriLayout::VSplit { left, right } => {
-                let rects =
-                    Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
-                        .split(area);
-                self.widgets
-                    .get(left)
-                    .expect("WidgetId should exist in hashmap")
-                    .render(rects[0], buf);
-                self.widgets
-                    .get(right)
-                    .expect("WidgetId should exist in hashmap")
-                    .render(rects[1], buf);
-            }
-            MemoriLayout::VSplitWithRightHSplit {
-                left,
-                right_top,
-                right_bottom,
-            } => {
-                let vertical_rects =
-                    Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
-                        .split(area);
-                self.widgets
-                    .get(left)
-                    .expect("WidgetId should exist in hashmap")
-                    .render(vertical_rects[0], buf);
 
-                let right_rects =
-                    Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)])
-                        .split(vertical_rects[1]);
-                self.widgets
-                    .get(right_top)
-                    .expect("WidgetId should exist in hashmap")
-                    .render(right_rects[0], buf);
-                self.widgets
-                    .get(right_bottom)
-                    .expect("WidgetId should exist in hashmap")
-                    .render(right_rects[1], buf);
-            }
-            MemoriLayout::HSplitWithTopVSplit {
-                bottom,
-                top_right,
-                top_left,
-            } => {
-                let horizontal_rects =
-                    Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)])
-                        .split(area);
+    pub fn active_frame_idx(&self) -> usize {
+        self.active_frame_idx
+    }
 
-                let top_rects =
-                    Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
-                        .split(horizontal_rects[0]);
+    pub fn frames(&self) -> &[MemoriLayout] {
+        &self.frames
+    }
 
-                self.widgets
-                    .get(top_left)
-                    .expect("WidgetId should exist in hashmap")
-                    .render(top_rects[0], buf);
-                self.widgets
-                    .get(top_right)
-                    .expect("WidgetId should exist in hashmap")
-                    .render(top_rects[1], buf);
-                self.widgets
-                    .get(bottom)
-                    .expect("WidgetId should exist in hashmap")
-                    .render(horizontal_rects[1], buf);
-            }
-            MemoriLayout::VSplitWithLeftHSplit {
-                left_top,
-                left_bottom,
-                right,
-            } => {
-                let vertical_rects =
-                    Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
-                        .split(area);
+    /// Seconds each frame is shown for before moving on to the next one, `0` to stay put.
+    pub fn frame_time(&self) -> u32 {
+        self.frame_time
+    }
 
-                let left_rects =
-                    Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)])
-                        .split(vertical_rects[0]);
-                self.widgets
-                    .get(left_top)
-                    .expect("WidgetId should exist in hashmap")
-                    .render(left_rects[0], buf);
-                self.widgets
-                    .get(left_bottom)
-                    .expect("WidgetId should exist in hashmap")
-                    .render(left_rects[1], buf);
+    /// Switches to the next frame, wrapping around to the first one after the last.
+    ///
+    /// Returns `false` and does nothing if there are no frames.
+    pub fn next_frame(&mut self) -> bool {
+        if self.frames.is_empty() {
+            return false;
+        }
 
-                self.widgets
-                    .get(right)
-                    .expect("WidgetId should exist in hashmap")
-                    .render(vertical_rects[1], buf);
-            }
-            MemoriLayout::HSplitWithBottomVSplit {
-                top,
-                bottom_left,
-                bottom_right,
+        self.active_frame_idx = (self.active_frame_idx + 1) % self.frames.len();
+        true
+    }
+
+    /// Switches to the previous frame, wrapping around to the last one before the first.
+    ///
+    /// Returns `false` and does nothing if there are no frames.
+    pub fn prev_frame(&mut self) -> bool {
+        if self.frames.is_empty() {
+            return false;
+        }
+
+        let last = self.frames.len() - 1;
+        self.active_frame_idx = match self.active_frame_idx {
+            0 => last,
+            idx => (idx - 1).min(last),
+        };
+        true
+    }
+
+    /// Inserts a widget, replacing the one with the same id. Returns the replaced widget.
+    pub fn upsert_widget(&mut self, widget: MemoriWidget) -> Option<MemoriWidget> {
+        self.widgets.insert(widget.id, widget)
+    }
+
+    /// Removes a widget, returns `None` if it doesn't exist.
+    ///
+    /// Make sure no frame shows the widget anymore, see [`MemoriState::is_widget_shown`].
+    pub fn remove_widget(&mut self, id: WidgetId) -> Option<MemoriWidget> {
+        self.widgets.remove(&id)
+    }
+
+    /// Whether any of the frames shows the widget with this id.
+    pub fn is_widget_shown(&self, id: WidgetId) -> bool {
+        self.frames
+            .iter()
+            .any(|frame| frame.widget_ids().contains(&id))
+    }
+
+    /// Replaces the frames, starting over at the first one if the active frame no longer exists.
+    ///
+    /// Returns `false` and leaves the frames alone if there are no frames.
+    pub fn set_frames(&mut self, frames: Vec<MemoriLayout>) -> bool {
+        if frames.is_empty() {
+            return false;
+        }
+
+        if self.active_frame_idx >= frames.len() {
+            self.active_frame_idx = 0;
+        }
+        self.frames = frames;
+        true
+    }
+
+    /// Switches to the frame at `idx`, returns `false` if there is no such frame.
+    pub fn set_active_frame(&mut self, idx: usize) -> bool {
+        if idx >= self.frames.len() {
+            return false;
+        }
+
+        self.active_frame_idx = idx;
+        true
+    }
+
+    /// Seconds until something on the display is due for a change, going by
+    /// the update frequencies of the widgets and, with `rotate_frames`, the
+    /// frame time. `None` if nothing ever changes on its own.
+    pub fn wake_period(&self, rotate_frames: bool) -> Option<u32> {
+        let rotation = (rotate_frames && self.frame_time > 0 && self.frames.len() > 1)
+            .then_some(self.frame_time);
+
+        self.widgets
+            .values()
+            .flat_map(|widget| {
+                [
+                    widget.get_remote_update_frequency(),
+                    widget.get_local_update_frequency(),
+                ]
+                .map(|frequency| frequency.to_seconds())
+            })
+            .chain([rotation])
+            .flatten()
+            .min()
+    }
+
+    /// Shows the battery in the top right corner of every frame, or hides it.
+    pub fn set_battery(&mut self, battery: Option<BatteryStatus>) {
+        self.battery = battery;
+    }
+
+    pub fn battery(&self) -> Option<BatteryStatus> {
+        self.battery
+    }
+
+    /// Tells every widget what time it is on the device, see [`MemoriWidgetImpl::set_time`].
+    ///
+    /// [`MemoriWidgetImpl::set_time`]: crate::widgets::MemoriWidgetImpl::set_time
+    pub fn set_time(&mut self, now: LocalTime) {
+        for widget in self.widgets.values_mut() {
+            widget.kind.as_widget_mut().set_time(now);
+        }
+    }
+}
+
+impl MemoriState {
+    /// Renders `layout` and everything nested in it into `area`.
+    fn render_layout(&self, layout: &MemoriLayout, area: Rect, buf: &mut Buffer) {
+        match layout {
+            MemoriLayout::Full(id) => match self.widgets.get(id) {
+                Some(widget) => widget.render(area, buf),
+                // A bad frame shouldn't take the whole display down with it.
+                None => Paragraph::new(format!("Missing widget {}", id.0))
+                    .centered()
+                    .wrap(Wrap { trim: true })
+                    .block(Block::bordered())
+                    .render(area, buf),
+            },
+            MemoriLayout::Split {
+                direction,
+                constraints,
+                children,
             } => {
-                let horizontal_rects =
-                    Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)])
-                        .split(area);
-                self.widgets
-                    .get(top)
-                    .expect("WidgetId should exist in hashmap")
-                    .render(horizontal_rects[0], buf);
+                let rects = Layout::new(
+                    (*direction).into(),
+                    constraints.iter().map(|&size| Constraint::from(size)),
+                )
+                .split(area);
 
-                let bottom_rects =
-                    Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
-                        .split(horizontal_rects[1]);
-                self.widgets
-                    .get(bottom_left)
-                    .expect("WidgetId should exist in hashmap")
-                    .render(bottom_rects[0], buf);
-                self.widgets
-                    .get(bottom_right)
-                    .expect("WidgetId should exist in hashmap")
-                    .render(bottom_rects[1], buf);
+                for (child, rect) in children.iter().zip(rects.iter()) {
+                    self.render_layout(child, *rect, buf);
+                }
             }
-            MemoriLayout::Fourths {
-                top_left,
-                top_right,
-                bottom_left,
-                bottom_right,
-            } => {
-                let horizontal_rects =
-                    Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)])
-                        .split(area);
+            shorthand => self.render_layout(&shorthand.expand(), area, buf),
+        }
+    }
+}
 
-                let top_rects =
-                    Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
-                        .split(horizontal_rects[0]);
-                self.widgets
-                    .get(top_left)
-                    .expect("WidgetId should exist in hashmap")
-                    .render(top_rects[0], buf);
-                self.widgets
-                    .get(top_right)
-                    .expect("WidgetId should exist in hashmap")
-                    .render(top_rects[1], buf);
+impl MemoriState {
+    /// The overlays of the widgets in the active frame, drawn into `area`, and
+    /// the battery on top.
+    pub fn overlays(&self, area: Rect) -> Vec<Overlay> {
+        let mut overlays = Vec::new();
+        self.layout_overlays(self.active_frame(), area, &mut overlays);
+        overlays.extend(self.battery.map(|battery| battery.indicator(area)));
+        overlays
+    }
 
-                let bottom_rects =
-                    Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
-                        .split(horizontal_rects[1]);
-                self.widgets
-                    .get(bottom_left)
-                    .expect("WidgetId should exist in hashmap")
-                    .render(bottom_rects[0], buf);
+    /// Like [`MemoriState::render_layout`], but collects overlays.
+    fn layout_overlays(&self, layout: &MemoriLayout, area: Rect, overlays: &mut Vec<Overlay>) {
+        match layout {
+            MemoriLayout::Full(id) => overlays.extend(
                 self.widgets
-                    .get(bottom_right)
-                    .expect("WidgetId should exist in hashmap")
-                    .render(bottom_rects[1], buf);
+                    .get(id)
+                    .and_then(|widget| widget.kind.as_widget().overlay(area)),
+            ),
+            MemoriLayout::Split {
+                direction,
+                constraints,
+                children,
+            } => {
+                let rects = Layout::new(
+                    (*direction).into(),
+                    constraints.iter().map(|&size| Constraint::from(size)),
+                )
+                .split(area);
+
+                for (child, rect) in children.iter().zip(rects.iter()) {
+                    self.layout_overlays(child, *rect, overlays);
+                }
             }
+            shorthand => self.layout_overlays(&shorthand.expand(), area, overlays),
         }
     }
 }
+
+impl Widget for &MemoriState {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        self.render_layout(self.active_frame(), area, buf);
+    }
+}
@@ -0,0 +1,122 @@
+//! Wall clock time, as shown on the device.
+//!
+//! There is no timezone database on the device, the host tells it the UTC time
+//! and how far off local time is, and everything here works on the result.
+
+use alloc::vec;
+use serde::{Deserialize, Serialize};
+
+const SECS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// A local date and time, to the second.
+#[derive(
+    Serialize, Deserialize, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Hash,
+)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct LocalTime {
+    /// Seconds since midnight on 1970-01-01, local time.
+    secs: i64,
+}
+
+impl LocalTime {
+    /// The local time at `utc_epoch_secs` in a timezone `utc_offset_minutes` ahead of UTC.
+    pub fn from_utc(utc_epoch_secs: i64, utc_offset_minutes: i16) -> Self {
+        Self {
+            secs: utc_epoch_secs + i64::from(utc_offset_minutes) * 60,
+        }
+    }
+
+    /// The given date and time, `month` and `day` start at 1.
+    pub fn from_ymd_hms(year: i32, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Self {
+        let secs = days_from_civil(year, month, day) * SECS_PER_DAY
+            + i64::from(hour) * 3600
+            + i64::from(minute) * 60
+            + i64::from(second);
+        Self { secs }
+    }
+
+    /// This time `secs` seconds later, or earlier when negative.
+    pub fn add_secs(self, secs: i64) -> Self {
+        Self {
+            secs: self.secs + secs,
+        }
+    }
+
+    fn secs_of_day(&self) -> i64 {
+        self.secs.rem_euclid(SECS_PER_DAY)
+    }
+
+    fn days(&self) -> i64 {
+        self.secs.div_euclid(SECS_PER_DAY)
+    }
+
+    /// Hour of the day, `0..24`.
+    pub fn hour(&self) -> u8 {
+        (self.secs_of_day() / 3600) as u8
+    }
+
+    /// Minute of the hour, `0..60`.
+    pub fn minute(&self) -> u8 {
+        (self.secs_of_day() / 60 % 60) as u8
+    }
+
+    /// Second of the minute, `0..60`.
+    pub fn second(&self) -> u8 {
+        (self.secs_of_day() % 60) as u8
+    }
+
+    /// Year, month (`1..=12`) and day of the month (`1..=31`).
+    pub fn date(&self) -> (i32, u8, u8) {
+        civil_from_days(self.days())
+    }
+
+    /// Day of the week, `0` is Monday and `6` is Sunday.
+    pub fn weekday(&self) -> u8 {
+        // 1970-01-01 was a Thursday.
+        (self.days() + 3).rem_euclid(7) as u8
+    }
+}
+
+/// Short English name of a weekday from [`LocalTime::weekday`].
+pub fn weekday_name(weekday: u8) -> &'static str {
+    ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"][weekday as usize % 7]
+}
+
+/// Short English name of a month, `1` is January.
+pub fn month_name(month: u8) -> &'static str {
+    [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ][(month as usize).saturating_sub(1) % 12]
+}
+
+// Days since 1970-01-01 and back, from
+// <https://howardhinnant.github.io/date_algorithms.html>.
+
+fn days_from_civil(year: i32, month: u8, day: u8) -> i64 {
+    let year = i64::from(year) - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i32, u8, u8) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u8;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u8;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year as i32, month, day)
+}
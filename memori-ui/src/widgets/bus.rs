@@ -1,9 +1,4 @@
-use alloc::{
-    format,
-    string::{String, ToString},
-    vec,
-    vec::Vec,
-};
+use alloc::{format, string::String, vec, vec::Vec};
 use core::cmp::min;
 use ratatui::{
     layout::Direction,
@@ -14,6 +9,8 @@ use ratatui::{
 };
 use serde::{Deserialize, Serialize};
 
+use crate::widgets::MemoriWidgetImpl;
+
 /// Define a widget by its data
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
@@ -31,9 +28,6 @@ impl Bus {
             predictions: prediction.into(),
         }
     }
-    pub fn update(&mut self) {
-        self.predictions.rotate_right(1);
-    }
     pub fn render2(&self, area: Rect, buf: &mut Buffer, num_routes: usize) {
         let predictions = self.predictions.clone();
         let bars: Vec<(String, u64)> = predictions
@@ -51,8 +45,9 @@ impl Bus {
         for i in 0..min(num_routes, bars.len()) {
             let bar = vec![bars[i].clone()];
 
-            let bar_width = min((bars[i].1 as u16) * 2, area.width);
-            let row_y = area.y * ((i + 1) as u16);
+            let bar_width = min((bars[i].1 as u16).saturating_mul(2), area.width);
+            // a bar and its label for every route
+            let row_y = area.y + 2 * i as u16;
 
             if row_y >= area.y + area.height {
                 break;
@@ -84,9 +79,13 @@ impl Default for Bus {
     }
 }
 
-impl Widget for &Bus {
-    fn render(self, area: Rect, buf: &mut Buffer) {
-        let t = truncate(self.stop.as_str(), (area.width - 2) as usize);
+impl MemoriWidgetImpl for Bus {
+    fn display_name(&self) -> &'static str {
+        "Bus"
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        let t = truncate(self.stop.as_str(), area.width.saturating_sub(2) as usize);
         let bus_block = Block::default()
             .title(Line::from(t).centered())
             .borders(Borders::ALL)
@@ -96,14 +95,19 @@ impl Widget for &Bus {
         let outer_inner = bus_block.inner(area);
         bus_block.clone().render(area, buf);
         Text::from("Route  min left").render(
-            Rect::new(1, 1, outer_inner.width - 1, outer_inner.height - 1),
+            Rect::new(
+                outer_inner.x,
+                outer_inner.y,
+                outer_inner.width,
+                outer_inner.height.min(1),
+            ),
             buf,
         );
         let outer_inner = Rect::new(
             outer_inner.x,
             outer_inner.y + 1,
             outer_inner.width,
-            outer_inner.height,
+            outer_inner.height.saturating_sub(1),
         );
         match (outer_inner.width, outer_inner.height) {
             (w, h) if w < 30 && h < 6 => {
@@ -120,13 +124,12 @@ impl Widget for &Bus {
             }
         }
     }
+
+    fn update(&mut self) {
+        self.predictions.rotate_right(1);
+    }
 }
 
 fn truncate(title: &str, max_length: usize) -> String {
-    if title.len() > max_length {
-        let truncated = &title[..max_length];
-        truncated.into()
-    } else {
-        title.to_string()
-    }
+    title.chars().take(max_length).collect()
 }
@@ -0,0 +1,131 @@
+use alloc::{format, string::String, vec, vec::Vec};
+use ratatui::{
+    layout::Rect,
+    prelude::Buffer,
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Borders, Paragraph, Widget, Wrap},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::time::{LocalTime, weekday_name};
+use crate::widgets::MemoriWidgetImpl;
+
+/// The next events on a calendar.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct Calendar {
+    /// Name of the calendar, shown as the title.
+    pub name: String,
+    /// Soonest first.
+    pub events: Vec<CalendarEvent>,
+    /// The time on the device, events that ended before it aren't shown.
+    pub now: LocalTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct CalendarEvent {
+    pub title: String,
+    pub start: LocalTime,
+    pub end: LocalTime,
+    /// Lasts the whole day, the times are left out.
+    pub all_day: bool,
+}
+
+impl Calendar {
+    pub fn new(
+        name: impl Into<String>,
+        events: impl Into<Vec<CalendarEvent>>,
+        now: LocalTime,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            events: events.into(),
+            now,
+        }
+    }
+
+    /// Events that haven't ended yet.
+    fn upcoming(&self) -> impl Iterator<Item = &CalendarEvent> {
+        self.events.iter().filter(|event| event.end > self.now)
+    }
+
+    /// When an event is, like `Today 09:30` or `Tue   all day`.
+    fn when(&self, event: &CalendarEvent) -> String {
+        let day = if event.start <= self.now {
+            "Now"
+        } else if event.start.date() == self.now.date() {
+            "Today"
+        } else {
+            weekday_name(event.start.weekday())
+        };
+
+        if event.all_day {
+            format!("{day:<5} all day")
+        } else {
+            format!(
+                "{day:<5} {:02}:{:02}",
+                event.start.hour(),
+                event.start.minute()
+            )
+        }
+    }
+}
+
+impl MemoriWidgetImpl for Calendar {
+    fn display_name(&self) -> &'static str {
+        "Calendar"
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        let title = if self.name.is_empty() {
+            "Calendar"
+        } else {
+            self.name.as_str()
+        };
+        let block = Block::default()
+            .title(Line::from(title).bold().centered())
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(ratatui::style::Color::White));
+
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if inner.is_empty() {
+            return;
+        }
+
+        if self.upcoming().next().is_none() {
+            Paragraph::new("No upcoming events")
+                .centered()
+                .wrap(Wrap { trim: true })
+                .render(inner, buf);
+            return;
+        }
+
+        // One line per event when there's room next to the time, otherwise the
+        // title goes under it.
+        let compact = inner.width < 30;
+        let lines_per_event = if compact { 2 } else { 1 };
+        let shown = (inner.height as usize / lines_per_event).max(1);
+
+        let mut lines = Vec::new();
+        for event in self.upcoming().take(shown) {
+            let when = self.when(event);
+            if compact {
+                lines.push(Line::from(when).bold());
+                lines.push(Line::from(event.title.as_str()));
+            } else {
+                lines.push(Line::from(format!("{when} {}", event.title)));
+            }
+        }
+
+        for (y, line) in (inner.top()..inner.bottom()).zip(lines) {
+            line.render(Rect::new(inner.x, y, inner.width, 1), buf);
+        }
+    }
+
+    fn set_time(&mut self, now: LocalTime) {
+        self.now = now;
+    }
+}
@@ -1,5 +1,5 @@
-use crate::alloc::string::ToString;
 use alloc::format;
+use alloc::string::String;
 use alloc::vec;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
@@ -8,55 +8,96 @@ use ratatui::symbols::border;
 use ratatui::widgets::{Block, Borders, Widget};
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+use crate::time::{LocalTime, month_name, weekday_name};
+use crate::widgets::MemoriWidgetImpl;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
 pub struct Clock {
-    pub seconds: u32,
-    pub minutes: u32,
-    pub hours: u32,
+    /// The time shown, the device replaces it with its own clock once the host
+    /// has synced it.
+    pub time: LocalTime,
+    pub format: ClockFormat,
 }
 
-impl Default for Clock {
+/// How a [`Clock`] shows the time.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "specta", specta(rename_all = "camelCase"))]
+pub struct ClockFormat {
+    /// `13:05` rather than `1:05 PM`.
+    pub twenty_four_hour: bool,
+    /// Show the date under the time.
+    pub show_date: bool,
+    /// Show the seconds, only useful with a local update frequency of a second.
+    pub show_seconds: bool,
+}
+
+impl Default for ClockFormat {
     fn default() -> Self {
-        Self::new(0, 0, 0)
+        Self {
+            twenty_four_hour: true,
+            show_date: false,
+            show_seconds: false,
+        }
     }
 }
 
 impl Clock {
-    pub fn new(hours: u32, minutes: u32, seconds: u32) -> Self {
+    pub fn new(time: LocalTime) -> Self {
         Clock {
-            seconds,
-            minutes,
-            hours,
+            time,
+            format: ClockFormat::default(),
         }
     }
 
-    pub fn update(&mut self) {
-        self.minutes += 1;
-        if self.minutes == 60 {
-            self.minutes = 0;
-            self.hours += 1;
-        }
-        if self.hours == 24 {
-            self.hours = 0;
+    pub fn with_format(mut self, format: ClockFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    fn time_string(&self) -> String {
+        let (hour, minute, second) = (self.time.hour(), self.time.minute(), self.time.second());
+        let seconds = if self.format.show_seconds {
+            format!(":{second:02}")
+        } else {
+            String::new()
+        };
+
+        if self.format.twenty_four_hour {
+            format!("{hour:02}:{minute:02}{seconds}")
+        } else {
+            let am_pm = if hour < 12 { "AM" } else { "PM" };
+            let hour = match hour % 12 {
+                0 => 12,
+                h => h,
+            };
+            format!("{hour}:{minute:02}{seconds} {am_pm}")
         }
     }
+
+    fn date_string(&self) -> String {
+        let (_, month, day) = self.time.date();
+        format!(
+            "{} {} {day}",
+            weekday_name(self.time.weekday()),
+            month_name(month)
+        )
+    }
 }
 
-impl Widget for &Clock {
-    fn render(self, area: Rect, buf: &mut Buffer) {
-        let mut hours_string = self.hours.to_string();
-        let mut minutes_string = self.minutes.to_string();
+impl MemoriWidgetImpl for Clock {
+    fn display_name(&self) -> &'static str {
+        "Clock"
+    }
 
-        if self.hours < 10 {
-            hours_string = format!("0{}", self.hours);
-        }
-        if self.minutes < 10 {
-            minutes_string = format!("0{}", self.minutes);
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        let mut lines = vec![self.time_string()];
+        if self.format.show_date {
+            lines.push(self.date_string());
         }
 
-        let string = format!("{}:{}", hours_string, minutes_string);
-
         let border_set = border::PLAIN;
 
         let outer_block = Block::default()
@@ -66,12 +107,29 @@ impl Widget for &Clock {
 
         let outer_inner = outer_block.inner(area);
         outer_block.render(area, buf);
+        if outer_inner.is_empty() {
+            return;
+        }
 
-        // Calculate center position
-        let text_len = string.len() as u16;
-        let center_x = outer_inner.x + (outer_inner.width.saturating_sub(text_len)) / 2;
-        let center_y = outer_inner.y + outer_inner.height / 2;
+        // Leave the date out when there's no room for both.
+        lines.truncate(outer_inner.height as usize);
+        let top = outer_inner.y + (outer_inner.height - lines.len() as u16).div_ceil(2);
+
+        for (y, line) in (top..).zip(lines) {
+            let text_len = line.chars().count() as u16;
+            let center_x = outer_inner.x + (outer_inner.width.saturating_sub(text_len)) / 2;
+
+            buf.set_stringn(
+                center_x,
+                y,
+                line,
+                outer_inner.width as usize,
+                Style::default(),
+            );
+        }
+    }
 
-        buf.set_string(center_x, center_y, string, Style::default());
+    fn set_time(&mut self, now: LocalTime) {
+        self.time = now;
     }
 }
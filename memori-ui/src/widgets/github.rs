@@ -12,6 +12,7 @@ use ratatui::widgets::{Bar, BarChart, BarGroup};
 use ratatui::widgets::{Block, Borders, Padding, Paragraph, Widget};
 use serde::{Deserialize, Serialize};
 use crate::alloc::string::ToString;
+use crate::widgets::MemoriWidgetImpl;
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
@@ -43,10 +44,6 @@ impl Github {
             weekday: 0,
         }
     }
-
-    pub fn update(&mut self) {
-        // Update stats here
-    }
 }
 
 impl Default for Github {
@@ -77,8 +74,12 @@ fn build_commit_graph(commits: &[u32; 7], today_weekday: usize) -> BarChart<'sta
         .value_style(Style::default().fg(Color::White))
 }
 
-impl Widget for &Github {
-    fn render(self, area: Rect, buf: &mut Buffer) {
+impl MemoriWidgetImpl for Github {
+    fn display_name(&self) -> &'static str {
+        "GitHub"
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
         let border_set = border::PLAIN;
 
         // Outer "Github" box
@@ -107,8 +108,8 @@ impl Widget for &Github {
             })
             .unwrap_or_default();
         
-        let repo_str = if repo_str.len() >= 10 {
-            format!("{}...", &repo_str[..10])
+        let repo_str = if repo_str.chars().count() >= 10 {
+            format!("{}...", repo_str.chars().take(10).collect::<String>())
         } else {
             repo_str.to_string()
         };
@@ -140,7 +141,7 @@ impl Widget for &Github {
                     .render(chunks[1], buf);
             }
 
-            (w, h) if h < 6 => {
+            (_, h) if h < 6 => {
                 let chunks = Layout::default()
                     .direction(Direction::Horizontal)
                     .constraints([
@@ -180,7 +181,13 @@ impl Widget for &Github {
                     ])
                     .split(outer_inner);
 
-                buf.set_string(chunks[0].x, chunks[0].y, &self.username, Style::default());
+                buf.set_stringn(
+                    chunks[0].x,
+                    chunks[0].y,
+                    &self.username,
+                    chunks[0].width as usize,
+                    Style::default(),
+                );
 
                 // Render graph
                 build_commit_graph(&self.commits, self.weekday).render(chunks[1], buf);
@@ -215,7 +222,13 @@ impl Widget for &Github {
                     ])
                     .split(outer_inner);
 
-                buf.set_string(chunks[0].x, chunks[0].y, &self.username, Style::default());
+                buf.set_stringn(
+                    chunks[0].x,
+                    chunks[0].y,
+                    &self.username,
+                    chunks[0].width as usize,
+                    Style::default(),
+                );
 
                 // Split main content area into left (graph) and right (repo box)
                 let horizontal_chunks = Layout::default()
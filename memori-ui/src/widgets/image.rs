@@ -0,0 +1,70 @@
+use alloc::vec;
+use ratatui::{
+    layout::Rect,
+    prelude::Buffer,
+    widgets::{Paragraph, Widget, Wrap},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::overlay::{Bitmap, Overlay, OverlayColors, cells_to_pixels};
+use crate::widgets::MemoriWidgetImpl;
+
+/// A black and white picture, drawn pixel for pixel over its slot.
+///
+/// Pixels that are on are black, whether the display is in dark mode or not,
+/// so pictures don't turn into negatives.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct Image {
+    pub bitmap: Bitmap,
+}
+
+impl Image {
+    pub fn new(bitmap: Bitmap) -> Self {
+        Self { bitmap }
+    }
+
+    /// The bitmap as big as it fits in `area`, made smaller when it's too big.
+    /// Pictures look best made for the slot they're in, see
+    /// [`SlotSize::pixel_size`](crate::widgets::SlotSize::pixel_size).
+    fn place(&self, area: Rect) -> Option<Overlay> {
+        let pixels = cells_to_pixels(area).size;
+        let (width, height) = (
+            u32::from(self.bitmap.width()),
+            u32::from(self.bitmap.height()),
+        );
+        if width == 0 || height == 0 || pixels.width == 0 || pixels.height == 0 {
+            return None;
+        }
+
+        let factor = width
+            .div_ceil(pixels.width)
+            .max(height.div_ceil(pixels.height));
+        let bitmap = if factor > 1 {
+            self.bitmap.downscale(factor as u16)
+        } else {
+            self.bitmap.clone()
+        };
+        Overlay::fit(bitmap, area, OverlayColors::Ink)
+    }
+}
+
+impl MemoriWidgetImpl for Image {
+    fn display_name(&self) -> &'static str {
+        "Image"
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        // The picture is drawn by the overlay, the cells under it stay blank.
+        if self.place(area).is_none() && !area.is_empty() {
+            Paragraph::new("No image")
+                .centered()
+                .wrap(Wrap { trim: true })
+                .render(area, buf);
+        }
+    }
+
+    fn overlay(&self, area: Rect) -> Option<Overlay> {
+        self.place(area)
+    }
+}
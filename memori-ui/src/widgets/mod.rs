@@ -1,23 +1,96 @@
 mod bus;
+mod calendar;
 mod clock;
 mod github;
+mod image;
 mod name;
 mod pair;
+mod qr;
+mod todo;
 mod twitch;
 mod weather;
 pub use bus::*;
+pub use calendar::*;
 pub use clock::*;
 pub use github::*;
+pub use image::*;
 pub use name::*;
 pub use pair::*;
+pub use qr::*;
+pub use todo::*;
 pub use twitch::*;
 pub use weather::*;
 
 use alloc::vec;
 use alloc::vec::Vec;
-use ratatui::widgets::Widget;
+use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+use crate::overlay::Overlay;
+use crate::{DISPLAY_HEIGHT, DISPLAY_WIDTH, FONT_REGULAR};
+use crate::time::LocalTime;
 use serde::{Deserialize, Serialize};
 
+/// The slots a layout can give a widget.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub enum SlotSize {
+    /// The whole display.
+    Full,
+    /// Half of a vertical split, narrow and tall.
+    Tall,
+    /// Half of a horizontal split, wide and short.
+    Wide,
+    /// A quarter of the display.
+    Quarter,
+}
+
+impl SlotSize {
+    pub const ALL: [SlotSize; 4] = [Self::Full, Self::Tall, Self::Wide, Self::Quarter];
+
+    /// Width and height in pixels of the cells a slot of this size gets. When
+    /// the display doesn't split evenly this is the smaller half.
+    pub fn pixel_size(&self) -> (u16, u16) {
+        let cell = FONT_REGULAR.character_size;
+        let columns = DISPLAY_WIDTH / cell.width as u16;
+        let rows = DISPLAY_HEIGHT / cell.height as u16;
+        let (columns, rows) = match self {
+            Self::Full => (columns, rows),
+            Self::Tall => (columns / 2, rows),
+            Self::Wide => (columns, rows / 2),
+            Self::Quarter => (columns / 2, rows / 2),
+        };
+        (columns * cell.width as u16, rows * cell.height as u16)
+    }
+}
+
+/// What the UI needs from a kind of widget. Implemented by the data of every
+/// [`WidgetKind`], which hands it out through [`WidgetKind::as_widget`].
+pub trait MemoriWidgetImpl {
+    /// Name of the kind shown to people, e.g. in the app's widget picker.
+    fn display_name(&self) -> &'static str;
+
+    /// Draws the widget into `area`, and nowhere else.
+    fn render(&self, area: Rect, buf: &mut Buffer);
+
+    /// Moves the widget along on the device between refreshes from the host,
+    /// like a clock ticking. Does nothing by default.
+    fn update(&mut self) {}
+
+    /// Tells the widget what time it is on the device, right before it's
+    /// drawn. Ignored by default.
+    fn set_time(&mut self, _now: LocalTime) {}
+
+    /// Pixels to draw over `area` after the cells, for what the character grid
+    /// is too coarse for. See [`crate::overlay`], none by default.
+    fn overlay(&self, _area: Rect) -> Option<Overlay> {
+        None
+    }
+
+    /// The slots the widget looks right in, all of them by default.
+    fn preferred_sizes(&self) -> &'static [SlotSize] {
+        &SlotSize::ALL
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Hash)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
 pub struct WidgetId(pub u32);
@@ -126,29 +199,59 @@ impl MemoriWidget {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
-#[cfg_attr(feature = "specta", derive(specta::Type))]
-pub enum WidgetKind {
-    Name(Name),
-    Clock(Clock),
-    Github(Github),
-    Weather(Weather),
-    Bus(Bus),
-    Twitch(Twitch),
-    Pair(Pair),
+/// Declares [`WidgetKind`], its [`WidgetKindTag`] and the dispatch between
+/// them from one list, each kind named after the type holding its data.
+macro_rules! widget_kinds {
+    ($($kind:ident),* $(,)?) => {
+        #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+        #[cfg_attr(feature = "specta", derive(specta::Type))]
+        pub enum WidgetKind {
+            $($kind($kind),)*
+        }
+
+        /// The kind of a widget without any of its data, used to describe which widgets are supported.
+        #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Hash)]
+        #[cfg_attr(feature = "specta", derive(specta::Type))]
+        pub enum WidgetKindTag {
+            $($kind,)*
+        }
+
+        impl WidgetKindTag {
+            /// Every widget kind this version of `memori-ui` knows how to render.
+            pub const ALL: [WidgetKindTag; [$(Self::$kind),*].len()] = [$(Self::$kind),*];
+        }
+
+        impl WidgetKind {
+            pub fn tag(&self) -> WidgetKindTag {
+                match self {
+                    $(Self::$kind(_) => WidgetKindTag::$kind,)*
+                }
+            }
+
+            /// The widget behind this kind.
+            pub fn as_widget(&self) -> &dyn MemoriWidgetImpl {
+                match self {
+                    $(Self::$kind(widget) => widget,)*
+                }
+            }
+
+            /// See [`WidgetKind::as_widget`].
+            pub fn as_widget_mut(&mut self) -> &mut dyn MemoriWidgetImpl {
+                match self {
+                    $(Self::$kind(widget) => widget,)*
+                }
+            }
+        }
+    };
 }
 
+widget_kinds!(
+    Name, Clock, Github, Weather, Bus, Twitch, Pair, Calendar, Todo, Qr, Image,
+);
+
 impl WidgetKind {
     pub fn update(&mut self) {
-        match self {
-            Self::Clock(c) => c.update(),
-            Self::Name(n) => n.update(),
-            Self::Weather(w) => w.update(),
-            Self::Bus(b) => b.update(),
-            Self::Twitch(t) => t.update(),
-            Self::Github(g) => g.update(),
-            Self::Pair(_) => {}
-        }
+        self.as_widget_mut().update();
     }
 }
 
@@ -157,14 +260,6 @@ impl Widget for &MemoriWidget {
     where
         Self: Sized,
     {
-        match &self.kind {
-            WidgetKind::Name(n) => n.render(area, buf),
-            WidgetKind::Clock(c) => c.render(area, buf),
-            WidgetKind::Github(g) => g.render(area, buf),
-            WidgetKind::Weather(w) => w.render(area, buf),
-            WidgetKind::Bus(b) => b.render(area, buf),
-            WidgetKind::Twitch(t) => t.render(area, buf),
-            WidgetKind::Pair(p) => p.render(area, buf),
-        }
+        self.kind.as_widget().render(area, buf);
     }
 }
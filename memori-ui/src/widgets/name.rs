@@ -2,9 +2,11 @@ use alloc::format;
 use alloc::string::String;
 use alloc::vec;
 use log::info;
-use ratatui::{text::Text, widgets::Widget};
+use ratatui::{buffer::Buffer, layout::Rect, text::Text, widgets::Widget};
 use serde::{Deserialize, Serialize};
 
+use crate::widgets::MemoriWidgetImpl;
+
 /// Define a widget by its data
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
@@ -16,16 +18,20 @@ impl Name {
     pub fn new(name: impl Into<String>) -> Self {
         Self { name: name.into() }
     }
-
-    pub fn update(&mut self) {
-        info!("Updated name");
-    }
 }
 
 // impl the function like this
-impl Widget for &Name {
-    fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer) {
+impl MemoriWidgetImpl for Name {
+    fn display_name(&self) -> &'static str {
+        "Name"
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
         let string = format!("Hello {}!", self.name);
         Text::from(string).render(area, buf);
     }
+
+    fn update(&mut self) {
+        info!("Updated name");
+    }
 }
@@ -1,30 +1,64 @@
 use alloc::{string::String, vec, format};
 
 use ratatui::{
-    layout::{Constraint, HorizontalAlignment},
+    buffer::Buffer,
+    layout::{Constraint, HorizontalAlignment, Rect},
     style::Style,
     text::Text,
     widgets::{Block, Paragraph, Widget},
 };
 use serde::{Deserialize, Serialize};
 
+use crate::overlay::Overlay;
+use crate::widgets::qr::{place_qr, qr_bitmap};
+use crate::widgets::{MemoriWidgetImpl, SlotSize};
+
+/// Where scanning the pairing QR code takes you, the app opens it with the
+/// code after it.
+pub const PAIR_LINK: &str = "https://memori/pair?code=";
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
 pub struct Pair {
     code: String,
+    /// Show a QR code of the [`PAIR_LINK`] next to the code.
+    qr: bool,
 }
 
 impl Pair {
     pub fn new(code: String) -> Self {
-        Self { code }
+        Self { code, qr: false }
+    }
+
+    /// Also show a QR code that opens pairing in the app.
+    pub fn with_qr(mut self) -> Self {
+        self.qr = true;
+        self
+    }
+
+    /// The QR code and the cells left for the code next to it, `None` when
+    /// there's no QR code or no room for both.
+    fn place_qr(&self, area: Rect) -> Option<(Overlay, Rect)> {
+        if !self.qr {
+            return None;
+        }
+        let link = format!("{PAIR_LINK}{}", self.code);
+        match place_qr(qr_bitmap(&link)?, area, true)? {
+            (overlay, Some(code_area)) => Some((overlay, code_area)),
+            (_, None) => None,
+        }
     }
 }
-impl Widget for &Pair {
-    fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer)
-    where
-        Self: Sized,
-    {
-        let area = area.centered(Constraint::Ratio(3, 4), Constraint::Ratio(3, 4));
+impl MemoriWidgetImpl for Pair {
+    fn display_name(&self) -> &'static str {
+        "Pair"
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        let area = match self.place_qr(area) {
+            Some((_, code_area)) => code_area,
+            None => area.centered(Constraint::Ratio(3, 4), Constraint::Ratio(3, 4)),
+        };
 
         let text = Text::raw(format!("\n\n{}", self.code.as_str().to_uppercase())).style(Style::new().bold());
 
@@ -37,4 +71,12 @@ impl Widget for &Pair {
             )
             .render(area, buf);
     }
+
+    fn overlay(&self, area: Rect) -> Option<Overlay> {
+        self.place_qr(area).map(|(overlay, _)| overlay)
+    }
+
+    fn preferred_sizes(&self) -> &'static [SlotSize] {
+        &[SlotSize::Full]
+    }
 }
@@ -0,0 +1,148 @@
+use alloc::{string::String, vec};
+use qrcodegen_no_heap::{QrCode, QrCodeEcc, Version};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Rect},
+    prelude::Buffer,
+    style::Stylize,
+    text::Text,
+    widgets::{Paragraph, Widget, Wrap},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::overlay::{Bitmap, Overlay, OverlayColors};
+use crate::widgets::MemoriWidgetImpl;
+
+/// The biggest QR code we make, 57 modules a side. Bigger ones need more pixels
+/// per module than the display has to be scanned.
+const MAX_VERSION: Version = Version::new(10);
+
+/// Light modules around the code, the standard asks for 4 but scanners manage
+/// with less and the display is small.
+const QUIET_ZONE: u16 = 2;
+
+/// Columns next to the code below which the label is left out.
+const MIN_LABEL_WIDTH: u16 = 8;
+
+/// `payload` as a QR code with a quiet zone around it, pixels that are on are
+/// the dark modules. `None` if it's too long.
+pub(crate) fn qr_bitmap(payload: &str) -> Option<Bitmap> {
+    let mut temp = [0; MAX_VERSION.buffer_len()];
+    let mut out = [0; MAX_VERSION.buffer_len()];
+    let qr = QrCode::encode_text(
+        payload,
+        &mut temp,
+        &mut out,
+        QrCodeEcc::Medium,
+        Version::MIN,
+        MAX_VERSION,
+        None,
+        true,
+    )
+    .ok()?;
+
+    let size = qr.size() as u16;
+    let mut bitmap = Bitmap::new(size + 2 * QUIET_ZONE, size + 2 * QUIET_ZONE);
+    for y in 0..size {
+        for x in 0..size {
+            bitmap.set(
+                x + QUIET_ZONE,
+                y + QUIET_ZONE,
+                qr.get_module(i32::from(x), i32::from(y)),
+            );
+        }
+    }
+    Some(bitmap)
+}
+
+/// Where the code goes in `area`, and the cells left for a label next to it.
+/// With no room for a label the code is centered and the label is `None`.
+pub(crate) fn place_qr(
+    bitmap: Bitmap,
+    area: Rect,
+    with_label: bool,
+) -> Option<(Overlay, Option<Rect>)> {
+    let centered = Overlay::fit(bitmap, area, OverlayColors::Ink)?;
+    if !with_label {
+        return Some((centered, None));
+    }
+
+    let cell_width = crate::FONT_REGULAR.character_size.width;
+    let code_width = centered.bounding_box().size.width.div_ceil(cell_width) as u16;
+    let [code_area, _, label_area] = Layout::horizontal([
+        Constraint::Length(code_width),
+        Constraint::Length(1),
+        Constraint::Fill(1),
+    ])
+    .areas(area);
+
+    if label_area.width < MIN_LABEL_WIDTH {
+        return Some((centered, None));
+    }
+    let overlay = Overlay::fit(centered.bitmap, code_area, OverlayColors::Ink)?;
+    Some((overlay, Some(label_area)))
+}
+
+/// Renders `text` vertically centered in `area`.
+pub(crate) fn render_label(text: Text, area: Rect, buf: &mut Buffer) {
+    // Roughly, wrapping at words can take a line more.
+    let height = (text.width() as u16).div_ceil(area.width.max(1)).max(1);
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(area);
+    Paragraph::new(text)
+        .centered()
+        .wrap(Wrap { trim: true })
+        .render(area, buf);
+}
+
+/// A QR code, like for Wi-Fi credentials or a meeting link, with an optional
+/// label next to it.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct Qr {
+    /// What the code encodes.
+    pub payload: String,
+    /// Shown next to the code when there's room.
+    pub label: String,
+}
+
+impl Qr {
+    pub fn new(payload: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            payload: payload.into(),
+            label: label.into(),
+        }
+    }
+
+    fn place(&self, area: Rect) -> Option<(Overlay, Option<Rect>)> {
+        place_qr(qr_bitmap(&self.payload)?, area, !self.label.is_empty())
+    }
+}
+
+impl MemoriWidgetImpl for Qr {
+    fn display_name(&self) -> &'static str {
+        "QR Code"
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+
+        // The code itself is drawn by the overlay, the cells under it stay blank.
+        match self.place(area) {
+            Some((_, Some(label_area))) => {
+                render_label(Text::from(self.label.as_str()).bold(), label_area, buf)
+            }
+            Some((_, None)) => {}
+            None if qr_bitmap(&self.payload).is_none() => {
+                render_label(Text::from("Too much data for a QR code"), area, buf)
+            }
+            None => render_label(Text::from("No room for a QR code"), area, buf),
+        }
+    }
+
+    fn overlay(&self, area: Rect) -> Option<Overlay> {
+        self.place(area).map(|(overlay, _)| overlay)
+    }
+}
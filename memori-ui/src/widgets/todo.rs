@@ -0,0 +1,139 @@
+use alloc::{format, string::String, vec, vec::Vec};
+use ratatui::{
+    layout::Rect,
+    prelude::Buffer,
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Borders, Paragraph, Widget, Wrap},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::widgets::MemoriWidgetImpl;
+
+/// A checklist, scrolled so the selected item is always in view.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct Todo {
+    /// Name of the list, shown as the title.
+    pub name: String,
+    pub items: Vec<TodoItem>,
+    /// Index into `items` of the item the device would check off next.
+    pub selected: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct TodoItem {
+    /// Stays the same when the item is edited or moved, so the host knows
+    /// which one the device checked off.
+    pub id: u32,
+    pub text: String,
+    pub done: bool,
+}
+
+impl TodoItem {
+    pub fn new(id: u32, text: impl Into<String>, done: bool) -> Self {
+        Self {
+            id,
+            text: text.into(),
+            done,
+        }
+    }
+}
+
+impl Todo {
+    pub fn new(name: impl Into<String>, items: impl Into<Vec<TodoItem>>) -> Self {
+        Self {
+            name: name.into(),
+            items: items.into(),
+            selected: 0,
+        }
+    }
+
+    /// Moves the selection down, stopping at the last item.
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.items.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Moves the selection up, stopping at the first item.
+    pub fn select_prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Checks off the selected item, or unchecks it if it was done. Returns the
+    /// item's id and whether it's done now, for telling the host.
+    pub fn toggle_selected(&mut self) -> Option<(u32, bool)> {
+        let item = self.items.get_mut(self.selected)?;
+        item.done = !item.done;
+        Some((item.id, item.done))
+    }
+
+    /// Marks the item with `id` as done or not, returns whether it was found.
+    pub fn set_done(&mut self, id: u32, done: bool) -> bool {
+        match self.items.iter_mut().find(|item| item.id == id) {
+            Some(item) => {
+                item.done = done;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl MemoriWidgetImpl for Todo {
+    fn display_name(&self) -> &'static str {
+        "To-do"
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        let done = self.items.iter().filter(|item| item.done).count();
+        let title = if self.name.is_empty() {
+            "To-do"
+        } else {
+            self.name.as_str()
+        };
+        let block = Block::default()
+            .title(Line::from(title).bold().centered())
+            .title_bottom(Line::from(format!("{done}/{}", self.items.len())).right_aligned())
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(ratatui::style::Color::White));
+
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if inner.is_empty() {
+            return;
+        }
+
+        if self.items.is_empty() {
+            Paragraph::new("Nothing to do")
+                .centered()
+                .wrap(Wrap { trim: true })
+                .render(inner, buf);
+            return;
+        }
+
+        // Scroll just far enough that the selected item is in view, with the
+        // one after it too so it's clear the list goes on.
+        let selected = self.selected.min(self.items.len() - 1);
+        let first = (selected + 2)
+            .min(self.items.len())
+            .saturating_sub(inner.height as usize);
+
+        for (y, (index, item)) in
+            (inner.top()..inner.bottom()).zip(self.items.iter().enumerate().skip(first))
+        {
+            let marker = if index == selected { ">" } else { " " };
+            let check = if item.done { "[x]" } else { "[ ]" };
+            let mut line = Line::from(format!("{marker}{check} {}", item.text));
+            if item.done {
+                line = line.dim();
+            }
+            if index == selected {
+                line = line.bold();
+            }
+            line.render(Rect::new(inner.x, y, inner.width, 1), buf);
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use alloc::{format, string::String, vec, vec::Vec};
 use ratatui::{
-    layout::{Alignment, Rect},
+    layout::{Alignment, Constraint, Layout, Rect},
     prelude::Buffer,
     style::{Color, Style, Stylize},
     text::{Line, Span, Text},
@@ -8,6 +8,8 @@ use ratatui::{
 };
 use serde::{Deserialize, Serialize};
 
+use crate::widgets::MemoriWidgetImpl;
+
 /// Define a widget by its data
 #[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
@@ -27,9 +29,6 @@ impl Twitch {
             live_channels: live_channels.into(),
         }
     }
-    pub fn update(&mut self) {
-        self.live_channels.rotate_right(1);
-    }
 }
 
 impl Default for Twitch {
@@ -42,8 +41,12 @@ impl Default for Twitch {
 }
 
 // impl the function like this
-impl Widget for &Twitch {
-    fn render(self, area: Rect, buf: &mut Buffer) {
+impl MemoriWidgetImpl for Twitch {
+    fn display_name(&self) -> &'static str {
+        "Twitch"
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
         let twitch_block = Block::default()
             .title(
                 Line::from(format!("Twitch user {}", self.username))
@@ -94,23 +97,31 @@ impl Widget for &Twitch {
             Text::from("No channels you follow are currently live").render(outer_inner, buf);
             return;
         }
+        let [top, bottom] = Layout::vertical([Constraint::Fill(1); 2]).areas(outer_inner);
+        let [left, right] = Layout::horizontal([Constraint::Fill(1); 2]).areas(outer_inner);
         match (outer_inner.width, outer_inner.height) {
             (w, h) if w < 30 && h < 6 => {
                 // small
                 p1.render(outer_inner, buf);
             }
-            (w, h) if w < 30 => {
+            (w, _) if w < 30 => {
                 // tall
-                p1.render(Rect::new(outer_inner.x, outer_inner.y, w, h / 2), buf);
-                p2.render(Rect::new(outer_inner.x, outer_inner.y + 6, w, h / 2), buf);
+                p1.render(top, buf);
+                p2.render(bottom, buf);
             }
-            (w, h) if h < 6 => {
+            (_, h) if h < 6 => {
                 // wide
-                p1.render(Rect::new(outer_inner.x, outer_inner.y, w / 2, h), buf);
-                p2.render(Rect::new(w / 2, outer_inner.y, w / 2, h), buf);
+                p1.render(left, buf);
+                p2.render(right, buf);
             }
-            (w, h) => {
+            (_, _) => {
                 // full
+                // as wide as the logo, it falls apart when wrapped
+                let [icon_area, streams] =
+                    Layout::horizontal([Constraint::Length(15), Constraint::Fill(1)])
+                        .areas(outer_inner);
+                let [top, bottom] = Layout::vertical([Constraint::Fill(1); 2]).areas(streams);
+
                 let icon_lines = TwitchIcon::Logo.to_ascii();
                 let icon_text =
                     Text::from(icon_lines.into_iter().map(Line::from).collect::<Vec<_>>())
@@ -118,12 +129,16 @@ impl Widget for &Twitch {
                 let icon = Paragraph::new(icon_text)
                     .alignment(Alignment::Center)
                     .wrap(Wrap { trim: false });
-                icon.render(Rect::new(outer_inner.x, outer_inner.y, w / 3, h), buf);
-                p1.render(Rect::new(w / 2, outer_inner.y, w, h / 2), buf);
-                p2.render(Rect::new(w / 2, outer_inner.y + 6, w, h / 2), buf);
+                icon.render(icon_area, buf);
+                p1.render(top, buf);
+                p2.render(bottom, buf);
             }
         }
     }
+
+    fn update(&mut self) {
+        self.live_channels.rotate_right(1);
+    }
 }
 
 enum TwitchIcon {
@@ -10,6 +10,8 @@ use ratatui::{
 };
 use serde::{Deserialize, Serialize};
 
+use crate::widgets::MemoriWidgetImpl;
+
 /// Define a widget by its data
 #[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
@@ -43,9 +45,6 @@ impl Weather {
             description: description.into(),
         }
     }
-    pub fn update(&mut self) {
-        info!("Updated name");
-    }
 }
 
 impl Default for Weather {
@@ -63,8 +62,12 @@ impl Default for Weather {
 }
 
 // impl the function like this
-impl Widget for &Weather {
-    fn render(self, area: Rect, buf: &mut Buffer) {
+impl MemoriWidgetImpl for Weather {
+    fn display_name(&self) -> &'static str {
+        "Weather"
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
         let newline = Line::from("");
         let temp = Line::from(format!("Temp: {}°F", self.temp.clone()));
         let clouds = Line::from(format!("Clouds: {}%", self.clouds.clone()));
@@ -84,41 +87,51 @@ impl Widget for &Weather {
             .alignment(Alignment::Center)
             .wrap(Wrap { trim: false });
 
+        // Everything is placed relative to the inside of the block and clipped
+        // to it, so nothing ends up in the neighbouring widget.
+        let (x, y) = (outer_inner.x, outer_inner.y);
         match (outer_inner.width, outer_inner.height) {
             (w, h) if w < 30 && h < 6 => {
                 // small
                 let temp = Paragraph::new(vec![temp])
                     .alignment(Alignment::Center)
                     .wrap(Wrap { trim: false });
-                icon.render(Rect::new(outer_inner.x, outer_inner.y, w / 3, h), buf);
-                temp.render(Rect::new((w / 3) + 2, outer_inner.y, w / 2, h), buf);
+                icon.render(Rect::new(x, y, w / 3, h), buf);
+                temp.render(
+                    Rect::new(x + w / 3 + 1, y, w / 2, h).intersection(outer_inner),
+                    buf,
+                );
             }
             (w, h) if w < 30 => {
                 // tall
                 let temp = Paragraph::new(vec![temp])
                     .alignment(Alignment::Center)
                     .wrap(Wrap { trim: true });
-                icon.render(Rect::new(outer_inner.x, outer_inner.y + 1, w, h / 2), buf);
-                temp.render(Rect::new(outer_inner.x, outer_inner.y + 5, w, h / 2), buf);
+                icon.render(Rect::new(x, y + 1, w, h / 2).intersection(outer_inner), buf);
+                temp.render(Rect::new(x, y + 5, w, h / 2).intersection(outer_inner), buf);
             }
             (w, h) if h < 6 => {
                 // wide
                 let temp = Paragraph::new(vec![temp])
                     .alignment(Alignment::Center)
                     .wrap(Wrap { trim: true });
-                icon.render(Rect::new(outer_inner.x, outer_inner.y, w / 3, h), buf);
-                temp.render(Rect::new(w / 3, outer_inner.y, w / 3, h), buf);
+                icon.render(Rect::new(x, y, w / 3, h), buf);
+                temp.render(Rect::new(x + w / 3, y, w / 3, h), buf);
             }
             (w, h) => {
                 // full
                 let temp = Paragraph::new(vec![newline, temp, wind, rain, clouds, humidity])
                     .alignment(Alignment::Center)
                     .wrap(Wrap { trim: true });
-                icon.render(Rect::new(outer_inner.x, outer_inner.y + 2, w / 2, h), buf);
-                temp.render(Rect::new(w / 2, outer_inner.y, w / 2, h), buf);
+                icon.render(Rect::new(x, y + 2, w / 2, h).intersection(outer_inner), buf);
+                temp.render(Rect::new(x + w / 2, y, w - w / 2, h), buf);
             }
         }
     }
+
+    fn update(&mut self) {
+        info!("Updated name");
+    }
 }
 
 enum WeatherIcon {
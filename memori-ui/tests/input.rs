@@ -0,0 +1,122 @@
+use memori_ui::MemoriState;
+use memori_ui::input::{
+    Button, DEBOUNCE_MS, InputEffect, InputEvent, LONG_PRESS_MS, Press, PressDetector,
+};
+use memori_ui::layout::MemoriLayout;
+use memori_ui::widgets::{MemoriWidget, Name, Todo, TodoItem, WidgetId, WidgetKind};
+
+/// Feeds `readings` of `(down, now_ms)` to a fresh detector, collects the presses.
+fn presses(readings: &[(bool, u64)]) -> Vec<Press> {
+    let mut detector = PressDetector::new();
+    readings
+        .iter()
+        .filter_map(|&(down, now)| detector.update(down, now))
+        .collect()
+}
+
+#[test]
+fn bounces_are_ignored() {
+    // Chatter on the way down and up, only one short press in between.
+    let readings = [
+        (true, 0),
+        (false, 5),
+        (true, 10),
+        (true, 10 + DEBOUNCE_MS),
+        (false, 200),
+        (true, 205),
+        (false, 210),
+        (false, 210 + DEBOUNCE_MS),
+    ];
+    assert_eq!(presses(&readings), [Press::Short]);
+
+    // Too short to be anything.
+    assert_eq!(
+        presses(&[(true, 0), (false, DEBOUNCE_MS - 1), (false, 500)]),
+        []
+    );
+}
+
+#[test]
+fn holding_is_a_long_press_right_away() {
+    let mut detector = PressDetector::new();
+    assert_eq!(detector.update(true, 0), None);
+    assert_eq!(detector.update(true, LONG_PRESS_MS - 1), None);
+    assert_eq!(detector.update(true, LONG_PRESS_MS), Some(Press::Long));
+    assert_eq!(detector.update(true, 2 * LONG_PRESS_MS), None);
+
+    // Letting go after a long press isn't a short one too.
+    assert_eq!(detector.update(false, 3 * LONG_PRESS_MS), None);
+    assert_eq!(
+        detector.update(false, 3 * LONG_PRESS_MS + DEBOUNCE_MS),
+        None
+    );
+    assert!(detector.is_idle());
+}
+
+fn two_frames(with_list: bool) -> MemoriState {
+    let name = MemoriWidget::with_never_update_frequency(0, WidgetKind::Name(Name::new("Ada")));
+    let list = MemoriWidget::with_never_update_frequency(
+        1,
+        WidgetKind::Todo(Todo::new(
+            "",
+            vec![
+                TodoItem::new(7, "Eggs", false),
+                TodoItem::new(8, "Milk", false),
+            ],
+        )),
+    );
+    let second = if with_list {
+        MemoriLayout::VSplit {
+            left: WidgetId(0),
+            right: WidgetId(1),
+        }
+    } else {
+        MemoriLayout::Full(WidgetId(0))
+    };
+    MemoriState::new(
+        0,
+        [name, list],
+        vec![MemoriLayout::Full(WidgetId(0)), second],
+        0,
+    )
+}
+
+#[test]
+fn buttons_flip_frames_and_check_off_todos() {
+    let mut state = two_frames(true);
+    let press = |button, press| InputEvent::new(button, press);
+
+    assert_eq!(
+        state.handle_input(press(Button::Select, Press::Short)),
+        InputEffect::Refresh(vec![WidgetId(0)])
+    );
+    assert_eq!(
+        state.handle_input(press(Button::Prev, Press::Short)),
+        InputEffect::Redraw
+    );
+    assert_eq!(state.active_frame_idx(), 1);
+
+    // The second frame has the list, held buttons move through it.
+    assert_eq!(
+        state.handle_input(press(Button::Next, Press::Long)),
+        InputEffect::Redraw
+    );
+    assert_eq!(
+        state.handle_input(press(Button::Select, Press::Short)),
+        InputEffect::TodoToggled {
+            widget_id: WidgetId(1),
+            item_id: 8,
+            done: true,
+        }
+    );
+    assert_eq!(
+        state.handle_input(press(Button::Select, Press::Long)),
+        InputEffect::Refresh(vec![WidgetId(0), WidgetId(1)])
+    );
+
+    let mut state = two_frames(false);
+    assert_eq!(
+        state.handle_input(press(Button::Next, Press::Long)),
+        InputEffect::None
+    );
+}
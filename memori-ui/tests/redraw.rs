@@ -0,0 +1,24 @@
+use memori_ui::{Memori, MemoriState};
+use ratatui::Terminal;
+use ratatui::backend::{Backend, TestBackend};
+
+#[test]
+fn redraw_all_repaints_what_the_display_lost() {
+    let mut memori = Memori::new(Terminal::new(TestBackend::new(42, 10)).unwrap());
+    let state = MemoriState::default();
+    memori.update(&state).unwrap();
+    let drawn = memori.backend_mut().buffer().clone();
+
+    // Like the device turning its display, what's on it is gone.
+    memori.backend_mut().clear().unwrap();
+    memori.update(&state).unwrap();
+    assert_ne!(
+        *memori.backend_mut().buffer(),
+        drawn,
+        "nothing changed, nothing drawn"
+    );
+
+    memori.redraw_all().unwrap();
+    memori.update(&state).unwrap();
+    assert_eq!(*memori.backend_mut().buffer(), drawn);
+}
@@ -0,0 +1,38 @@
+use memori_ui::time::LocalTime;
+
+#[test]
+fn utc_offset_moves_the_local_time() {
+    // 2024-02-29 23:30:15 UTC
+    let utc = 1_709_249_415;
+
+    let pacific = LocalTime::from_utc(utc, -8 * 60);
+    assert_eq!(pacific.date(), (2024, 2, 29));
+    assert_eq!(
+        (pacific.hour(), pacific.minute(), pacific.second()),
+        (15, 30, 15)
+    );
+
+    let india = LocalTime::from_utc(utc, 5 * 60 + 30);
+    assert_eq!(india.date(), (2024, 3, 1));
+    assert_eq!((india.hour(), india.minute()), (5, 0));
+    assert_eq!(india.weekday(), 4);
+}
+
+#[test]
+fn dates_roundtrip() {
+    for (year, month, day) in [(1970, 1, 1), (1969, 12, 31), (2000, 2, 29), (2100, 3, 1)] {
+        let time = LocalTime::from_ymd_hms(year, month, day, 12, 0, 0);
+        assert_eq!(time.date(), (year, month, day));
+        assert_eq!(
+            time.add_secs(12 * 60 * 60).date(),
+            time.add_secs(86_400).date()
+        );
+    }
+
+    assert_eq!(
+        LocalTime::from_ymd_hms(1970, 1, 1, 0, 0, 0),
+        LocalTime::default()
+    );
+    // 2026-10-18 is a Sunday.
+    assert_eq!(LocalTime::from_ymd_hms(2026, 10, 18, 0, 0, 0).weekday(), 6);
+}
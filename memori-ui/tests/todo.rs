@@ -0,0 +1,41 @@
+use memori_ui::widgets::{Todo, TodoItem};
+
+fn list() -> Todo {
+    Todo::new(
+        "Chores",
+        vec![
+            TodoItem::new(10, "Dishes", false),
+            TodoItem::new(20, "Laundry", true),
+        ],
+    )
+}
+
+#[test]
+fn selection_stays_on_the_list() {
+    let mut todo = list();
+    todo.select_prev();
+    assert_eq!(todo.selected, 0);
+
+    todo.select_next();
+    todo.select_next();
+    assert_eq!(todo.selected, 1);
+}
+
+#[test]
+fn toggling_reports_the_item_id() {
+    let mut todo = list();
+    assert_eq!(todo.toggle_selected(), Some((10, true)));
+    todo.select_next();
+    assert_eq!(todo.toggle_selected(), Some((20, false)));
+    assert!(todo.items.iter().all(|item| item.done != (item.id == 20)));
+
+    assert_eq!(Todo::new("", vec![]).toggle_selected(), None);
+}
+
+#[test]
+fn set_done_finds_items_by_id() {
+    let mut todo = list();
+    assert!(todo.set_done(20, false));
+    assert!(!todo.items[1].done);
+    assert!(!todo.set_done(30, true));
+}
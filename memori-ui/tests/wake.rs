@@ -0,0 +1,55 @@
+use memori_ui::MemoriState;
+use memori_ui::layout::MemoriLayout;
+use memori_ui::widgets::{MemoriWidget, Name, UpdateFrequency, WidgetId, WidgetKind};
+
+fn name(id: u32, remote: UpdateFrequency, local: UpdateFrequency) -> MemoriWidget {
+    MemoriWidget::new(
+        WidgetId(id),
+        WidgetKind::Name(Name::new("Ada")),
+        remote,
+        local,
+    )
+}
+
+#[test]
+fn wakes_for_the_soonest_update() {
+    let frames = vec![
+        MemoriLayout::Full(WidgetId(0)),
+        MemoriLayout::Full(WidgetId(1)),
+    ];
+
+    let still = MemoriState::new(
+        0,
+        [
+            name(0, UpdateFrequency::Never, UpdateFrequency::Never),
+            name(1, UpdateFrequency::Never, UpdateFrequency::Never),
+        ],
+        frames.clone(),
+        0,
+    );
+    assert_eq!(still.wake_period(true), None);
+
+    let busy = MemoriState::new(
+        0,
+        [
+            name(0, UpdateFrequency::Hours(1), UpdateFrequency::Never),
+            name(1, UpdateFrequency::Never, UpdateFrequency::Minutes(10)),
+        ],
+        frames,
+        900,
+    );
+    assert_eq!(busy.wake_period(false), Some(600));
+
+    // Frames only count while they rotate, and there is more than one.
+    let rotating = MemoriState::new(
+        0,
+        [
+            name(0, UpdateFrequency::Hours(1), UpdateFrequency::Never),
+            name(1, UpdateFrequency::Never, UpdateFrequency::Never),
+        ],
+        busy.frames().to_vec(),
+        300,
+    );
+    assert_eq!(rotating.wake_period(true), Some(300));
+    assert_eq!(rotating.wake_period(false), Some(3600));
+}
@@ -0,0 +1,311 @@
+//! Every widget drawn in every slot a layout can give it, compared against the
+//! snapshots in `tests/snapshots`.
+//!
+//! After changing how a widget looks on purpose, run the tests with
+//! `UPDATE_SNAPSHOTS=1` and review the new snapshots like any other change.
+
+use std::{env, fs, path::PathBuf};
+
+use memori_ui::layout::MemoriLayout;
+use memori_ui::overlay::Bitmap;
+use memori_ui::time::LocalTime;
+use memori_ui::widgets::{
+    Bus, Calendar, CalendarEvent, Clock, ClockFormat, Github, Image, MemoriWidget, Name, Pair, Qr,
+    Todo, TodoItem, Twitch, Weather, WidgetId, WidgetKind,
+};
+use memori_ui::{DISPLAY_HEIGHT, DISPLAY_WIDTH, FONT_REGULAR, MemoriState};
+use ratatui::{
+    Terminal,
+    backend::TestBackend,
+    buffer::{Buffer, Cell},
+    layout::Rect,
+    widgets::Widget,
+};
+
+/// Size of the terminal on the device, in cells.
+fn terminal_size() -> (u16, u16) {
+    let font = FONT_REGULAR.character_size;
+    (
+        DISPLAY_WIDTH / font.width as u16,
+        DISPLAY_HEIGHT / font.height as u16,
+    )
+}
+
+fn widgets() -> Vec<(&'static str, WidgetKind)> {
+    let github = Github {
+        username: "octocat".into(),
+        repo: Some("octocat/hello-world".into()),
+        open_issues: 12,
+        open_prs: 3,
+        stars: 1500,
+        notifications: 4,
+        commits: [1, 4, 0, 2, 7, 3, 5],
+        weekday: 2,
+    };
+    let morning = LocalTime::from_ymd_hms(2026, 10, 18, 9, 5, 0);
+    let afternoon = LocalTime::from_ymd_hms(2026, 10, 18, 13, 5, 42);
+    let event = |title: &str, start: LocalTime, minutes: i64, all_day: bool| CalendarEvent {
+        title: title.into(),
+        start,
+        end: start.add_secs(minutes * 60),
+        all_day,
+    };
+    let events = vec![
+        event("Breakfast", morning.add_secs(-3600), 30, false),
+        event("Standup", morning.add_secs(25 * 60), 15, false),
+        event("Design review", afternoon, 60, false),
+        event(
+            "Hackathon",
+            LocalTime::from_ymd_hms(2026, 10, 19, 0, 0, 0),
+            24 * 60,
+            true,
+        ),
+        event(
+            "Dentist",
+            LocalTime::from_ymd_hms(2026, 10, 22, 16, 30, 0),
+            45,
+            false,
+        ),
+    ];
+    let mut groceries = Todo::new(
+        "Groceries",
+        vec![
+            TodoItem::new(1, "Eggs", true),
+            TodoItem::new(2, "Milk", true),
+            TodoItem::new(3, "Bread", false),
+            TodoItem::new(4, "Coffee beans", false),
+            TodoItem::new(5, "Apples", false),
+            TodoItem::new(6, "Rice", false),
+            TodoItem::new(7, "Spinach", false),
+            TodoItem::new(8, "Tofu", false),
+        ],
+    );
+    // far enough down that the smaller slots have to scroll
+    groceries.selected = 6;
+    // bigger than a full slot, so it's scaled down
+    let mut checkers = Bitmap::new(320, 160);
+    for y in 0..160 {
+        for x in 0..320 {
+            checkers.set(x, y, (x / 16 + y / 16) % 2 == 0);
+        }
+    }
+
+    vec![
+        ("name", WidgetKind::Name(Name::new("Surendra"))),
+        ("clock", WidgetKind::Clock(Clock::new(morning))),
+        (
+            "clock_12h_date",
+            WidgetKind::Clock(Clock::new(afternoon).with_format(ClockFormat {
+                twenty_four_hour: false,
+                show_date: true,
+                show_seconds: true,
+            })),
+        ),
+        ("github", WidgetKind::Github(github)),
+        (
+            "github_no_repo",
+            WidgetKind::Github(Github::new("octocat".into(), None)),
+        ),
+        (
+            "weather",
+            WidgetKind::Weather(Weather::new(
+                "Santa Cruz",
+                "61",
+                "80",
+                "7",
+                "0.4",
+                "90",
+                "Rain",
+            )),
+        ),
+        (
+            "bus",
+            WidgetKind::Bus(Bus::new(
+                "Science Hill",
+                vec![
+                    ("10".into(), "Metro Center".into(), 4),
+                    ("15".into(), "Bay & High".into(), 9),
+                    ("20".into(), "Delaware & Swift".into(), 13),
+                ],
+            )),
+        ),
+        (
+            "twitch",
+            WidgetKind::Twitch(Twitch::new(
+                "viewer",
+                vec![
+                    (
+                        "streamer".into(),
+                        "Celeste".into(),
+                        "any% practice".into(),
+                        "1234".into(),
+                    ),
+                    (
+                        "speedy".into(),
+                        "Tetris".into(),
+                        "level 29".into(),
+                        "87".into(),
+                    ),
+                ],
+            )),
+        ),
+        (
+            "twitch_offline",
+            WidgetKind::Twitch(Twitch::new("viewer", vec![])),
+        ),
+        ("pair", WidgetKind::Pair(Pair::new("ab12".into()))),
+        (
+            "pair_qr",
+            WidgetKind::Pair(Pair::new("ab12".into()).with_qr()),
+        ),
+        (
+            "qr",
+            WidgetKind::Qr(Qr::new("WIFI:T:WPA;S:memori;P:hunter22;;", "Guest Wi-Fi")),
+        ),
+        (
+            "qr_unlabeled",
+            WidgetKind::Qr(Qr::new("https://example.com/standup", "")),
+        ),
+        ("image", WidgetKind::Image(Image::new(checkers))),
+        ("image_empty", WidgetKind::Image(Image::default())),
+        (
+            "calendar",
+            WidgetKind::Calendar(Calendar::new("Work", events, morning)),
+        ),
+        (
+            "calendar_empty",
+            WidgetKind::Calendar(Calendar::new("", vec![], morning)),
+        ),
+        ("todo", WidgetKind::Todo(groceries)),
+        ("todo_empty", WidgetKind::Todo(Todo::new("", vec![]))),
+    ]
+}
+
+/// The widget in every slot of the shapes giving it the full display, a half
+/// or a quarter.
+fn layouts() -> Vec<(&'static str, MemoriLayout)> {
+    let id = WidgetId(0);
+    vec![
+        ("full", MemoriLayout::Full(id)),
+        (
+            "vsplit",
+            MemoriLayout::VSplit {
+                left: id,
+                right: id,
+            },
+        ),
+        (
+            "hsplit",
+            MemoriLayout::HSplit {
+                top: id,
+                bottom: id,
+            },
+        ),
+        (
+            "fourths",
+            MemoriLayout::Fourths {
+                top_left: id,
+                top_right: id,
+                bottom_left: id,
+                bottom_right: id,
+            },
+        ),
+    ]
+}
+
+fn buffer_to_string(buf: &Buffer) -> String {
+    let area = buf.area;
+    let mut text = String::new();
+    for y in area.top()..area.bottom() {
+        let line: String = (area.left()..area.right())
+            .map(|x| buf[(x, y)].symbol())
+            .collect();
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+    text
+}
+
+fn render(kind: WidgetKind, layout: MemoriLayout) -> String {
+    let widget = MemoriWidget::with_never_update_frequency(0, kind);
+    let state = MemoriState::new(0, [widget], vec![layout], 0);
+
+    let (width, height) = terminal_size();
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal
+        .draw(|f| f.render_widget(&state, f.area()))
+        .unwrap();
+
+    buffer_to_string(terminal.backend().buffer())
+}
+
+#[test]
+fn widgets_match_snapshots() {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots");
+    let update = env::var_os("UPDATE_SNAPSHOTS").is_some();
+
+    let mut mismatched = Vec::new();
+    for (widget_name, kind) in widgets() {
+        for (layout_name, layout) in layouts() {
+            let rendered = render(kind.clone(), layout);
+            let path = dir.join(format!("{widget_name}_{layout_name}.txt"));
+
+            if update {
+                fs::create_dir_all(&dir).unwrap();
+                fs::write(&path, &rendered).unwrap();
+                continue;
+            }
+
+            let expected = fs::read_to_string(&path).unwrap_or_default();
+            if rendered != expected {
+                eprintln!("{} changed, now renders as:\n{rendered}", path.display());
+                mismatched.push(path);
+            }
+        }
+    }
+
+    assert!(
+        mismatched.is_empty(),
+        "{} snapshot(s) don't match, rerun with UPDATE_SNAPSHOTS=1 if that's intended: {mismatched:?}",
+        mismatched.len()
+    );
+}
+
+#[test]
+fn widgets_stay_inside_their_area() {
+    let outside = Cell::new("~");
+    let sizes = [
+        (0, 0),
+        (1, 1),
+        (2, 2),
+        (3, 3),
+        (5, 2),
+        (10, 3),
+        (21, 4),
+        (21, 5),
+        (21, 9),
+        (42, 4),
+        (42, 9),
+    ];
+
+    for (widget_name, kind) in widgets() {
+        let widget = MemoriWidget::with_never_update_frequency(0, kind);
+
+        for (width, height) in sizes {
+            // Room around the widget on every side to catch it drawing there.
+            let mut buf = Buffer::filled(Rect::new(0, 0, width + 6, height + 4), outside.clone());
+            let area = Rect::new(3, 2, width, height);
+            (&widget).render(area, &mut buf);
+
+            for (i, cell) in buf.content.iter().enumerate() {
+                let (x, y) = buf.pos_of(i);
+                if !area.contains((x, y).into()) {
+                    assert_eq!(
+                        cell, &outside,
+                        "{widget_name} at {width}x{height} drew outside its area at ({x}, {y})"
+                    );
+                }
+            }
+        }
+    }
+}